@@ -15,6 +15,16 @@ save-mode = Save mode
 error = Error
 unknown = Unknown
 update = Update
+install-pinned = Install pinned version
+channel = Channel
+channel-tooltip = Which version of this source to follow. Pinning to a tag or commit keeps it there until you change it.
+channel-latest = Latest release
+channel-prerelease = Latest prerelease
+channel-tag = Tag
+channel-commit = Commit
+channel-branch = Branch
+channel-ref = Ref
+channel-apply = Apply
 create-arg = Create new { $arg }
 not-create-arg = Use existing { $arg }
 description = Description
@@ -66,8 +76,10 @@ no-trigger = No trigger position provided.
 no-category = No category provided.
 map-id-wrong = Map ID incorrect.
 no-positions = No marker positions provided.
+agent-marker-blank = Agent markers can't be blank.
 validation-fail = Validation failed due to:
 filename-empty = No filename provided.
+filename-outside = The filename has to stay inside the markers folder.
 count = Count
 actions = Actions
 
@@ -112,12 +124,35 @@ open-error = { error } opening { $kind }: { $path }
 data-sources-tab = Data Sources
 checking-for-updates = Checking for updates!
 check-for-updates = Check for updates
-check-for-updates-tooltip = Check for updates to any data sources. We don't do this automatically to respect your choice on whether or not to make network requests.
+check-for-updates-tooltip = Check for updates to any data sources right now.
 checked-for-updates-last = Last checked for updates at: { $time }
+updates-available = { $count } data source(s) can be updated.
+update-check = Check for updates
+update-check-tooltip = When to check for updates by yourself. This is off unless you turn it on, to respect your choice on whether or not to make network requests.
+update-check-hours = Hours between checks
+update-action = When there's an update
+update-action-tooltip = Whether to just let you know, or to download the update and reload straight away. Only sources you've already installed are updated automatically.
+github-token = GitHub token
+github-token-tooltip = A GitHub personal access token, with no scopes, raises the rate limit from 60 to 5000 requests an hour. It's kept in the addon's settings.json.
+github-token-set = Save token
+github-token-clear = Forget token
+release-notes = Release notes for { $version }
+release-published = Published { $time }
+release-notes-empty = This release didn't come with any notes.
+timer-changes = Last update: { $added } added, { $removed } removed, { $changed } changed
+timer-changes-versions = From { $from } to { $to }
+timer-changes-none = No timers were added, removed or changed.
+timer-changes-nothing = nothing
+pack-name = { $name } { $version }
+pack-needs-newer = Needs TaimiHUD { $version } or newer, so it hasn't been loaded.
+pack-default-disabled = { $count } timer(s) are off unless you turn them on.
 reload-data-sources = Reload data sources
 reload-data-sources-tooltip = Reload items from currently installed data sources. Useful if you have changed the files within them!
 
 remote = Remote
+source-kind = A { $kind } source
+source-contents = Contents
+pathing = Pathing
 update-status = Update Status
 actions = Actions
 version-installed = Installed version: { $version }
@@ -127,6 +162,8 @@ update-not-required = Update not required; up to date!
 update-available = New version available: { $version }!
 update-error = { error } updating: { $error }!
 attempt-update = Attempt to update anyway?
+roll-back = Roll back to { $version }
+roll-back-tooltip = Put the previously installed version back. You can roll forward again the same way.
 settings-unloaded = Settings have not yet loaded!
 
 ## Info tab
@@ -162,6 +199,8 @@ current-scaling-factor-multiple = Current { scaling-factor } as multiple of ft p
 scaling-factor-reset = { reset } detected { scaling-factor }
 no-file-associated = Couldn't find associated file
 markers-arg = { markers }: { $count }
+agent-markers-arg = Agent { markers }: { $count }
+agent-marker-on = { $marker } on { $target }
 marker-type = { marker } Type
 local-header = Local (XYZ)
 map-header = Map (XY)
@@ -174,6 +213,35 @@ no-markers-for-map = No markers found for current map.
 cant-place-markers = Can't place
 autoplacement-disable = Disable auto-placement
 autoplacement-enable = Enable auto-placement
+share-copy = Copy share code
+placement-result = Last placement: { $failed } of { $count } failed
+placement-placed = Placed
+placement-unverified = Placed, but RTAPI isn't there to check it landed
+placement-too-far = Too far away to place
+placement-out-of-view = Couldn't drag the map to it
+placement-missed = Didn't land where it should have
+share-category = Copy share code for category
+
+## Import markers window
+
+import = Import
+import-markers = { import } { markers }
+share-code = Share code
+share-code-read = Read share code
+paste = Paste
+category-arg = { category }: { $category }
+import-no-map-id = Has no map ID, so it can't be imported.
+import-duplicate = Already loaded, importing it will make a duplicate.
+
+## Convert markers window
+
+convert = Convert
+convert-markers = { convert } { markers }
+convert-explanation = Merges the chosen files into a new one of whichever format.
+convert-dedupe = Remove duplicate { marker-set }s
+convert-set-aside = Rename the chosen files to .markers.bak, so they stop loading alongside it
+convert-no-files = No files chosen.
+convert-no-filetype = No file type chosen.
 
 ## Markers window
 clear-markers = { clear } { markers }
@@ -194,7 +262,11 @@ position-get = Get current { position }
 set-manually = Set manually
 manual-position = Manual { position }
 set-manually-save = { save } manual { position }
-trigger-explanation = A trigger for a marker set is a 15m radius sphere with its centre at the trigger location.
+trigger-explanation = A trigger for a marker set is a sphere with its centre at the trigger location, 15m in radius unless changed.
+trigger-radius = Trigger radius
+trigger-area-set = This marker set uses a custom trigger area from its file.
+require-entry = Require entry
+require-departure = Require departure
 
 ## Timer tab
 
@@ -203,9 +275,14 @@ timer-tab = { timers }
 source-arg = Source: { $source }
 source-adhoc = Source: Ad-hoc
 select-a-timer = Please select a timer to configure!
+timer-category-enable = { enable } { $category } from this source
+timer-category-disable = { disable } { $category } from this source
+timer-source-enable = { enable } all from this source
+timer-source-disable = { disable } all from this source
 
 ## Timer window
 
 timer-window = { timers }
 no-phases-active = No phases currently active, no timers running.
+direction-distance = { $name } - { $distance }m away
 reset-timers = { reset } { timers }
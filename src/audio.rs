@@ -0,0 +1,212 @@
+use std::{
+    fmt::Debug,
+    path::PathBuf,
+    sync::{mpsc, Arc, Mutex, OnceLock},
+    thread,
+};
+
+pub static AUDIO_SINK: OnceLock<Arc<dyn AudioSink>> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundCue {
+    pub path: PathBuf,
+    pub volume: f32,
+}
+
+/*
+ * Anything that can make noise for us. The timers only ever talk to this,
+ * so that they can be run without a sound card attached.
+ */
+pub trait AudioSink: Send + Sync + Debug {
+    fn play(&self, cue: SoundCue) -> anyhow::Result<()>;
+}
+
+pub fn audio_sink() -> Arc<dyn AudioSink> {
+    match AUDIO_SINK.get() {
+        Some(sink) => sink.clone(),
+        None => Arc::new(NullSink),
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct NullSink;
+
+impl AudioSink for NullSink {
+    fn play(&self, cue: SoundCue) -> anyhow::Result<()> {
+        log::debug!("No audio sink, dropping {:?}", cue.path);
+        Ok(())
+    }
+}
+
+// tokio's clock, so that recordings follow a paused runtime in simulations
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct RecordingSink {
+    played: Mutex<Vec<(tokio::time::Instant, SoundCue)>>,
+}
+
+#[cfg(test)]
+impl RecordingSink {
+    pub fn played(&self) -> Vec<(tokio::time::Instant, SoundCue)> {
+        self.played.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl AudioSink for RecordingSink {
+    fn play(&self, cue: SoundCue) -> anyhow::Result<()> {
+        self.played
+            .lock()
+            .unwrap()
+            .push((tokio::time::Instant::now(), cue));
+        Ok(())
+    }
+}
+
+/*
+ * rodio's output stream isn't Send, so it lives on its own thread and we
+ * just throw cues at it.
+ */
+#[derive(Debug)]
+pub struct RodioSink {
+    sender: Mutex<mpsc::Sender<SoundCue>>,
+}
+
+impl RodioSink {
+    pub fn new() -> anyhow::Result<Self> {
+        let (sender, receiver) = mpsc::channel::<SoundCue>();
+        thread::Builder::new()
+            .name("taimi-audio".to_string())
+            .spawn(move || Self::run(receiver))?;
+        Ok(Self {
+            sender: Mutex::new(sender),
+        })
+    }
+
+    fn run(receiver: mpsc::Receiver<SoundCue>) {
+        let (_stream, handle) = match rodio::OutputStream::try_default() {
+            Ok(stream) => stream,
+            Err(err) => {
+                log::error!("Couldn't open an audio output stream: {err}");
+                return;
+            }
+        };
+        while let Ok(cue) = receiver.recv() {
+            if let Err(err) = Self::play_cue(&handle, &cue) {
+                log::error!("Couldn't play {:?}: {err}", cue.path);
+            }
+        }
+    }
+
+    fn play_cue(handle: &rodio::OutputStreamHandle, cue: &SoundCue) -> anyhow::Result<()> {
+        let file = std::io::BufReader::new(std::fs::File::open(&cue.path)?);
+        let source = rodio::Decoder::new(file)?;
+        let sink = rodio::Sink::try_new(handle)?;
+        sink.set_volume(cue.volume);
+        sink.append(source);
+        sink.detach();
+        Ok(())
+    }
+}
+
+impl AudioSink for RodioSink {
+    fn play(&self, cue: SoundCue) -> anyhow::Result<()> {
+        self.sender.lock().unwrap().send(cue)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{RecordingSink, SoundCue},
+        crate::{
+            render::RenderEvent,
+            timer::{Position, TimerFile, TimerMachine},
+        },
+        glam::Vec3,
+        std::{path::PathBuf, sync::Arc},
+        tokio::{
+            sync::mpsc::{channel, Receiver},
+            time::{sleep, Duration, Instant},
+        },
+    };
+
+    // starts as soon as we're stood at the origin, never resets on its own
+    const TIMER: &str = r#"{
+        "id": "sound-test",
+        "name": "Sound test",
+        "category": "Tests",
+        "description": "",
+        "author": "",
+        "icon": "icon.png",
+        "map": 1,
+        "reset": { "position": [1000, 1000, 1000], "radius": 1, "requireEntry": true },
+        "phases": [{
+            "name": "Only",
+            "start": { "position": [0, 0, 0], "radius": 10 },
+            "sounds": [
+                { "file": "beep.wav", "volume": 0.5, "timestamps": [1, 3] },
+                { "file": "loud.wav", "volume": 4, "timestamps": [2] }
+            ]
+        }]
+    }"#;
+
+    // the receiver has to outlive the machine, or its text alerts go nowhere
+    fn machine(sink: Arc<RecordingSink>) -> (TimerMachine, Receiver<RenderEvent>) {
+        let mut timer: TimerFile = serde_json::from_str(TIMER).unwrap();
+        timer.path = Some(PathBuf::from("timers/sound-test.bhtimer"));
+        let (sender, receiver) = channel(16);
+        let mut machine = TimerMachine::new(Arc::new(timer), Default::default(), sender)
+            .with_recorder(Default::default())
+            .with_audio_sink(sink);
+        machine.update_on_map(1);
+        (machine, receiver)
+    }
+
+    fn cue(file: &str, volume: f32) -> SoundCue {
+        SoundCue {
+            path: PathBuf::from("timers").join(file),
+            volume,
+        }
+    }
+
+    fn played_since(sink: &RecordingSink, start: Instant) -> Vec<(Duration, SoundCue)> {
+        sink.played()
+            .into_iter()
+            .map(|(at, cue)| (at - start, cue))
+            .collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sounds_play_at_their_timestamps() {
+        let sink = Arc::new(RecordingSink::default());
+        let (mut machine, _alerts) = machine(sink.clone());
+        let start = Instant::now();
+        machine.tick(Position::Vec3(Vec3::ZERO)).await;
+        sleep(Duration::from_secs(5)).await;
+        assert_eq!(
+            played_since(&sink, start),
+            vec![
+                (Duration::from_secs(1), cue("beep.wav", 0.5)),
+                (Duration::from_secs(2), cue("loud.wav", 1.0)),
+                (Duration::from_secs(3), cue("beep.wav", 0.5)),
+            ]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reset_silences_pending_sounds() {
+        let sink = Arc::new(RecordingSink::default());
+        let (mut machine, _alerts) = machine(sink.clone());
+        let start = Instant::now();
+        machine.tick(Position::Vec3(Vec3::ZERO)).await;
+        sleep(Duration::from_millis(1500)).await;
+        machine.do_reset().await;
+        sleep(Duration::from_secs(5)).await;
+        assert_eq!(
+            played_since(&sink, start),
+            vec![(Duration::from_secs(1), cue("beep.wav", 0.5))]
+        );
+    }
+}
@@ -0,0 +1,84 @@
+/*
+ * Lints directories of .bhtimer files without the game around.
+ *
+ * usage: taimi-lint [--json] <dir or file>...
+ */
+use {
+    std::{
+        path::{Path, PathBuf},
+        process::ExitCode,
+    },
+    taimi_hud::tools::{lint_dir, lint_file, FileReport, Severity},
+};
+
+fn lint(path: &Path) -> anyhow::Result<Vec<FileReport>> {
+    match path.is_dir() {
+        true => lint_dir(path),
+        false => Ok(vec![lint_file(path).1]),
+    }
+}
+
+fn main() -> ExitCode {
+    let mut json = false;
+    let mut paths = Vec::new();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => {
+                println!("usage: taimi-lint [--json] <dir or file>...");
+                return ExitCode::SUCCESS;
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        eprintln!("usage: taimi-lint [--json] <dir or file>...");
+        return ExitCode::from(2);
+    }
+
+    let mut reports = Vec::new();
+    for path in &paths {
+        match lint(path) {
+            Ok(r) => reports.extend(r),
+            Err(err) => {
+                eprintln!("{}: {err}", path.display());
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    let (mut errors, mut warnings) = (0usize, 0usize);
+    for report in &reports {
+        for diagnostic in &report.diagnostics {
+            match diagnostic.severity {
+                Severity::Error => errors += 1,
+                Severity::Warning => warnings += 1,
+            }
+            if !json {
+                println!("{}: {diagnostic}", report.path.display());
+            }
+        }
+    }
+    if json {
+        let out: Vec<_> = reports
+            .iter()
+            .filter(|r| !r.diagnostics.is_empty())
+            .map(|r| {
+                serde_json::json!({
+                    "file": r.path,
+                    "diagnostics": r.diagnostics,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&out).unwrap_or_default());
+    } else {
+        eprintln!(
+            "{} files checked, {errors} errors, {warnings} warnings",
+            reports.len()
+        );
+    }
+    match errors {
+        0 => ExitCode::SUCCESS,
+        _ => ExitCode::FAILURE,
+    }
+}
@@ -0,0 +1,77 @@
+/*
+ * Converts, merges and dedupes .markers files without the game around.
+ *
+ * usage: taimi-markers [--to community|integrated|taimi] [--dedupe] -o <out> <file>...
+ */
+use {
+    std::{path::PathBuf, process::ExitCode},
+    taimi_hud::tools::{merge, MarkerFiletype, MarkerFormats},
+};
+
+const USAGE: &str =
+    "usage: taimi-markers [--to community|integrated|taimi] [--dedupe] -o <out> <file>...";
+
+fn main() -> ExitCode {
+    let mut filetype = None;
+    let mut dedupe = false;
+    let mut output = None;
+    let mut inputs = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dedupe" => dedupe = true,
+            "--to" => match args.next().map(|f| f.parse::<MarkerFiletype>()) {
+                Some(Ok(f)) => filetype = Some(f),
+                Some(Err(err)) => {
+                    eprintln!("{err}");
+                    return ExitCode::from(2);
+                }
+                None => {
+                    eprintln!("{USAGE}");
+                    return ExitCode::from(2);
+                }
+            },
+            "-o" | "--output" => output = args.next().map(PathBuf::from),
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ => inputs.push(PathBuf::from(arg)),
+        }
+    }
+    let Some(output) = output.filter(|_| !inputs.is_empty()) else {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    };
+
+    // one file and no format given just tidies it up in its own format
+    let filetype = match filetype {
+        Some(f) => f,
+        None => match MarkerFormats::read(&inputs[0]) {
+            Ok(file) => file.filetype(),
+            Err(err) => {
+                eprintln!("{}: {err}", inputs[0].display());
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+    let (file, summary) = match merge(&inputs, filetype.clone(), dedupe) {
+        Ok(merged) => merged,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(err) = file.write(&output) {
+        eprintln!("{}: {err}", output.display());
+        return ExitCode::FAILURE;
+    }
+    eprintln!(
+        "{} files into {} as {filetype}, {} sets, {} duplicates dropped",
+        summary.files,
+        output.display(),
+        summary.sets,
+        summary.duplicates
+    );
+    ExitCode::SUCCESS
+}
@@ -1,8 +1,10 @@
 #[cfg(feature = "markers")]
 use {
     crate::marker::{
+        agent::{select_agent, AgentSelection, AgentTracker, KnownAgent},
         atomic::{CurrentPerspective, MarkerInputData, MinimapPlacement, ScreenPoint},
         format::{MarkerSet, RuntimeMarkers},
+        placement::{PlacementPlanner, PlacementStep},
     },
     arcdps::extras::UserInfoOwned,
     tokio::task::JoinHandle,
@@ -19,12 +21,19 @@ use {
             format::{MarkerEntry, MarkerFiletype},
         },
         render::TextFont,
-        settings::{MarkerAutoPlaceSettings, RemoteSource, Settings, SettingsLock, SourcesFile},
-        timer::{CombatState, Position, TimerFile, TimerMachine},
-        MumbleIdentityUpdate, RenderEvent, IMGUI_TEXTURES, SETTINGS, SOURCES,
+        settings::{
+            Channel, MarkerAutoPlaceSettings, RemoteSource, Settings, SettingsLock, SourceKind,
+            SourcesFile, TimerChanges, TimerScope, UpdateAction, UpdateSettings,
+        },
+        timer::{
+            CombatEventInfo, CombatState, Mount, Position, Profession, TimerFile, TimerMachine,
+            TimerPack, TimerWatcher,
+        },
+        MumbleIdentityUpdate, RenderEvent, CONTROLLER_SENDER, IMGUI_TEXTURES, SETTINGS, SOURCES,
     },
     anyhow::anyhow,
     arcdps::{evtc::event::Event as arcEvent, AgentOwned},
+    chrono::{DateTime, Utc},
     glam::{f32::Vec3, Vec2},
     nexus::{
         data_link::{
@@ -32,7 +41,7 @@ use {
             mumble::{MumblePtr, UiState},
             read_nexus_link, MumbleLink,
         },
-        gamebind::invoke_gamebind_async,
+        gamebind::{invoke_gamebind_async, GameBind},
         paths::get_addon_dir,
         rtapi::GroupMemberOwned,
         texture::{load_texture_from_file, load_texture_from_memory, RawTextureReceiveCallback},
@@ -43,7 +52,7 @@ use {
         collections::{HashMap, HashSet},
         ffi::OsStr,
         fs::exists,
-        path::PathBuf,
+        path::{Path, PathBuf},
         sync::{Arc, RwLock},
         time::SystemTime,
     },
@@ -55,7 +64,7 @@ use {
             mpsc::{Receiver, Sender},
             Mutex,
         },
-        time::{interval, sleep, Duration},
+        time::{interval, sleep, Duration, Instant},
     },
     windows::Win32::{
         Foundation::GetLastError,
@@ -72,6 +81,38 @@ use {
 #[cfg(feature = "space")]
 use crate::space::dx11::PerspectiveInputData;
 
+// how long timer files have to sit untouched before they're reloaded
+const TIMER_RELOAD_SETTLE: Duration = Duration::from_millis(300);
+// how long arcdps takes to tell us the target changed, give or take
+#[cfg(feature = "markers")]
+const TARGET_SETTLE: Duration = Duration::from_millis(150);
+// how many targets to go through looking for an agent before giving up
+#[cfg(feature = "markers")]
+const MAX_TARGET_CYCLES: usize = 25;
+// how long squad markers take to show up in RTAPI once they're put down
+#[cfg(feature = "markers")]
+const PLACEMENT_SETTLE: Duration = Duration::from_millis(250);
+
+// the game's targeting, as arcdps sees it
+#[cfg(feature = "markers")]
+struct GameSelection(Arc<RwLock<AgentTracker>>);
+
+#[cfg(feature = "markers")]
+impl AgentSelection for GameSelection {
+    fn current_target(&self) -> Option<KnownAgent> {
+        self.0.read().ok()?.current_target()
+    }
+
+    async fn target_next(&mut self, ally: bool) {
+        let bind = match ally {
+            true => GameBind::TargetingAllyNext,
+            false => GameBind::TargetingNext,
+        };
+        invoke_gamebind_async(bind, 10i32);
+        sleep(TARGET_SETTLE).await;
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Controller {
     #[cfg(feature = "markers")]
@@ -88,17 +129,29 @@ pub struct Controller {
     pub map_id_to_markers: HashMap<u32, HashSet<Arc<MarkerSet>>>,
     #[cfg(feature = "markers")]
     pub marker_autoplace: Option<MarkerAutoPlaceSettings>,
+    // shared with marker placement, which needs to see targets change
+    #[cfg(feature = "markers")]
+    agents: Arc<RwLock<AgentTracker>>,
     pub rt_sender: Sender<RenderEvent>,
     pub cached_identity: Option<MumbleIdentityUpdate>,
     pub mumble_pointer: Option<MumblePtr>,
     pub map_id: Option<u32>,
     pub player_position: Option<Vec3>,
+    pub mount: Option<Mount>,
+    pub profession: Option<Profession>,
     alert_sem: Arc<Mutex<()>>,
     pub timers: Vec<Arc<TimerFile>>,
     pub current_timers: Vec<TimerMachine>,
     pub sources_to_timers: HashMap<Arc<RemoteSource>, Vec<Arc<TimerFile>>>,
     pub map_id_to_timers: HashMap<u32, Vec<Arc<TimerFile>>>,
+    timer_watcher: Option<Arc<TimerWatcher>>,
+    // timer files touched on disk, and when the last one was
+    changed_timer_files: HashSet<PathBuf>,
+    timer_files_changed_at: Option<Instant>,
     settings: SettingsLock,
+    // for the scheduled update checks
+    loaded_at: DateTime<Utc>,
+    update_failures: u32,
     last_fov: f32,
     scaling: f32,
 }
@@ -108,6 +161,58 @@ impl Controller {
         self.player_position.map(Position::Vec3)
     }
 
+    fn timer_machine(&self, timer: Arc<TimerFile>) -> TimerMachine {
+        let mut machine = TimerMachine::new(timer, self.alert_sem.clone(), self.rt_sender.clone());
+        machine.set_mount(self.mount);
+        machine.set_profession(self.profession);
+        machine
+    }
+
+    fn new(
+        rt_sender: Sender<RenderEvent>,
+        settings: SettingsLock,
+        mumble_pointer: Option<MumblePtr>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "markers")]
+            rtapi_squad: Default::default(),
+            #[cfg(feature = "markers")]
+            extras_squad: Default::default(),
+            #[cfg(feature = "markers")]
+            marker_autoplace: Default::default(),
+            #[cfg(feature = "markers")]
+            agents: Default::default(),
+            last_fov: 0.0,
+            previous_combat_state: Default::default(),
+            rt_sender,
+            settings,
+            #[cfg(feature = "markers")]
+            markers: Default::default(),
+            #[cfg(feature = "markers")]
+            map_id_to_markers: Default::default(),
+            #[cfg(feature = "markers")]
+            spent_markers: Default::default(),
+            agent: Default::default(),
+            cached_identity: Default::default(),
+            mumble_pointer,
+            map_id: Default::default(),
+            player_position: Default::default(),
+            mount: Default::default(),
+            profession: Default::default(),
+            alert_sem: Default::default(),
+            timers: Default::default(),
+            current_timers: Default::default(),
+            sources_to_timers: Default::default(),
+            map_id_to_timers: Default::default(),
+            timer_watcher: Default::default(),
+            changed_timer_files: Default::default(),
+            timer_files_changed_at: Default::default(),
+            scaling: 0.0f32,
+            loaded_at: Utc::now(),
+            update_failures: 0,
+        }
+    }
+
     pub fn load(
         mut controller_receiver: Receiver<ControllerEvent>,
         rt_sender: Sender<crate::RenderEvent>,
@@ -122,42 +227,14 @@ impl Controller {
             let sources = Arc::new(RwLock::new(sources));
             let _ = SOURCES.set(sources);
             let settings = Settings::load_access(&addon_dir.clone()).await;
-            let mut state = Controller {
-                #[cfg(feature = "markers")]
-                rtapi_squad: Default::default(),
-                #[cfg(feature = "markers")]
-                extras_squad: Default::default(),
-                #[cfg(feature = "markers")]
-                marker_autoplace: Default::default(),
-                last_fov: 0.0,
-                previous_combat_state: Default::default(),
-                rt_sender,
-                settings,
-                #[cfg(feature = "markers")]
-                markers: Default::default(),
-                #[cfg(feature = "markers")]
-                map_id_to_markers: Default::default(),
-                #[cfg(feature = "markers")]
-                spent_markers: Default::default(),
-                agent: Default::default(),
-                cached_identity: Default::default(),
-                mumble_pointer: mumble_link,
-                map_id: Default::default(),
-                player_position: Default::default(),
-                alert_sem: Default::default(),
-                timers: Default::default(),
-                current_timers: Default::default(),
-                sources_to_timers: Default::default(),
-                map_id_to_timers: Default::default(),
-                scaling: 0.0f32,
-            };
+            let mut state = Controller::new(rt_sender, settings, mumble_link);
             let _ = SETTINGS.set(state.settings.clone());
             let settings = SETTINGS.get().unwrap();
             let mut settings_lock = settings.write().await;
             settings_lock.handle_sources_changes();
             drop(settings_lock);
             let settings_lock = settings.read().await;
-            state.marker_autoplace = Some(settings_lock.marker_autoplace.clone());
+            state.marker_autoplace = Some(settings_lock.config.marker_autoplace.clone());
             drop(settings_lock);
             state.setup_timers().await;
             #[cfg(feature = "markers")]
@@ -276,8 +353,23 @@ impl Controller {
         if !exists(&markers_dir).expect("Can't check if directory exists") {
             create_dir_all(&markers_dir).await?;
         }
-        let markers = RuntimeMarkers::load_many(&markers_dir, 100).await?;
+        let mut markers = RuntimeMarkers::load_many(&markers_dir, 100).await?;
+        let settings_lock = self.settings.read().await;
+        let source_dirs: Vec<PathBuf> = settings_lock
+            .get_paths_for(SourceKind::Markers)
+            .into_iter()
+            .cloned()
+            .collect();
+        drop(settings_lock);
+        for source_dir in source_dirs {
+            markers.extend(RuntimeMarkers::load_many(&source_dir, 100).await?);
+        }
         let markers = RuntimeMarkers::markers(markers).await;
+        let mut settings_lock = self.settings.write().await;
+        settings_lock
+            .adopt_marker_uuids(markers.values().flatten().map(AsRef::as_ref))
+            .await;
+        drop(settings_lock);
         let _ = self
             .rt_sender
             .send(RenderEvent::MarkerData(markers.clone()))
@@ -303,8 +395,19 @@ impl Controller {
     async fn load_timer_files(&self) -> Vec<Arc<TimerFile>> {
         let settings_lock = self.settings.read().await;
         let mut timers = Vec::new();
-        for remote in settings_lock.remotes.iter() {
-            timers.extend(remote.load().await);
+        let mut packs = Vec::new();
+        for remote in settings_lock.state.remotes.iter() {
+            let (pack, loaded) = remote.load().await;
+            timers.extend(loaded);
+            packs.push((remote.source.clone(), pack));
+        }
+        drop(settings_lock);
+        // kept for the sources tab, and for reloading single files later
+        let mut settings_lock = self.settings.write().await;
+        for (source, pack) in packs {
+            if let Some(remote) = settings_lock.get_status_for_mut(&source).await {
+                remote.pack = pack;
+            }
         }
         drop(settings_lock);
         let timers_len = timers.len();
@@ -327,42 +430,204 @@ impl Controller {
                 .await
                 .expect("Can't create timers dir");
         }
-        for timer in &self.timers {
-            if let Some(association) = &timer.association {
-                self.sources_to_timers
-                    .entry(association.clone())
-                    .or_default();
-                if let Some(val) = self.sources_to_timers.get_mut(association) {
-                    val.push(timer.clone());
-                };
-            }
-            // Handle map to timers
-            self.map_id_to_timers.entry(timer.map_id).or_default();
-            if let Some(val) = self.map_id_to_timers.get_mut(&timer.map_id) {
-                val.push(timer.clone());
-            };
-            let association = match &timer.association {
-                Some(s) => format!("{}", s),
-                None => "unassociated".to_string(),
-            };
-            // Handle id to timer file allocation
-            log::info!(
-                "Set up {4} {0}: {3} for map {1}, category {2}",
-                timer.id,
-                timer.name.replace("\n", " "),
-                timer.map_id,
-                timer.category,
-                association,
-            );
+        for timer in self.timers.clone() {
+            self.index_timer(&timer);
         }
+        let mut settings_lock = self.settings.write().await;
+        settings_lock.adopt_unscoped_timers(&self.timers).await;
+        drop(settings_lock);
         log::info!("Set up {} timers.", self.timers.len());
+        self.watch_timer_dirs().await;
         let _ = self
             .rt_sender
             .send(RenderEvent::TimerData(self.timers.clone()))
             .await;
     }
 
+    fn index_timer(&mut self, timer: &Arc<TimerFile>) {
+        if let Some(association) = &timer.association {
+            self.sources_to_timers
+                .entry(association.clone())
+                .or_default()
+                .push(timer.clone());
+        }
+        // Handle map to timers
+        self.map_id_to_timers
+            .entry(timer.map_id)
+            .or_default()
+            .push(timer.clone());
+        let association = match &timer.association {
+            Some(s) => format!("{}", s),
+            None => "unassociated".to_string(),
+        };
+        log::info!(
+            "Set up {4} {0}: {3} for map {1}, category {2}",
+            timer.id,
+            timer.name.replace("\n", " "),
+            timer.map_id,
+            timer.category,
+            association,
+        );
+    }
+
+    fn unindex_timer(&mut self, timer: &Arc<TimerFile>) {
+        if let Some(association) = &timer.association {
+            if let Some(timers) = self.sources_to_timers.get_mut(association) {
+                timers.retain(|t| !Arc::ptr_eq(t, timer));
+                if timers.is_empty() {
+                    self.sources_to_timers.remove(association);
+                }
+            }
+        }
+        if let Some(timers) = self.map_id_to_timers.get_mut(&timer.map_id) {
+            timers.retain(|t| !Arc::ptr_eq(t, timer));
+            if timers.is_empty() {
+                self.map_id_to_timers.remove(&timer.map_id);
+            }
+        }
+    }
+
+    async fn watch_timer_dirs(&mut self) {
+        let addon_dir = get_addon_dir("Taimi").expect("Invalid addon dir");
+        let mut dirs = vec![addon_dir.join("timers")];
+        let settings_lock = self.settings.read().await;
+        dirs.extend(settings_lock.get_paths().into_iter().cloned());
+        drop(settings_lock);
+        if self.timer_watcher.is_none() {
+            let watcher = TimerWatcher::new(|paths| {
+                if let Some(sender) = CONTROLLER_SENDER.get() {
+                    let _ = sender.blocking_send(ControllerEvent::TimerFilesChanged(paths));
+                }
+            });
+            match watcher {
+                Ok(watcher) => self.timer_watcher = Some(Arc::new(watcher)),
+                Err(err) => log::error!("Couldn't start watching timer files: {err}"),
+            }
+        }
+        if let Some(watcher) = &self.timer_watcher {
+            watcher.watch_dirs(dirs);
+        }
+    }
+
+    async fn timer_enabled(&self, timer: &TimerFile) -> bool {
+        let settings_lock = self.settings.read().await;
+        settings_lock.timer_enabled(timer)
+    }
+
+    async fn timer_association(
+        &self,
+        path: &Path,
+    ) -> (Option<Arc<RemoteSource>>, Option<TimerPack>) {
+        let settings_lock = self.settings.read().await;
+        settings_lock
+            .state
+            .remotes
+            .iter()
+            .find(|r| {
+                r.installed_path.as_ref().is_some_and(|p| {
+                    // the watcher's paths are resolved, the install path may not be
+                    path.starts_with(p) || p.canonicalize().is_ok_and(|p| path.starts_with(p))
+                })
+            })
+            .map(|r| (Some(r.source.clone()), r.pack.clone()))
+            .unwrap_or_default()
+    }
+
+    async fn timer_files_changed(&mut self, paths: Vec<PathBuf>) {
+        // one file can turn up under a few names, it should only be reloaded the once
+        self.changed_timer_files
+            .extend(paths.iter().map(|path| TimerWatcher::canonical(path)));
+        self.timer_files_changed_at = Some(Instant::now());
+    }
+
+    /*
+     * Reloads just the timer files that changed on disk. Machines for every
+     * other file are left alone, so you can edit one encounter's timers while
+     * standing in it without everything else on the map losing its place.
+     */
+    async fn reload_changed_timers(&mut self) {
+        let paths: Vec<_> = self.changed_timer_files.drain().collect();
+        self.timer_files_changed_at = None;
+        let mut changed = false;
+        for path in paths {
+            let canonical = TimerWatcher::canonical(&path);
+            let old = self.timers.iter().position(|t| {
+                t.path
+                    .as_deref()
+                    .is_some_and(|p| TimerWatcher::canonical(p) == canonical)
+            });
+            // reloaded from wherever it was first loaded from, so it still matches its source
+            let path = match old {
+                Some(idx) => self.timers[idx].path.clone().unwrap_or(path),
+                None => path,
+            };
+            let (association, pack) = self.timer_association(&path).await;
+            // a file the manifest leaves out may as well not be there
+            let included = pack
+                .as_ref()
+                .is_none_or(|pack| pack.unmet_requirement().is_none() && pack.includes(&path));
+            let new = match path.exists() && included {
+                true => {
+                    match TimerFile::load(&path, association, pack.as_ref()).await {
+                        Ok(timer) => Some(timer),
+                        Err(err) => {
+                            // mid-edit, most likely; keep whatever was working before
+                            log::error!("Couldn't reload timer file {path:?}: {err}");
+                            continue;
+                        }
+                    }
+                }
+                false => None,
+            };
+            if old.is_none() && new.is_none() {
+                continue;
+            }
+            if let Some(idx) = old {
+                let old = self.timers.remove(idx);
+                self.unindex_timer(&old);
+                let machines = self
+                    .current_timers
+                    .iter_mut()
+                    .filter(|m| Arc::ptr_eq(&m.timer, &old));
+                for machine in machines {
+                    machine.cleanup().await;
+                }
+                self.current_timers.retain(|m| !Arc::ptr_eq(&m.timer, &old));
+                log::info!("Unloaded timer {} from {path:?}.", old.id);
+            }
+            if let Some(new) = new {
+                self.index_timer(&new);
+                self.timers.push(new.clone());
+                if self.map_id == Some(new.map_id) && self.timer_enabled(&new).await {
+                    let mut machine = self.timer_machine(new.clone());
+                    machine.update_on_map(new.map_id);
+                    self.current_timers.push(machine);
+                }
+            }
+            changed = true;
+        }
+        if changed {
+            let _ = self
+                .rt_sender
+                .send(RenderEvent::TimerData(self.timers.clone()))
+                .await;
+        }
+    }
+
     async fn tick(&mut self) -> anyhow::Result<()> {
+        let settled = self
+            .timer_files_changed_at
+            .is_some_and(|at| at.elapsed() >= TIMER_RELOAD_SETTLE);
+        if settled {
+            self.reload_changed_timers().await;
+        }
+        if self
+            .next_update_check()
+            .await
+            .is_some_and(|due| Utc::now() >= due)
+        {
+            self.check_updates().await;
+        }
         Ok(())
     }
 
@@ -426,6 +691,13 @@ impl Controller {
                 );
             }
             self.player_position = Some(playpos);
+            let mount = Mount::from_index(mumble.read_context().mount_index as u32);
+            if mount != self.mount {
+                for machine in &mut self.current_timers {
+                    machine.set_mount(mount);
+                }
+                self.mount = mount;
+            }
             let combat_state = mumble
                 .read_context()
                 .ui_state
@@ -461,6 +733,13 @@ impl Controller {
                 self.last_fov = identity.fov;
             }
         }
+        let profession = Profession::from_index(identity.profession as u32);
+        if profession != self.profession {
+            for machine in &mut self.current_timers {
+                machine.set_profession(profession);
+            }
+            self.profession = profession;
+        }
         let new_map_id = identity.map_id;
         if Some(new_map_id) != self.map_id {
             #[cfg(feature = "markers")]
@@ -477,6 +756,10 @@ impl Controller {
                     .await;
                 MarkerInputData::from_mapchange(new_map_id);
                 self.spent_markers = Default::default();
+                // agent ids don't mean anything on the next map
+                if let Ok(mut agents) = self.agents.write() {
+                    agents.clear();
+                }
             }
             for timer in &mut self.current_timers {
                 timer.cleanup().await;
@@ -484,21 +767,12 @@ impl Controller {
             self.current_timers.clear();
             if self.map_id_to_timers.contains_key(&new_map_id) {
                 let map_timers = &self.map_id_to_timers[&new_map_id];
-                for timer in map_timers {
-                    let settings_lock = self.settings.read().await;
-                    let settings_for_timer = settings_lock.timers.get(&timer.id);
-                    let timer_enabled = match settings_for_timer {
-                        Some(setting) => !setting.disabled,
-                        None => true,
-                    };
-                    if timer_enabled {
-                        self.current_timers.push(TimerMachine::new(
-                            timer.clone(),
-                            self.alert_sem.clone(),
-                            self.rt_sender.clone(),
-                        ));
+                for timer in map_timers.clone() {
+                    if self.timer_enabled(&timer).await {
+                        let mut machine = self.timer_machine(timer);
+                        machine.arrived_from(self.map_id);
+                        self.current_timers.push(machine);
                     }
-                    drop(settings_lock);
                 }
                 for machine in &mut self.current_timers {
                     machine.update_on_map(new_map_id)
@@ -509,7 +783,24 @@ impl Controller {
         self.cached_identity = Some(identity);
     }
 
-    async fn handle_combat_event(&mut self, src: arcdps::AgentOwned, evt: arcEvent) {
+    async fn handle_combat_event(
+        &mut self,
+        src: arcdps::AgentOwned,
+        dst: Option<arcdps::AgentOwned>,
+        evt: arcEvent,
+    ) {
+        #[cfg(feature = "markers")]
+        if let Ok(mut agents) = self.agents.write() {
+            agents.seen(&src);
+            if let Some(dst) = &dst {
+                agents.seen(dst);
+            }
+        }
+        if let Some(info) = CombatEventInfo::from_arc(&src, dst.as_ref(), &evt) {
+            for machine in &mut self.current_timers {
+                machine.combat_event(info.clone());
+            }
+        }
         let is_self = src.is_self != 0;
         if is_self {
             match &mut self.agent {
@@ -527,6 +818,8 @@ impl Controller {
         use arcdps::StateChange;
         match evt.get_statechange() {
             StateChange::None => {}
+            // the area feed has everyone else going in and out of combat too
+            _ if !is_self => (),
             StateChange::EnterCombat => {
                 log::info!("ArcDPS: Combat begins at {}!", evt.time);
                 for machine in &mut self.current_timers {
@@ -543,6 +836,13 @@ impl Controller {
         }
     }
 
+    #[cfg(feature = "markers")]
+    async fn handle_agent_tracking(&mut self, src: AgentOwned, dst: Option<AgentOwned>) {
+        if let Ok(mut agents) = self.agents.write() {
+            agents.tracking(&src, dst.as_ref());
+        }
+    }
+
     async fn toggle_marker(&mut self, id: &str) {
         let mut settings_lock = self.settings.write().await;
         let disabled = settings_lock.toggle_marker(id.to_string()).await;
@@ -561,71 +861,92 @@ impl Controller {
         drop(settings_lock);
     }
 
-    async fn toggle_timer(&mut self, id: &str) {
-        let mut settings_lock = self.settings.write().await;
-        let disabled = settings_lock.toggle_timer(id.to_string()).await;
-        drop(settings_lock);
-        match disabled {
-            false => {
-                if let Some(map_id) = self.map_id {
-                    if let Some(timers_for_map) = &self.map_id_to_timers.get(&map_id) {
-                        let timers = timers_for_map.iter().filter(|t| t.id == id);
-                        for timer in timers {
-                            log::debug!(
-                                "Creating timer machine for {} as it has been enabled.",
-                                timer.id
-                            );
-                            self.current_timers.push(TimerMachine::new(
-                                timer.clone(),
-                                self.alert_sem.clone(),
-                                self.rt_sender.clone(),
-                            ));
-                        }
+    /*
+     * Switches a source, category or single timer on or off, or over to
+     * whichever it isn't with None. Timers can share an id between
+     * sources, so a single timer is picked out by its source as well.
+     */
+    async fn set_timers_enabled(&mut self, scope: TimerScope, enabled: Option<bool>) {
+        let timer = match &scope {
+            TimerScope::Timer { .. } => {
+                match self.timers.iter().find(|t| scope.contains(t)).cloned() {
+                    Some(timer) => Some(timer),
+                    None => {
+                        log::warn!("Couldn't find {scope} to switch.");
+                        return;
                     }
                 }
             }
-            true => {
-                let timers_to_remove = self.current_timers.iter_mut().filter(|t| t.timer.id == id);
-                for timer in timers_to_remove {
-                    log::debug!(
-                        "Starting cleanup for timer {} as it has been disabled.",
-                        timer.timer.id
-                    );
-                    timer.cleanup().await;
-                }
-            }
-        }
-    }
-
-    async fn enable_timer(&mut self, id: &str) {
+            _ => None,
+        };
         let mut settings_lock = self.settings.write().await;
-        settings_lock.enable_timer(id.to_string()).await;
+        let current = match &timer {
+            Some(timer) => settings_lock.timer_enabled(timer),
+            None => settings_lock.scope_enabled(&scope),
+        };
+        let enabled = enabled.unwrap_or(!current);
+        settings_lock
+            .set_scope_enabled(&scope, timer.as_deref(), enabled)
+            .await;
         drop(settings_lock);
-        if let Some(map_id) = self.map_id {
-            if let Some(timers_for_map) = &self.map_id_to_timers.get(&map_id) {
-                let timers = timers_for_map.iter().filter(|t| t.id == id);
-                for timer in timers {
-                    log::debug!("Creating timer machine for {}", timer.id);
-                    self.current_timers.push(TimerMachine::new(
-                        timer.clone(),
-                        self.alert_sem.clone(),
-                        self.rt_sender.clone(),
-                    ));
-                }
+        log::debug!(
+            "{} {scope}.",
+            match enabled {
+                true => "Enabled",
+                false => "Disabled",
             }
-        }
+        );
+        self.refresh_timer_machines(&scope).await;
     }
 
-    async fn disable_timer(&mut self, id: &str) {
-        let mut settings_lock = self.settings.write().await;
-        settings_lock.disable_timer(id.to_string()).await;
-        drop(settings_lock);
-        let timers_to_remove = self.current_timers.iter_mut().filter(|t| t.timer.id == id);
-        for timer in timers_to_remove {
-            log::debug!("Starting cleanup for timer {}", timer.timer.id);
-            timer.cleanup().await;
+    /*
+     * Brings the machines for the current map in line with the settings, for
+     * the timers the change could have touched. Ones that stay as they were
+     * keep their place.
+     */
+    async fn refresh_timer_machines(&mut self, scope: &TimerScope) {
+        let Some(map_id) = self.map_id else {
+            return;
+        };
+        let timers_for_map = self
+            .map_id_to_timers
+            .get(&map_id)
+            .cloned()
+            .unwrap_or_default();
+        for timer in timers_for_map.iter().filter(|t| scope.contains(t)) {
+            let enabled = self.timer_enabled(timer).await;
+            let running = self
+                .current_timers
+                .iter()
+                .any(|m| Arc::ptr_eq(&m.timer, timer));
+            match (enabled, running) {
+                (true, false) => {
+                    log::debug!(
+                        "Creating timer machine for {} as it has been enabled.",
+                        timer.id
+                    );
+                    let mut machine = self.timer_machine(timer.clone());
+                    machine.update_on_map(map_id);
+                    self.current_timers.push(machine);
+                }
+                (false, true) => {
+                    let machines = self
+                        .current_timers
+                        .iter_mut()
+                        .filter(|m| Arc::ptr_eq(&m.timer, timer));
+                    for machine in machines {
+                        log::debug!(
+                            "Starting cleanup for timer {} as it has been disabled.",
+                            timer.id
+                        );
+                        machine.cleanup().await;
+                    }
+                    self.current_timers
+                        .retain(|m| !Arc::ptr_eq(&m.timer, timer));
+                }
+                _ => (),
+            }
         }
-        self.current_timers.retain(|t| t.timer.id != id);
     }
 
     async fn check_updates(&mut self) {
@@ -633,23 +954,100 @@ impl Controller {
             .rt_sender
             .send(RenderEvent::CheckingForUpdates(true))
             .await;
-        match Settings::check_for_updates().await {
-            Ok(_) => (),
-            Err(err) => log::error!("Controller.check_updates(): {}", err),
+        let checked = match Settings::check_for_updates().await {
+            Ok(_) => true,
+            Err(err) => {
+                log::error!("Controller.check_updates(): {}", err);
+                false
+            }
+        };
+        let settings_lock = self.settings.read().await;
+        let failed = !checked || settings_lock.update_check_failed();
+        let action = settings_lock.config.updates.action;
+        let available = settings_lock.updates_available();
+        drop(settings_lock);
+        if failed {
+            self.update_failures += 1;
+            log::warn!(
+                "Checking for updates failed {} time(s) in a row, trying again in {} minutes at most.",
+                self.update_failures,
+                UpdateSettings::backoff(self.update_failures).num_minutes(),
+            );
+        } else {
+            self.update_failures = 0;
         }
         let _ = self
             .rt_sender
             .send(RenderEvent::CheckingForUpdates(false))
             .await;
+        self.act_on_updates(action, available).await;
+    }
+
+    async fn next_update_check(&self) -> Option<DateTime<Utc>> {
+        let settings_lock = self.settings.read().await;
+        settings_lock.config.updates.next_check(
+            settings_lock.state.last_checked,
+            self.loaded_at,
+            self.update_failures,
+        )
+    }
+
+    async fn act_on_updates(&mut self, action: UpdateAction, available: Vec<Arc<RemoteSource>>) {
+        if available.is_empty() {
+            return;
+        }
+        match action {
+            UpdateAction::Notify => {
+                log::info!("{} data source(s) have updates available.", available.len());
+            }
+            UpdateAction::AutoDownload => {
+                log::info!("Automatically updating {} data source(s).", available.len());
+                let sources: Vec<&RemoteSource> = available.iter().map(|s| &**s).collect();
+                self.do_updates(&sources).await;
+            }
+        }
+    }
+
+    async fn set_github_token(&mut self, token: Option<String>) {
+        let mut settings_lock = self.settings.write().await;
+        settings_lock.set_github_token(token).await;
+        drop(settings_lock);
+    }
+
+    async fn set_update_settings(&mut self, updates: UpdateSettings) {
+        let mut settings_lock = self.settings.write().await;
+        settings_lock.set_update_settings(updates).await;
+        drop(settings_lock);
     }
 
     async fn reload_data(&mut self) {
         self.reload_timers().await;
         #[cfg(feature = "markers")]
         self.reload_markers().await;
+        #[cfg(feature = "space")]
+        self.reload_pathing().await;
+    }
+
+    #[cfg(feature = "space")]
+    async fn reload_pathing(&mut self) {
+        use crate::{space::engine::SpaceEvent, SPACE_SENDER};
+        // the engine reads these itself when it starts, so only bother if it's running
+        if let Some(space_sender) = SPACE_SENDER.get() {
+            let settings_lock = self.settings.read().await;
+            let pack_dirs = settings_lock
+                .get_paths_for(SourceKind::Pathing)
+                .into_iter()
+                .cloned()
+                .collect();
+            drop(settings_lock);
+            let _ = space_sender.send(SpaceEvent::LoadPacks(pack_dirs)).await;
+        }
     }
 
     async fn reload_timers(&mut self) {
+        // everything is about to be read again anyway
+        self.changed_timer_files.clear();
+        self.timer_files_changed_at = None;
         self.timers.clear();
         self.sources_to_timers.clear();
         self.map_id_to_timers.clear();
@@ -672,9 +1070,7 @@ impl Controller {
     }
     #[cfg(feature = "markers")]
     async fn clear_markers(&self) {
-        use crate::marker::format::MarkerType;
-
-        invoke_gamebind_async(MarkerType::ClearMarkers.to_place_world_gamebind(), 10i32);
+        invoke_gamebind_async(GameBind::SquadMarkerClearAllWorld, 10i32);
     }
 
     #[cfg(feature = "markers")]
@@ -783,38 +1179,24 @@ impl Controller {
         point: ScreenPoint,
         marker: &MarkerEntry,
     ) {
+        let Some(bind) = marker.marker.to_place_world_gamebind() else {
+            log::debug!("Skipping a blank marker, there's nothing to place.");
+            return;
+        };
         sleep(wait_duration).await;
         match Self::move_cursor_pos(point.into()) {
             Ok(_) => (),
             Err(e) => log::error!("{}", e),
         }
         sleep(wait_duration).await;
-        invoke_gamebind_async(marker.marker.to_place_world_gamebind(), place_duration);
-    }
-
-    #[cfg(feature = "markers")]
-    async fn place_marker_from_map(
-        wait_duration: Duration,
-        place_duration: i32,
-        point: Vec3,
-        marker: &MarkerEntry,
-    ) {
-        use crate::marker::atomic::LocalPoint;
-        let mid = MarkerInputData::read();
-        if let Some(mid) = mid {
-            let point: LocalPoint = Vec3::from(marker.position.clone()).into();
-            let point = mid.map_local_to_map(point);
-            let point = mid.map_map_to_screen(point);
-            if let Some(point) = point {
-                Self::place_marker(wait_duration, 10i32, point, marker).await;
-            }
-        }
+        invoke_gamebind_async(bind, place_duration);
     }
 
     #[cfg(feature = "markers")]
     fn set_marker(&self, markers: &MarkerSet) -> JoinHandle<anyhow::Result<()>> {
         tokio::spawn(Self::set_marker_task(
             markers.clone(),
+            self.agents.clone(),
             self.rt_sender.clone(),
         ))
     }
@@ -822,39 +1204,15 @@ impl Controller {
     #[cfg(feature = "markers")]
     async fn set_marker_task(
         markers: MarkerSet,
+        agents: Arc<RwLock<AgentTracker>>,
         rt_sender: Sender<crate::RenderEvent>,
     ) -> anyhow::Result<()> {
-        use {
-            crate::marker::atomic::{LocalPoint, MapPoint},
-            anyhow::anyhow,
-            glamour::TransformMap,
-            windows::Win32::Graphics::Gdi::ScreenToClient,
-        };
-        if let Some(mid) = MarkerInputData::read() {
-            let player_position = mid.local_player_pos;
-            let mut too_far = false;
-            for marker in &markers.markers {
-                if player_position.distance(marker.position.clone().into()) >= 127.0 {
-                    too_far = true;
-                    break;
-                }
-            }
-            if too_far {
-                let err =
-                    anyhow!("Player is too far away from the markers they are trying to place.");
-                let _ = rt_sender
-                    .send(RenderEvent::OpenableError(
-                        format!("Error setting marker set: {}", &markers.name),
-                        err,
-                    ))
-                    .await;
-                return Err(anyhow!(
-                    "Player is too far away from the markers they are trying to place."
-                ));
-            }
-        }
-
+        use windows::Win32::Graphics::Gdi::ScreenToClient;
         let wait_duration = Duration::from_millis(50);
+        let Some(mid) = MarkerInputData::read() else {
+            return Err(anyhow!("No map data to place markers with yet."));
+        };
+        let mut planner = PlacementPlanner::new(&markers.markers, mid.local_player_pos);
         let mut pos_ptr: POINT = POINT::default();
         let original_position = unsafe {
             let hwnd = GetForegroundWindow();
@@ -864,107 +1222,148 @@ impl Controller {
         }
         .map_err(anyhow::Error::from)
         .map(|()| pos_ptr)?;
-        for marker in &markers.markers {
-            // check if it is possible to place immediately
-            let local_point: LocalPoint = Vec3::from(marker.position.clone()).into();
-            let (map_point, screen_point) = if let Some(mid) = MarkerInputData::read() {
-                let map_point = mid.map_local_to_map(local_point);
-                let screen_point = mid.map_map_to_screen(map_point);
-                (Some(map_point), screen_point)
-            } else {
-                (None, None)
-            };
-            match screen_point {
-                // if the marker is on the map, that's fine, place it
-                Some(point) => {
-                    Self::place_marker(wait_duration, 10i32, point, marker).await;
+        while let Some(view) = MarkerInputData::read() {
+            match planner.next_step(&view) {
+                PlacementStep::Place(batch) => {
+                    for (idx, point) in &batch {
+                        Self::place_marker(wait_duration, 10i32, *point, &markers.markers[*idx])
+                            .await;
+                    }
+                    sleep(PLACEMENT_SETTLE).await;
+                    let batch: Vec<_> = batch.into_iter().map(|(idx, _)| idx).collect();
+                    planner.verify(&view, &batch, Self::observed_squad_markers());
                 }
-                // if the marker isn't on the map, we need to get our perspective to include
-                // the marker
-                None => {
-                    if let Some(map_point) = map_point {
-                        let max_attempts = 10; // inshallah
-                        let mut attempts = 0;
-                        let map_centre: Option<MapPoint> =
-                            MarkerInputData::read().map(|mid| mid.global_map.into());
-                        log::debug!("Reached none arm for marker placement");
-                        if let Some(mut map_centre) = map_centre {
-                            while (map_centre.distance(map_point) > 5.0)
-                                && (attempts < max_attempts)
-                            {
-                                log::debug!("Attempt {}/{}", attempts, max_attempts);
-                                if let Some(mid) = MarkerInputData::read() {
-                                    let bounds = mid.screen_bound();
-                                    map_centre = mid.global_map.into();
-                                    let remaining_distance = map_centre.distance(map_point);
-                                    log::debug!("Remaining distance: {}", remaining_distance);
-                                    let drag_from = mid.random_map_screen_coordinate();
-                                    let difference_map = map_point - map_centre;
-                                    let difference_fake = mid.map_to_fake_tf().map(difference_map);
-                                    let difference_screen =
-                                        mid.screen_to_fake().inverse().map(difference_fake);
-
-                                    // the l
-                                    let (min, max) = (bounds.min(), bounds.max());
-                                    let drag_res = drag_from - difference_screen;
-                                    let drag_res = drag_res.clamp(min, max);
-                                    log::debug!(
-                                        "Map centre: {:?}, destination: {:?}",
-                                        map_centre,
-                                        map_point
-                                    );
-                                    log::debug!("Min: {:?}, max: {:?}", min, max);
-                                    log::debug!(
-                                        "Attempting a drag from {:?} to {:?}",
-                                        drag_from,
-                                        drag_res
-                                    );
-                                    Self::drag_mouse_abs(drag_from.into(), drag_res.into()).await?;
-                                    sleep(wait_duration).await;
-                                }
-                                attempts += 1;
-                            }
-                            log::info!("Attempts: {}", attempts);
-                            if map_centre.distance(map_point) > 5.0 {
-                                let err =
-                                    anyhow!("Could not drag map perspective to marker location!");
-                                let _ = rt_sender
-                                    .send(RenderEvent::OpenableError(
-                                        format!("Error setting marker set: {}", &markers.name),
-                                        err,
-                                    ))
-                                    .await;
-                                return Err(anyhow!(
-                                    "Could not drag map perspective to marker location!"
-                                ));
-                                break;
-                            } else {
-                                Self::place_marker_from_map(
-                                    wait_duration,
-                                    10i32,
-                                    marker.position.clone().into(),
-                                    marker,
-                                )
-                                .await;
-                            }
-                        }
+                PlacementStep::Pan { from, to } => {
+                    if let Err(err) = Self::drag_mouse_abs(from.into(), to.into()).await {
+                        log::error!("Couldn't drag the map to place markers: {err}");
+                        planner.abandon();
+                        break;
                     }
+                    sleep(wait_duration).await;
                 }
-                _ => unreachable!("set_marker: this should not happen!"),
+                PlacementStep::Done => break,
             }
         }
+        // the map data went away partway through
+        if !planner.is_done() {
+            planner.abandon();
+        }
         sleep(wait_duration).await;
         let original_position = Vec2::new(original_position.x as f32, original_position.y as f32);
         Self::move_cursor_pos(original_position)?;
+        let report = planner.report(&markers.id());
+        log::info!(
+            "Placed marker set {}, {} of {} failed.",
+            &markers.name,
+            report.failures(),
+            report.results.len()
+        );
+        let _ = rt_sender.send(RenderEvent::MarkerPlacement(report)).await;
+        Self::set_agent_markers(&markers, agents, &rt_sender).await;
         Ok(())
     }
 
+    // where the squad's world markers are right now, if RTAPI can say
+    #[cfg(feature = "markers")]
+    fn observed_squad_markers() -> Option<[Option<Vec3>; 8]> {
+        use nexus::rtapi::RealTimeApi;
+        let group = RealTimeApi::get()?.read_group()?;
+        Some(
+            group
+                .squad_markers
+                .map(|marker| (marker != [f32::INFINITY; 3]).then(|| Vec3::from_array(marker))),
+        )
+    }
+
+    /*
+     * There's no targeting an agent directly, so this cycles through targets
+     * until the right one comes up. Whatever was targeted before won't be
+     * afterwards, unless it was the one being marked.
+     */
+    #[cfg(feature = "markers")]
+    async fn set_agent_markers(
+        markers: &MarkerSet,
+        agents: Arc<RwLock<AgentTracker>>,
+        rt_sender: &Sender<crate::RenderEvent>,
+    ) {
+        let mut selection = GameSelection(agents);
+        let mut failures = Vec::new();
+        for entry in &markers.agent_markers {
+            let Some(bind) = entry.marker.to_set_agent_gamebind() else {
+                failures.push(format!("{}: Blank can't go on anybody", entry.marker));
+                continue;
+            };
+            match select_agent(&mut selection, &entry.target, MAX_TARGET_CYCLES).await {
+                Ok(agent) => {
+                    log::debug!(
+                        "Marking {} ({}) with {}.",
+                        entry.target,
+                        agent.id,
+                        entry.marker
+                    );
+                    invoke_gamebind_async(bind, 10i32);
+                    sleep(TARGET_SETTLE).await;
+                }
+                Err(err) => {
+                    log::warn!("Couldn't place {} on {}: {err}", entry.marker, entry.target);
+                    failures.push(format!("{}: {err}", entry.marker));
+                }
+            }
+        }
+        if !failures.is_empty() {
+            let _ = rt_sender
+                .send(RenderEvent::OpenableError(
+                    format!("Error setting marker set: {}", &markers.name),
+                    anyhow!(failures.join("\n")),
+                ))
+                .await;
+        }
+    }
+
     async fn do_update(&mut self, source: &RemoteSource) {
-        match Settings::download_latest(source).await {
+        self.do_updates(&[source]).await;
+    }
+
+    // installs each of them, then reloads once and works out what they changed
+    async fn do_updates(&mut self, sources: &[&RemoteSource]) {
+        let mut updated = Vec::new();
+        for &source in sources {
+            let before = self
+                .sources_to_timers
+                .get(source)
+                .cloned()
+                .unwrap_or_default();
+            match Settings::download_latest(source).await {
+                Ok(_) => updated.push((source, before)),
+                Err(err) => log::error!("Controller.do_update() error for \"{}\": {}", source, err),
+            };
+        }
+        self.reload_data().await;
+        let mut settings_lock = self.settings.write().await;
+        for (source, before) in updated {
+            let after = self
+                .sources_to_timers
+                .get(source)
+                .cloned()
+                .unwrap_or_default();
+            let changes = TimerChanges::between(&before, &after);
+            settings_lock.record_timer_changes(source, changes).await;
+        }
+        drop(settings_lock);
+    }
+
+    async fn set_data_source_channel(&mut self, source: &RemoteSource, channel: Channel) {
+        let mut settings_lock = self.settings.write().await;
+        settings_lock.set_channel(source, channel).await;
+        drop(settings_lock);
+    }
+
+    async fn roll_back(&mut self, source: &RemoteSource) {
+        match Settings::roll_back(source).await {
             Ok(_) => (),
-            Err(err) => log::error!("Controller.do_update() error for \"{}\": {}", source, err),
+            Err(err) => log::error!("Controller.roll_back() error for \"{}\": {}", source, err),
         };
-        self.reload_timers().await;
+        self.reload_data().await;
     }
 
     async fn progress_bar_style(&mut self, style: ProgressBarStyleChange) {
@@ -1016,21 +1415,19 @@ impl Controller {
     }
 
     async fn load_texture(&self, rel: RelativePathBuf, base: PathBuf) {
-        if let Some(base) = base.parent() {
-            let abs = rel.to_path(base);
-            let cally: RawTextureReceiveCallback = texture_receive!(|id, texture| {
-                let gooey = IMGUI_TEXTURES.get().unwrap();
-                let mut gooey_lock = gooey.write().unwrap();
-                if let Some(texture) = texture {
-                    gooey_lock
-                        .entry(id.into())
-                        .or_insert(Arc::new(texture.clone()));
-                }
-                drop(gooey_lock);
-                log::info!("Texture {id} loaded.");
-            });
-            load_texture_from_file(rel.as_str(), abs, Some(cally));
-        }
+        let abs = rel.to_path(base);
+        let cally: RawTextureReceiveCallback = texture_receive!(|id, texture| {
+            let gooey = IMGUI_TEXTURES.get().unwrap();
+            let mut gooey_lock = gooey.write().unwrap();
+            if let Some(texture) = texture {
+                gooey_lock
+                    .entry(id.into())
+                    .or_insert(Arc::new(texture.clone()));
+            }
+            drop(gooey_lock);
+            log::info!("Texture {id} loaded.");
+        });
+        load_texture_from_file(rel.as_str(), abs, Some(cally));
     }
 
     async fn reset_timers(&mut self) {
@@ -1049,7 +1446,28 @@ impl Controller {
                 RuntimeMarkers::create(&p, ft, ms).await?;
             }
             MarkerSaveEvent::Edit(ms, p, oc, idx) => {
-                RuntimeMarkers::edit(ms, &p, oc, idx).await?;
+                // whatever the set was known as before, its settings follow it
+                let previous = self
+                    .markers
+                    .values()
+                    .flatten()
+                    .find(|set| {
+                        set.path.as_ref() == Some(&p) && set.idx == Some(idx) && set.category == oc
+                    })
+                    .map(|set| set.id());
+                let uuid = RuntimeMarkers::edit(ms, &p, oc, idx).await?;
+                if let Some(previous) = previous {
+                    let mut settings_lock = self.settings.write().await;
+                    settings_lock
+                        .rekey_marker(&previous, &uuid.to_string())
+                        .await;
+                }
+            }
+            MarkerSaveEvent::Import(sets, p, Some(ft)) => {
+                RuntimeMarkers::create_many(&p, ft, sets).await?;
+            }
+            MarkerSaveEvent::Import(sets, p, None) => {
+                RuntimeMarkers::append_many(&p, sets).await?;
             }
         }
         self.reload_markers().await;
@@ -1068,6 +1486,49 @@ impl Controller {
         Ok(())
     }
 
+    #[cfg(feature = "markers-edit")]
+    async fn convert_markers(
+        &mut self,
+        inputs: Vec<PathBuf>,
+        filetype: MarkerFiletype,
+        dedupe: bool,
+        output: &Path,
+        set_aside: bool,
+    ) -> anyhow::Result<()> {
+        use {crate::marker::convert::merge, tokio::fs::rename};
+        let addon_dir = get_addon_dir("Taimi").expect("Invalid addon dir");
+        let output = addon_dir
+            .join("markers")
+            .join(RuntimeMarkers::file_name(output)?);
+        // reading and writing every file in one go, so not on the controller's thread
+        let (output, summary) = tokio::task::spawn_blocking({
+            let inputs = inputs.clone();
+            move || -> anyhow::Result<_> {
+                let (file, summary) = merge(&inputs, filetype, dedupe)?;
+                file.write(&output)?;
+                Ok((output, summary))
+            }
+        })
+        .await??;
+        // left as they were, they'd load alongside the merged file
+        if set_aside {
+            for input in inputs.iter().filter(|input| **input != output) {
+                let mut aside = input.clone().into_os_string();
+                aside.push(".bak");
+                rename(input, aside).await?;
+            }
+        }
+        log::info!(
+            "Merged {} markers files into {}: {} sets, {} duplicates dropped.",
+            summary.files,
+            output.display(),
+            summary.sets,
+            summary.duplicates
+        );
+        self.reload_markers().await;
+        Ok(())
+    }
+
     #[cfg(feature = "markers-edit")]
     async fn get_marker_paths(&self) -> anyhow::Result<()> {
         let addon_dir = get_addon_dir("Taimi").expect("Invalid addon dir");
@@ -1211,6 +1672,7 @@ impl Controller {
             ClearMarkers => self.clear_markers().await,
             ReloadData => self.reload_data().await,
             ReloadTimers => self.reload_timers().await,
+            TimerFilesChanged(paths) => self.timer_files_changed(paths).await,
             #[cfg(feature = "markers")]
             MarkerAutoPlaceSettings(maps) => self.set_marker_autoplace_settings(maps).await?,
             #[cfg(feature = "markers")]
@@ -1219,18 +1681,26 @@ impl Controller {
             OpenOpenable(key, uri) => self.open_openable(key, uri).await,
             UninstallAddon(dd) => self.uninstall_addon(&dd).await?,
             MumbleIdentityUpdated(identity) => self.handle_mumble(identity).await,
-            CombatEvent { src, evt } => self.handle_combat_event(src, evt).await,
-            TimerEnable(id) => self.enable_timer(&id).await,
-            TimerDisable(id) => self.disable_timer(&id).await,
-            TimerToggle(id) => self.toggle_timer(&id).await,
+            CombatEvent { src, dst, evt } => self.handle_combat_event(src, dst, evt).await,
+            #[cfg(feature = "markers")]
+            AgentTracking { src, dst } => self.handle_agent_tracking(src, dst).await,
+            TimerEnable(scope) => self.set_timers_enabled(scope, Some(true)).await,
+            TimerDisable(scope) => self.set_timers_enabled(scope, Some(false)).await,
+            TimerToggle(scope) => self.set_timers_enabled(scope, None).await,
             TimerReset => self.reset_timers().await,
             CheckDataSourceUpdates => self.check_updates().await,
+            UpdateSettings(updates) => self.set_update_settings(updates).await,
+            SetGitHubToken(token) => self.set_github_token(token).await,
             #[cfg(feature = "markers")]
             SetMarker(t) => {
                 self.set_marker(&t);
             }
             TimerKeyTrigger(id, is_release) => self.timer_key_trigger(id, is_release).await,
             DoDataSourceUpdate { source } => self.do_update(&source).await,
+            RollBackDataSource { source } => self.roll_back(&source).await,
+            SetDataSourceChannel { source, channel } => {
+                self.set_data_source_channel(&source, channel).await
+            }
             ProgressBarStyle(style) => self.progress_bar_style(style).await,
             WindowState(window, state) => self.set_window_state(window, state).await,
             LoadTexture(rel, base) => self.load_texture(rel, base).await,
@@ -1247,6 +1717,27 @@ impl Controller {
             } => self.delete_marker(&path, category, idx).await?,
             #[cfg(feature = "markers-edit")]
             GetMarkerPaths => self.get_marker_paths().await?,
+            #[cfg(feature = "markers-edit")]
+            ConvertMarkers {
+                inputs,
+                filetype,
+                dedupe,
+                output,
+                set_aside,
+            } => {
+                if let Err(err) = self
+                    .convert_markers(inputs, filetype, dedupe, &output, set_aside)
+                    .await
+                {
+                    let _ = self
+                        .rt_sender
+                        .send(RenderEvent::OpenableError(
+                            format!("Error converting markers into {}", output.display()),
+                            err,
+                        ))
+                        .await;
+                }
+            }
             Quit => return Ok(false),
             // I forget why we needed this, but I think it's a holdover from the buttplug one o:
             //_ => (),
@@ -1276,6 +1767,8 @@ pub enum MarkerSaveEvent {
     Append(MarkerSet, PathBuf),
     Create(MarkerSet, PathBuf, MarkerFiletype),
     Edit(MarkerSet, PathBuf, Option<String>, usize),
+    // from a share code, into a new file when there's a filetype
+    Import(Vec<MarkerSet>, PathBuf, Option<MarkerFiletype>),
 }
 
 #[derive(Debug, Clone, Display)]
@@ -1310,16 +1803,40 @@ pub enum ControllerEvent {
     },
     #[cfg(feature = "markers-edit")]
     GetMarkerPaths,
+    // merges .markers files into a new one, setting the originals aside
+    #[cfg(feature = "markers-edit")]
+    ConvertMarkers {
+        inputs: Vec<PathBuf>,
+        filetype: MarkerFiletype,
+        dedupe: bool,
+        output: PathBuf,
+        // rename the inputs to .bak afterwards, so they stop loading
+        set_aside: bool,
+    },
     UninstallAddon(Arc<RemoteSource>),
     MumbleIdentityUpdated(MumbleIdentityUpdate),
     ToggleKatRender,
     CombatEvent {
         src: arcdps::AgentOwned,
+        dst: Option<arcdps::AgentOwned>,
         evt: arcEvent,
     },
+    // arcdps events without a cbtevent, for target changes and squad members
+    #[cfg(feature = "markers")]
+    AgentTracking {
+        src: arcdps::AgentOwned,
+        dst: Option<arcdps::AgentOwned>,
+    },
     DoDataSourceUpdate {
         source: Arc<RemoteSource>,
     },
+    RollBackDataSource {
+        source: Arc<RemoteSource>,
+    },
+    SetDataSourceChannel {
+        source: Arc<RemoteSource>,
+        channel: Channel,
+    },
     ProgressBarStyle(ProgressBarStyleChange),
     WindowState(String, Option<bool>),
     #[strum(to_string = "Id {0}, pressed {1}")]
@@ -1327,7 +1844,10 @@ pub enum ControllerEvent {
     LoadTextureIntegrated(String, Vec<u8>),
     LoadTexture(RelativePathBuf, PathBuf),
     CheckDataSourceUpdates,
+    UpdateSettings(UpdateSettings),
+    SetGitHubToken(Option<String>),
     ReloadTimers,
+    TimerFilesChanged(Vec<PathBuf>),
     #[cfg(feature = "markers")]
     ReloadMarkers,
     ReloadData,
@@ -1342,12 +1862,96 @@ pub enum ControllerEvent {
     #[allow(dead_code)]
     MarkerDisable(String),
 
-    #[allow(dead_code)]
-    TimerEnable(String),
-    #[allow(dead_code)]
-    TimerDisable(String),
+    #[strum(to_string = "Enabled {0}")]
+    TimerEnable(TimerScope),
+    #[strum(to_string = "Disabled {0}")]
+    TimerDisable(TimerScope),
     TimerReset,
     #[strum(to_string = "Toggled {0}")]
-    TimerToggle(String),
+    TimerToggle(TimerScope),
     Quit,
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::timer::state_machine::MachineRecorder,
+        serde_json::json,
+        std::fs::{create_dir, write},
+        tokio::sync::mpsc::channel,
+    };
+
+    fn timer(id: &str, name: &str) -> String {
+        json!({
+            "id": id,
+            "name": name,
+            "category": "Tests",
+            "description": "",
+            "author": "",
+            "icon": "icon.png",
+            "map": 1,
+            "reset": { "position": [0, 0, 0], "radius": 50, "requireDeparture": true },
+            "phases": [{ "name": "Only", "start": { "position": [0, 0, 0], "radius": 10 } }]
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn reloads_only_the_changed_timer() {
+        let dir = tempfile::tempdir().unwrap();
+        create_dir(dir.path().join("sub")).unwrap();
+        let ids = ["a", "b", "c"];
+        for id in ids {
+            write(dir.path().join(format!("{id}.bhtimer")), timer(id, id)).unwrap();
+        }
+        let (sender, _receiver) = channel(16);
+        let mut controller = Controller::new(sender, Default::default(), None);
+        controller.map_id = Some(1);
+        let mut recorders = HashMap::new();
+        for id in ids {
+            // loaded the long way round, the watcher will say where it really is
+            let path = dir.path().join("sub/..").join(format!("{id}.bhtimer"));
+            let timer = TimerFile::load(&path, None, None).await.unwrap();
+            controller.index_timer(&timer);
+            controller.timers.push(timer.clone());
+            let recorder = MachineRecorder::default();
+            let mut machine = controller
+                .timer_machine(timer)
+                .with_recorder(recorder.clone());
+            machine.update_on_map(1);
+            // into the first phase, which a fresh machine wouldn't be in
+            machine.tick(Position::Vec3(Vec3::ZERO)).await;
+            controller.current_timers.push(machine);
+            recorders.insert(id, recorder);
+        }
+        let before = controller.timers.clone();
+        let seen: HashMap<_, _> = recorders
+            .iter()
+            .map(|(&id, recorder)| (id, recorder.lock().unwrap().len()))
+            .collect();
+
+        write(dir.path().join("a.bhtimer"), timer("a", "a, edited")).unwrap();
+        let resolved = dir.path().canonicalize().unwrap().join("a.bhtimer");
+        controller.timer_files_changed(vec![resolved]).await;
+        controller.reload_changed_timers().await;
+
+        assert_eq!(controller.timers.len(), 3);
+        assert_eq!(controller.current_timers.len(), 3);
+        assert_eq!(controller.map_id_to_timers[&1].len(), 3);
+        let edited = controller.timers.iter().find(|t| t.id == "a").unwrap();
+        assert_eq!(edited.name, "a, edited");
+        for old in &before {
+            let kept = controller
+                .current_timers
+                .iter()
+                .any(|m| Arc::ptr_eq(&m.timer, old));
+            assert_eq!(kept, old.id != "a", "{}", old.id);
+        }
+        // the edited one's old machine was cleaned up, the others weren't touched
+        for (id, recorder) in recorders {
+            let touched = recorder.lock().unwrap().len() > seen[id];
+            assert_eq!(touched, id == "a", "{id}");
+        }
+    }
+}
@@ -1,3 +1,4 @@
+mod audio;
 mod controller;
 mod render;
 mod settings;
@@ -11,8 +12,6 @@ mod marker;
 mod space;
 
 //use i18n_embed_fl::fl;
-#[cfg(feature = "space")]
-use space::{engine::SpaceEvent, resources::Texture, Engine};
 use {
     crate::{
         controller::{Controller, ControllerEvent},
@@ -28,7 +27,7 @@ use {
     marker::format::MarkerType,
     nexus::{
         event::{
-            arc::{CombatData, ACCOUNT_NAME, COMBAT_LOCAL},
+            arc::{CombatData, ACCOUNT_NAME, COMBAT_LOCAL, COMBAT_SQUAD},
             event_consume,
             extras::{SquadUpdate, EXTRAS_SQUAD_UPDATE},
             Event, MumbleIdentityUpdate, MUMBLE_IDENTITY_UPDATED,
@@ -58,9 +57,15 @@ use {
         sync::{Arc, LazyLock, Mutex, OnceLock, RwLock},
         thread::{self, JoinHandle},
     },
+    timer::{combat::SeenEvents, CombatEventInfo},
     tokio::sync::mpsc::{channel, Sender},
     unic_langid_impl::LanguageIdentifier,
 };
+#[cfg(feature = "space")]
+use {
+    settings::SourceKind,
+    space::{engine::SpaceEvent, resources::Texture, Engine},
+};
 
 // https://github.com/kellpossible/cargo-i18n/blob/95634c35eb68643d4a08ff4cd17406645e428576/i18n-embed/examples/library-fluent/src/lib.rs
 #[derive(RustEmbed)]
@@ -99,6 +104,17 @@ pub fn localizer() -> DefaultLocalizer<'static> {
     DefaultLocalizer::new(&*LANGUAGE_LOADER, &*LOCALIZATIONS)
 }
 
+// all the tools in src/bin get to see of the addon
+pub mod tools {
+    pub use crate::timer::{lint_dir, lint_file, Diagnostic, FileReport, Severity};
+
+    #[cfg(feature = "markers")]
+    pub use crate::marker::{
+        convert::{merge, ConvertSummary},
+        format::{MarkerFiletype, MarkerFormats},
+    };
+}
+
 pub mod built_info {
     include!(concat!(env!("OUT_DIR"), "/built.rs"));
 }
@@ -114,6 +130,7 @@ static ACCOUNT_NAME_CELL: OnceLock<String> = OnceLock::new();
 static SPACE_SENDER: OnceLock<Sender<SpaceEvent>> = OnceLock::new();
 
 static CONTROLLER_THREAD: OnceLock<JoinHandle<()>> = OnceLock::new();
+static COMBAT_SEEN: LazyLock<Mutex<SeenEvents>> = LazyLock::new(Default::default);
 
 nexus::export! {
     name: "TaimiHUD",
@@ -160,6 +177,50 @@ fn marker_icon_data(marker_type: MarkerType) -> Option<Vec<u8>> {
     }
 }
 
+fn forward_combat(cdata: Option<&CombatData>, area: bool) {
+    let Some(combat_data) = cdata else {
+        return;
+    };
+    let sender = CONTROLLER_SENDER.get().unwrap();
+    if let Some(evt) = combat_data.event() {
+        if let Some(agt) = combat_data.src() {
+            let agt = AgentOwned::from(unsafe { ptr::read(agt) });
+            let dst = combat_data
+                .dst()
+                .map(|dst| AgentOwned::from(unsafe { ptr::read(dst) }));
+            if area && !CombatEventInfo::npc_event(&agt, dst.as_ref(), evt) {
+                return;
+            }
+            let first = COMBAT_SEEN
+                .lock()
+                .map(|mut seen| seen.first_sighting(combat_data.id()))
+                .unwrap_or(true);
+            if !first {
+                return;
+            }
+            let event_send = sender.try_send(ControllerEvent::CombatEvent {
+                src: agt,
+                dst,
+                evt: evt.clone(),
+            });
+            drop(event_send);
+        }
+    } else if let Some(agt) = combat_data.src() {
+        // no event means it's agent tracking: target changes and squad members
+        #[cfg(feature = "markers")]
+        {
+            let agt = AgentOwned::from(unsafe { ptr::read(agt) });
+            let dst = combat_data
+                .dst()
+                .map(|dst| AgentOwned::from(unsafe { ptr::read(dst) }));
+            let event_send = sender.try_send(ControllerEvent::AgentTracking { src: agt, dst });
+            drop(event_send);
+        }
+        #[cfg(not(feature = "markers"))]
+        let _ = agt;
+    }
+}
+
 fn load() {
     let _ = IMGUI_TEXTURES.set(RwLock::new(HashMap::new()));
     #[cfg(feature = "space")]
@@ -174,6 +235,13 @@ fn load() {
 
     reload_language();
 
+    match audio::RodioSink::new() {
+        Ok(sink) => {
+            let _ = audio::AUDIO_SINK.set(Arc::new(sink));
+        }
+        Err(err) => log::error!("Couldn't set up audio, sounds will be silent: {err}"),
+    }
+
     let (controller_sender, controller_receiver) = channel::<ControllerEvent>(32);
     let (render_sender, render_receiver) = channel::<RenderEvent>(32);
 
@@ -198,11 +266,16 @@ fn load() {
     #[cfg(feature = "space")]
     let space_render = render!(|ui| {
         if let Some(settings) = SETTINGS.get().and_then(|settings| settings.try_read().ok()) {
-            if settings.enable_katrender {
+            if settings.config.enable_katrender {
                 if !ENGINE_INITIALIZED.get() {
                     let (space_sender, space_receiver) = channel::<SpaceEvent>(32);
                     let _ = SPACE_SENDER.set(space_sender);
-                    let drawstate_inner = Engine::initialise(ui, space_receiver);
+                    let pack_dirs = settings
+                        .get_paths_for(SourceKind::Pathing)
+                        .into_iter()
+                        .cloned()
+                        .collect();
+                    let drawstate_inner = Engine::initialise(ui, space_receiver, pack_dirs);
                     if let Err(error) = &drawstate_inner {
                         log::error!("DrawState setup failed: {error:?}");
                     };
@@ -324,22 +397,17 @@ fn load() {
         }))
         .revert_on_unload();
 
-    let combat_callback = event_consume!(|cdata: Option<&CombatData>| {
-        let sender = CONTROLLER_SENDER.get().unwrap();
-        if let Some(combat_data) = cdata {
-            if let Some(evt) = combat_data.event() {
-                if let Some(agt) = combat_data.src() {
-                    let agt = AgentOwned::from(unsafe { ptr::read(agt) });
-                    let event_send = sender.try_send(ControllerEvent::CombatEvent {
-                        src: agt,
-                        evt: evt.clone(),
-                    });
-                    drop(event_send);
-                }
-            }
-        }
-    });
-    COMBAT_LOCAL.subscribe(combat_callback).revert_on_unload();
+    // arcdps' local feed has what we get up to, the area one what the bosses do
+    COMBAT_LOCAL
+        .subscribe(event_consume!(|cdata: Option<&CombatData>| {
+            forward_combat(cdata, false);
+        }))
+        .revert_on_unload();
+    COMBAT_SQUAD
+        .subscribe(event_consume!(|cdata: Option<&CombatData>| {
+            forward_combat(cdata, true);
+        }))
+        .revert_on_unload();
 
     // MumbleLink Identity
     MUMBLE_IDENTITY_UPDATED
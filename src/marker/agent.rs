@@ -0,0 +1,314 @@
+use {
+    arcdps::AgentOwned,
+    serde::{Deserialize, Serialize},
+    std::{collections::HashMap, fmt, future::Future},
+};
+
+// arcdps puts this in elite for anything that isn't a player
+const NPC_ELITE: u32 = u32::MAX;
+// and this in the top of prof for gadgets, which have no species
+const GADGET_PROF: u32 = 0xffff;
+
+/*
+ * Who an agent marker goes on. Species ids are the ones arcdps reports for
+ * NPCs, so "put Star on the boss" is the boss's species id.
+ */
+#[derive(Hash, Eq, PartialEq, Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AgentTarget {
+    // whatever's targeted when the set's placed
+    CurrentTarget,
+    Species { id: u32 },
+    // someone in the squad, "Arrow on the tank"
+    Account { name: String },
+}
+
+impl AgentTarget {
+    pub fn matches(&self, agent: &KnownAgent) -> bool {
+        use AgentTarget::*;
+        match (self, &agent.kind) {
+            (CurrentTarget, _) => true,
+            (Species { id }, AgentKind::Npc { species }) => id == species,
+            (
+                Account { name },
+                AgentKind::Player {
+                    account: Some(account),
+                },
+            ) => {
+                // arcdps has the leading colon, people typing it in might not
+                account.trim_start_matches(':') == name.trim_start_matches(':')
+            }
+            _ => false,
+        }
+    }
+
+    // which way to cycle targets looking for it
+    pub fn ally(&self) -> bool {
+        matches!(self, Self::Account { .. })
+    }
+}
+
+impl fmt::Display for AgentTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use AgentTarget::*;
+        match self {
+            CurrentTarget => write!(f, "the current target"),
+            Species { id } => write!(f, "species {id}"),
+            Account { name } => write!(f, "{name}"),
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum AgentKind {
+    Player { account: Option<String> },
+    Npc { species: u32 },
+    Gadget,
+    // targeted, but we've not seen it in any event yet
+    Unknown,
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct KnownAgent {
+    pub id: usize,
+    pub kind: AgentKind,
+}
+
+impl KnownAgent {
+    pub fn from_arc(agent: &AgentOwned) -> Self {
+        let kind = match agent.elite {
+            NPC_ELITE if agent.prof >> 16 == GADGET_PROF => AgentKind::Gadget,
+            NPC_ELITE => AgentKind::Npc {
+                species: agent.prof & 0xffff,
+            },
+            _ => AgentKind::Player { account: None },
+        };
+        Self { id: agent.id, kind }
+    }
+}
+
+/*
+ * What arcdps has told us about agents and the current target. Fed from
+ * the controller, read from the marker placement task.
+ */
+#[derive(Default, Debug, Clone)]
+pub struct AgentTracker {
+    target: Option<usize>,
+    agents: HashMap<usize, KnownAgent>,
+}
+
+impl AgentTracker {
+    // every agent that shows up in a combat event, to learn NPC species
+    pub fn seen(&mut self, agent: &AgentOwned) {
+        if agent.id == 0 {
+            return;
+        }
+        self.agents
+            .entry(agent.id)
+            .or_insert_with(|| KnownAgent::from_arc(agent));
+    }
+
+    /*
+     * The events arcdps sends without a cbtevent: with elite 1 it's a target
+     * change, otherwise a squad member being added (dst holds the account
+     * name) or, with no prof, removed.
+     */
+    pub fn tracking(&mut self, src: &AgentOwned, dst: Option<&AgentOwned>) {
+        match (src.elite, src.prof) {
+            (1, _) => self.target = (src.id != 0).then_some(src.id),
+            (0, 0) => {
+                self.agents.remove(&src.id);
+            }
+            (0, _) => {
+                let account = dst.and_then(|dst| dst.name.clone());
+                self.agents.insert(
+                    src.id,
+                    KnownAgent {
+                        id: src.id,
+                        kind: AgentKind::Player { account },
+                    },
+                );
+            }
+            _ => (),
+        }
+    }
+
+    pub fn current_target(&self) -> Option<KnownAgent> {
+        let id = self.target?;
+        Some(self.agents.get(&id).cloned().unwrap_or(KnownAgent {
+            id,
+            kind: AgentKind::Unknown,
+        }))
+    }
+
+    pub fn clear(&mut self) {
+        *self = Default::default();
+    }
+}
+
+/*
+ * What selecting an agent gets to see and do. In game that's the tracker
+ * and the targeting gamebinds, anywhere else it can be anything that acts
+ * like them.
+ */
+pub trait AgentSelection {
+    fn current_target(&self) -> Option<KnownAgent>;
+    // moves on to the next target, and waits for the game to have done it
+    fn target_next(&mut self, ally: bool) -> impl Future<Output = ()> + Send;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectError {
+    NoTarget,
+    NotFound(AgentTarget),
+}
+
+impl fmt::Display for SelectError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use SelectError::*;
+        match self {
+            NoTarget => write!(f, "Nothing is targeted"),
+            NotFound(target) => write!(f, "Couldn't find {target} to target"),
+        }
+    }
+}
+
+impl std::error::Error for SelectError {}
+
+/*
+ * Leaves the wanted agent targeted, cycling through targets until it comes
+ * up. Gives up once it's gone round `max_cycles` times, or come back around
+ * to wherever it started.
+ */
+pub async fn select_agent<S: AgentSelection>(
+    selection: &mut S,
+    target: &AgentTarget,
+    max_cycles: usize,
+) -> Result<KnownAgent, SelectError> {
+    let start = selection.current_target();
+    if let AgentTarget::CurrentTarget = target {
+        return start.ok_or(SelectError::NoTarget);
+    }
+    if let Some(agent) = start.as_ref().filter(|agent| target.matches(agent)) {
+        return Ok(agent.clone());
+    }
+    for _ in 0..max_cycles {
+        selection.target_next(target.ally()).await;
+        let current = selection.current_target();
+        match current {
+            Some(agent) if target.matches(&agent) => return Ok(agent),
+            Some(agent) if start.as_ref().is_some_and(|start| start.id == agent.id) => break,
+            _ => (),
+        }
+    }
+    Err(SelectError::NotFound(target.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // targets go round in a circle, like tab does in game
+    struct FakeSelection {
+        agents: Vec<KnownAgent>,
+        target: Option<usize>,
+        cycles: usize,
+    }
+
+    impl FakeSelection {
+        fn new(species: &[u32], target: Option<usize>) -> Self {
+            let agents = species
+                .iter()
+                .enumerate()
+                .map(|(id, &species)| KnownAgent {
+                    id: id + 1,
+                    kind: AgentKind::Npc { species },
+                })
+                .collect();
+            Self {
+                agents,
+                target,
+                cycles: 0,
+            }
+        }
+    }
+
+    impl AgentSelection for FakeSelection {
+        fn current_target(&self) -> Option<KnownAgent> {
+            self.target.map(|i| self.agents[i].clone())
+        }
+
+        async fn target_next(&mut self, _ally: bool) {
+            self.cycles += 1;
+            self.target = Some(self.target.map_or(0, |i| (i + 1) % self.agents.len()));
+        }
+    }
+
+    #[tokio::test]
+    async fn cycles_to_a_match() {
+        let mut selection = FakeSelection::new(&[10, 20, 30, 40], Some(0));
+        let agent = select_agent(&mut selection, &AgentTarget::Species { id: 30 }, 25)
+            .await
+            .unwrap();
+        assert_eq!(agent.id, 3);
+        assert_eq!(selection.cycles, 2);
+
+        // already on it, so nothing needs to change
+        let agent = select_agent(&mut selection, &AgentTarget::Species { id: 30 }, 25)
+            .await
+            .unwrap();
+        assert_eq!(agent.id, 3);
+        assert_eq!(selection.cycles, 2);
+    }
+
+    #[tokio::test]
+    async fn stops_after_coming_back_around() {
+        let mut selection = FakeSelection::new(&[10, 20, 30, 40], Some(1));
+        let missing = AgentTarget::Species { id: 99 };
+        assert_eq!(
+            select_agent(&mut selection, &missing, 25).await,
+            Err(SelectError::NotFound(missing))
+        );
+        assert_eq!(selection.cycles, 4);
+        assert_eq!(selection.target, Some(1));
+    }
+
+    #[tokio::test]
+    async fn stops_at_max_cycles() {
+        // nothing targeted to start with, so there's no coming back around
+        let mut selection = FakeSelection::new(&[10, 20, 30], None);
+        let missing = AgentTarget::Species { id: 99 };
+        assert!(select_agent(&mut selection, &missing, 7).await.is_err());
+        assert_eq!(selection.cycles, 7);
+
+        let mut selection = FakeSelection::new(&[10, 20, 30, 40], Some(0));
+        let far = AgentTarget::Species { id: 40 };
+        assert!(select_agent(&mut selection, &far, 2).await.is_err());
+        assert_eq!(selection.cycles, 2);
+    }
+
+    #[tokio::test]
+    async fn current_target_needs_a_target() {
+        let mut selection = FakeSelection::new(&[10], None);
+        assert_eq!(
+            select_agent(&mut selection, &AgentTarget::CurrentTarget, 25).await,
+            Err(SelectError::NoTarget)
+        );
+        assert_eq!(selection.cycles, 0);
+    }
+
+    #[test]
+    fn accounts_match_with_or_without_the_colon() {
+        let agent = KnownAgent {
+            id: 1,
+            kind: AgentKind::Player {
+                account: Some(":Taimi.1234".to_string()),
+            },
+        };
+        let target = AgentTarget::Account {
+            name: "Taimi.1234".to_string(),
+        };
+        assert!(target.matches(&agent));
+        assert!(target.ally());
+    }
+}
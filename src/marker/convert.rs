@@ -0,0 +1,230 @@
+use {
+    crate::marker::format::{
+        IntegratedMarkers, MarkerCategory, MarkerFile, MarkerFiletype, MarkerFormats, MarkerSet,
+    },
+    chrono::Utc,
+    std::{
+        fs::{read_to_string, write},
+        path::Path,
+        str::FromStr,
+    },
+};
+
+/*
+ * Conversion between the three kinds of .markers file, without the game or
+ * the addon directory around, so the marker tab and taimi-markers can share it.
+ */
+
+const NO_CATEGORY: &str = "No category";
+
+impl FromStr for MarkerFiletype {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "community" => Ok(Self::Community),
+            "integrated" => Ok(Self::Integrated),
+            "taimi" => Ok(Self::Taimi),
+            _ => Err(anyhow::anyhow!(
+                "Unknown markers format {s:?}, expected community, integrated or taimi"
+            )),
+        }
+    }
+}
+
+impl MarkerFormats {
+    pub fn filetype(&self) -> MarkerFiletype {
+        match self {
+            Self::Community(_) => MarkerFiletype::Community,
+            Self::Integrated(_) => MarkerFiletype::Integrated,
+            Self::Taimi(_) => MarkerFiletype::Taimi,
+        }
+    }
+
+    // every set in the file, each carrying the category it was filed under
+    pub fn sets(&self) -> Vec<MarkerSet> {
+        match self {
+            Self::Community(f) => f
+                .categories
+                .iter()
+                .flat_map(|category| {
+                    category.marker_sets.iter().map(|ms| MarkerSet {
+                        category: Some(category.name.clone()),
+                        ..ms.clone()
+                    })
+                })
+                .collect(),
+            Self::Integrated(c) => c.squad_marker_preset.clone(),
+            Self::Taimi(t) => t.clone(),
+        }
+    }
+
+    pub fn from_sets(filetype: MarkerFiletype, sets: Vec<MarkerSet>) -> Self {
+        match filetype {
+            MarkerFiletype::Community => {
+                let mut categories: Vec<MarkerCategory> = Vec::new();
+                for ms in sets {
+                    let name = ms.category.clone().unwrap_or(NO_CATEGORY.to_string());
+                    match categories.iter_mut().find(|c| c.name == name) {
+                        Some(category) => category.marker_sets.push(ms),
+                        None => categories.push(MarkerCategory {
+                            name,
+                            marker_sets: vec![ms],
+                        }),
+                    }
+                }
+                Self::Community(MarkerFile {
+                    last_edit: Utc::now(),
+                    path: None,
+                    categories,
+                })
+            }
+            MarkerFiletype::Integrated => Self::Integrated(IntegratedMarkers {
+                version: "2.0.0".to_string(),
+                path: None,
+                squad_marker_preset: sets,
+            }),
+            MarkerFiletype::Taimi => Self::Taimi(sets),
+        }
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let mut file_data = read_to_string(path)?;
+        json_strip_comments::strip(&mut file_data)?;
+        Ok(serde_json::from_str(&file_data)?)
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+// keeps the first of each set that's in there more than once
+pub fn dedupe(sets: Vec<MarkerSet>) -> (Vec<MarkerSet>, usize) {
+    let mut kept: Vec<MarkerSet> = Vec::new();
+    let mut removed = 0;
+    for ms in sets {
+        if kept.iter().any(|k| k.same_as(&ms)) {
+            removed += 1;
+        } else {
+            kept.push(ms);
+        }
+    }
+    (kept, removed)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConvertSummary {
+    pub files: usize,
+    pub sets: usize,
+    pub duplicates: usize,
+}
+
+/*
+ * Reads every input, in order, into one file of the given format. Nothing is
+ * written here, so an input can safely be the output too.
+ */
+pub fn merge(
+    inputs: &[impl AsRef<Path>],
+    filetype: MarkerFiletype,
+    remove_duplicates: bool,
+) -> anyhow::Result<(MarkerFormats, ConvertSummary)> {
+    let mut sets = Vec::new();
+    for input in inputs {
+        let input = input.as_ref();
+        let file = MarkerFormats::read(input)
+            .map_err(|err| anyhow::anyhow!("{}: {err}", input.display()))?;
+        sets.extend(file.sets());
+    }
+    let mut summary = ConvertSummary {
+        files: inputs.len(),
+        ..Default::default()
+    };
+    if remove_duplicates {
+        let (kept, removed) = dedupe(sets);
+        sets = kept;
+        summary.duplicates = removed;
+    }
+    summary.sets = sets.len();
+    Ok((MarkerFormats::from_sets(filetype, sets), summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::path::PathBuf};
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/markers");
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(FIXTURES).join(format!("{name}.markers"))
+    }
+
+    fn fixtures() -> Vec<PathBuf> {
+        ["community", "integrated", "taimi"].map(fixture).to_vec()
+    }
+
+    #[test]
+    fn each_format_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, filetype) in [
+            ("community", MarkerFiletype::Community),
+            ("integrated", MarkerFiletype::Integrated),
+            ("taimi", MarkerFiletype::Taimi),
+        ] {
+            let file = MarkerFormats::read(&fixture(name)).unwrap();
+            assert_eq!(file.filetype(), filetype, "{name}");
+            let sets = file.sets();
+            assert_eq!(sets.len(), 2, "{name}");
+
+            let written = dir.path().join(format!("{name}.markers"));
+            MarkerFormats::from_sets(filetype.clone(), sets.clone())
+                .write(&written)
+                .unwrap();
+            let read_back = MarkerFormats::read(&written).unwrap();
+            assert_eq!(read_back.filetype(), filetype, "{name}");
+            assert_eq!(read_back.sets(), sets, "{name}");
+        }
+    }
+
+    #[test]
+    fn community_sets_keep_their_category() {
+        let sets = MarkerFormats::read(&fixture("community")).unwrap().sets();
+        assert!(sets
+            .iter()
+            .all(|ms| ms.category.as_deref() == Some("Spirit Vale")));
+    }
+
+    #[test]
+    fn merges_without_duplicates() {
+        // Sabetha's in all three, with a uuid only in the taimi one
+        let (file, summary) = merge(&fixtures(), MarkerFiletype::Taimi, true).unwrap();
+        assert_eq!(summary.files, 3);
+        assert_eq!(summary.duplicates, 2);
+        assert_eq!(summary.sets, 4);
+        let names: Vec<String> = file.sets().into_iter().map(|ms| ms.name).collect();
+        assert_eq!(names, ["Vale Guardian", "Sabetha", "Gorseval", "Matthias"]);
+
+        let (file, summary) = merge(&fixtures(), MarkerFiletype::Taimi, false).unwrap();
+        assert_eq!(summary.duplicates, 0);
+        assert_eq!(file.sets().len(), 6);
+    }
+
+    #[test]
+    fn merged_files_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        for filetype in [
+            MarkerFiletype::Community,
+            MarkerFiletype::Integrated,
+            MarkerFiletype::Taimi,
+        ] {
+            let (file, _) = merge(&fixtures(), filetype.clone(), true).unwrap();
+            let written = dir.path().join(format!("{filetype}.markers"));
+            file.write(&written).unwrap();
+            // and nothing new turns up as a duplicate once it's been through a file
+            let (read_back, summary) = merge(&[&written], filetype, true).unwrap();
+            assert_eq!(summary.duplicates, 0);
+            assert_eq!(read_back.sets(), file.sets());
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use {
     crate::{
+        marker::agent::AgentTarget,
         render::RenderState,
-        timer::{BlishVec3, Polytope, Position},
+        timer::{BlishArea, BlishVec3, Polytope, Position, TimerTrigger},
         SETTINGS,
     },
     anyhow::anyhow,
@@ -10,12 +11,12 @@ use {
     glob::Paths,
     nexus::{gamebind::GameBind, imgui::Ui, paths::get_addon_dir},
     ordered_float::OrderedFloat,
-    serde::{Deserialize, Serialize},
+    serde::{de, Deserialize, Deserializer, Serialize},
     serde_repr::{Deserialize_repr, Serialize_repr},
     std::{
         collections::HashMap,
         fs::exists,
-        path::{Path, PathBuf},
+        path::{Component, Path, PathBuf},
         sync::Arc,
     },
     strum::IntoEnumIterator,
@@ -26,6 +27,7 @@ use {
         sync::Semaphore,
         task::JoinSet,
     },
+    uuid::Uuid,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -57,6 +59,30 @@ impl RuntimeMarkers {
         "**/*.markers".to_string()
     }
 
+    /*
+     * The file a name typed in for saving ends up as, relative to the markers
+     * folder. Has to stay inside it, and gets the one .markers on the end
+     * whether or not it was typed in already.
+     */
+    pub fn file_name(name: &Path) -> anyhow::Result<PathBuf> {
+        let name = name
+            .to_str()
+            .ok_or_else(|| anyhow!("{} isn't unicode", name.display()))?;
+        let name = Path::new(name.strip_suffix(".markers").unwrap_or(name));
+        let inside = name
+            .components()
+            .all(|part| matches!(part, Component::Normal(_)));
+        if name.as_os_str().is_empty() || !inside {
+            return Err(anyhow!(
+                "{} isn't a file name inside the markers folder",
+                name.display()
+            ));
+        }
+        let mut file = name.as_os_str().to_os_string();
+        file.push(".markers");
+        Ok(file.into())
+    }
+
     pub fn path_glob(path: &Path) -> PathBuf {
         path.join(Self::glob())
     }
@@ -153,13 +179,19 @@ impl RuntimeMarkers {
         Ok(())
     }
 
+    // gives back the uuid the set ended up with, which it gets here if it had none
     pub async fn edit(
-        ms: MarkerSet,
+        mut ms: MarkerSet,
         path: &PathBuf,
         original_category: Option<String>,
         idx: usize,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<Uuid> {
         let mut file = Self::load_arcless(path).await?;
+        let previous = file
+            .get_entry(path, original_category.clone(), idx)
+            .await?
+            .uuid;
+        let uuid = *ms.uuid.get_or_insert(previous.unwrap_or_else(Uuid::new_v4));
         if ms.category != original_category {
             file.remove(path, original_category, idx).await?;
             file.append_raw(ms).await?;
@@ -168,7 +200,7 @@ impl RuntimeMarkers {
             *entry = ms;
         }
         file.save(path).await?;
-        Ok(())
+        Ok(uuid)
     }
 
     pub async fn delete(
@@ -284,8 +316,16 @@ impl RuntimeMarkers {
     }
 
     pub async fn append(path: &PathBuf, ms: MarkerSet) -> anyhow::Result<()> {
+        Self::append_many(path, vec![ms]).await
+    }
+
+    pub async fn append_many(path: &PathBuf, sets: Vec<MarkerSet>) -> anyhow::Result<()> {
         let mut file = Self::load_arcless(path).await?;
-        file.append_raw(ms).await?;
+        // always a new uuid, a set saved twice is two sets as far as settings go
+        for mut ms in sets {
+            ms.uuid = Some(Uuid::new_v4());
+            file.append_raw(ms).await?;
+        }
         file.save(path).await?;
         Ok(())
     }
@@ -294,50 +334,44 @@ impl RuntimeMarkers {
         path: &PathBuf,
         format: MarkerFiletype,
         ms: MarkerSet,
+    ) -> anyhow::Result<()> {
+        Self::create_many(path, format, vec![ms]).await
+    }
+
+    pub async fn create_many(
+        path: &PathBuf,
+        format: MarkerFiletype,
+        sets: Vec<MarkerSet>,
     ) -> anyhow::Result<()> {
         let addon_dir = get_addon_dir("Taimi").expect("Invalid addon dir");
         let markers_dir = addon_dir.join("markers");
         if !exists(&markers_dir).expect("Can't check if directory exists") {
             create_dir_all(&markers_dir).await?;
         }
-        let path = markers_dir.join(format!("{}.markers", path.display()));
-        match format {
-            MarkerFiletype::Community => {
-                let file_data = MarkerFile {
-                    last_edit: Utc::now(),
-                    path: Some(path.clone()),
-                    categories: vec![MarkerCategory {
-                        name: ms.category.clone().unwrap_or("No category".to_string()),
-                        marker_sets: vec![ms],
-                    }],
-                };
-                let file = RuntimeMarkers {
-                    path: Some(path.clone()),
-                    file: MarkerFormats::Community(file_data),
-                };
-                file.create_file(&path).await?;
-            }
-            MarkerFiletype::Taimi => {
-                let file_data = vec![ms];
-                let file = RuntimeMarkers {
-                    path: Some(path.clone()),
-                    file: MarkerFormats::Taimi(file_data),
-                };
-                file.create_file(&path).await?;
-            }
-            MarkerFiletype::Integrated => {
-                let file_data = IntegratedMarkers {
-                    version: "2.0.0".to_string(),
-                    path: Some(path.clone()),
-                    squad_marker_preset: vec![ms],
-                };
-                let file = RuntimeMarkers {
-                    path: Some(path.clone()),
-                    file: MarkerFormats::Integrated(file_data),
-                };
-                file.create_file(&path).await?;
-            }
+        let path = markers_dir.join(Self::file_name(path)?);
+        let file = match format {
+            MarkerFiletype::Community => MarkerFormats::Community(MarkerFile {
+                last_edit: Utc::now(),
+                path: Some(path.clone()),
+                categories: Vec::new(),
+            }),
+            MarkerFiletype::Taimi => MarkerFormats::Taimi(Vec::new()),
+            MarkerFiletype::Integrated => MarkerFormats::Integrated(IntegratedMarkers {
+                version: "2.0.0".to_string(),
+                path: Some(path.clone()),
+                squad_marker_preset: Vec::new(),
+            }),
+        };
+        let mut file = RuntimeMarkers {
+            path: Some(path.clone()),
+            file,
+        };
+        // appending sorts community sets into their categories for us
+        for mut ms in sets {
+            ms.uuid = Some(Uuid::new_v4());
+            file.append_raw(ms).await?;
         }
+        file.create_file(&path).await?;
         Ok(())
     }
 
@@ -426,18 +460,45 @@ fn default_true() -> bool {
     true
 }
 
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+pub const DEFAULT_TRIGGER_RADIUS: f32 = 15.0;
+
 #[derive(Hash, Eq, PartialEq, Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct MarkerSet {
     #[serde(default = "default_true")]
     pub enabled: bool,
+    // what settings are kept under, once a set has been saved by us
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<Uuid>,
     pub category: Option<String>,
     pub author: Option<String>,
     pub name: String,
     pub description: String,
     pub map_id: u32,
     pub trigger: MarkerPosition,
+    // size of the sphere around trigger, DEFAULT_TRIGGER_RADIUS if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigger_radius: Option<OrderedFloat<f32>>,
+    // replaces the sphere around trigger, if set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub area: Option<BlishArea>,
+    // same meaning as on timer triggers
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub require_entry: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub require_departure: bool,
     pub markers: Vec<MarkerEntry>,
+    // put on agents once the world markers are down
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agent_markers: Vec<AgentMarkerEntry>,
     #[serde(default, skip)]
     pub path: Option<PathBuf>,
     #[serde(default, skip)]
@@ -446,6 +507,14 @@ pub struct MarkerSet {
 
 impl MarkerSet {
     pub fn id(&self) -> String {
+        match &self.uuid {
+            Some(uuid) => uuid.to_string(),
+            None => self.legacy_id(),
+        }
+    }
+
+    // what settings were kept under before uuids, and still are for sets without one
+    pub fn legacy_id(&self) -> String {
         let mut pieces = Vec::new();
         if let Some(category) = &self.category {
             pieces.push(category.clone());
@@ -462,10 +531,21 @@ impl MarkerSet {
         pieces.join("/")
     }
 
+    // whether the two are the same set, wherever they were loaded from
+    pub fn same_as(&self, other: &MarkerSet) -> bool {
+        let strip = |ms: &MarkerSet| MarkerSet {
+            uuid: None,
+            path: None,
+            idx: None,
+            ..ms.clone()
+        };
+        strip(self) == strip(other)
+    }
+
     pub fn status(&self) -> bool {
         let settings = SETTINGS.get().unwrap();
         if let Ok(settings_lock) = settings.try_read() {
-            let result = if let Some(marker) = settings_lock.markers.get(&self.id()) {
+            let result = if let Some(marker) = settings_lock.config.markers.get(&self.id()) {
                 !marker.disabled
             } else {
                 self.enabled
@@ -476,9 +556,25 @@ impl MarkerSet {
         }
     }
 
+    pub fn trigger_radius(&self) -> f32 {
+        self.trigger_radius
+            .map(OrderedFloat::into_inner)
+            .unwrap_or(DEFAULT_TRIGGER_RADIUS)
+    }
+
+    pub fn polytope(&self) -> Polytope {
+        match &self.area {
+            Some(area) => area.polytope(),
+            None => Polytope::NSphere {
+                center: self.trigger.clone().into(),
+                radius: self.trigger_radius(),
+            },
+        }
+    }
+
     pub fn trigger(&self, pos: Vec3) -> bool {
-        let trig_vec: Vec3 = self.trigger.clone().into();
-        trig_vec.distance(pos) <= 15.0
+        let inside = self.polytope().point_is_within(pos.into());
+        TimerTrigger::area_check(Some(inside), self.require_entry, self.require_departure)
     }
     pub fn combined(&self) -> String {
         if let Some(author) = &self.author {
@@ -499,6 +595,22 @@ pub struct MarkerEntry {
     pub position: MarkerPosition,
 }
 
+// a squad marker for an agent, rather than for a spot on the ground
+#[derive(Hash, Eq, PartialEq, Serialize, Deserialize, Debug, Clone)]
+pub struct AgentMarkerEntry {
+    #[serde(alias = "i", deserialize_with = "agent_marker")]
+    pub marker: MarkerType,
+    pub target: AgentTarget,
+}
+
+// blank is no marker at all, so there's nothing to put on anybody
+fn agent_marker<'de, D: Deserializer<'de>>(deserializer: D) -> Result<MarkerType, D::Error> {
+    match MarkerType::deserialize(deserializer)? {
+        MarkerType::Blank => Err(de::Error::custom("an agent marker can't be blank")),
+        marker => Ok(marker),
+    }
+}
+
 #[derive(Hash, Eq, PartialEq, Serialize, Deserialize, Debug, Clone)]
 pub struct MarkerPosition {
     pub x: OrderedFloat<f32>,
@@ -541,7 +653,7 @@ impl From<MarkerPosition> for Polytope {
     fn from(local: MarkerPosition) -> Self {
         Polytope::NSphere {
             center: local.into(),
-            radius: 15.0,
+            radius: DEFAULT_TRIGGER_RADIUS,
         }
     }
 }
@@ -573,13 +685,22 @@ impl MarkerType {
         (1..9).flat_map(|i| Self::from_repr(i))
     }
 
+    // where it sits in RTAPI's squad markers, for the ones that can be put down at all
+    pub fn squad_slot(&self) -> Option<usize> {
+        match self {
+            Self::Blank | Self::ClearMarkers => None,
+            marker => Some(marker.clone() as usize - 1),
+        }
+    }
+
     pub fn icon(&self, ui: &Ui) {
         RenderState::marker_icon(ui, Some(32.0), &self);
     }
 
-    pub fn to_place_world_gamebind(&self) -> GameBind {
-        match self {
-            Self::Blank => panic!("i can't believe you've done this"),
+    // blank has no bind, there's nothing to place
+    pub fn to_place_world_gamebind(&self) -> Option<GameBind> {
+        Some(match self {
+            Self::Blank => return None,
             Self::Arrow => GameBind::SquadMarkerPlaceWorldArrow,
             Self::Circle => GameBind::SquadMarkerPlaceWorldCircle,
             Self::Heart => GameBind::SquadMarkerPlaceWorldHeart,
@@ -590,13 +711,12 @@ impl MarkerType {
             Self::Triangle => GameBind::SquadMarkerPlaceWorldTriangle,
             Self::Cross => GameBind::SquadMarkerPlaceWorldCross,
             Self::ClearMarkers => GameBind::SquadMarkerClearAllWorld,
-        }
+        })
     }
 
-    #[allow(dead_code)]
-    pub fn to_set_agent_gamebind(&self) -> GameBind {
-        match self {
-            Self::Blank => panic!("i can't believe you've done this"),
+    pub fn to_set_agent_gamebind(&self) -> Option<GameBind> {
+        Some(match self {
+            Self::Blank => return None,
             Self::Arrow => GameBind::SquadMarkerSetAgentArrow,
             Self::Circle => GameBind::SquadMarkerSetAgentCircle,
             Self::Heart => GameBind::SquadMarkerSetAgentHeart,
@@ -606,7 +726,48 @@ impl MarkerType {
             Self::Spiral => GameBind::SquadMarkerSetAgentSwirl,
             Self::Triangle => GameBind::SquadMarkerSetAgentTriangle,
             Self::Cross => GameBind::SquadMarkerSetAgentCross,
-            Self::ClearMarkers => GameBind::SquadMarkerClearAllWorld,
+            Self::ClearMarkers => GameBind::SquadMarkerClearAllAgent,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agent_markers_cant_be_blank() {
+        let entry = |marker: u8| {
+            serde_json::from_str::<AgentMarkerEntry>(&format!(
+                r#"{{"i": {marker}, "target": {{"type": "currentTarget"}}}}"#
+            ))
+        };
+        assert!(entry(0).is_err());
+        assert_eq!(entry(5).unwrap().marker, MarkerType::Star);
+        assert!(MarkerType::Blank.to_set_agent_gamebind().is_none());
+        assert!(MarkerType::Blank.to_place_world_gamebind().is_none());
+        assert!(matches!(
+            MarkerType::ClearMarkers.to_set_agent_gamebind(),
+            Some(GameBind::SquadMarkerClearAllAgent)
+        ));
+    }
+
+    #[test]
+    fn file_names_stay_in_the_markers_folder() {
+        let name = |name: &str| RuntimeMarkers::file_name(Path::new(name));
+        assert_eq!(name("raid").unwrap(), Path::new("raid.markers"));
+        assert_eq!(name("raid.markers").unwrap(), Path::new("raid.markers"));
+        assert_eq!(name("w1/vg").unwrap(), Path::new("w1/vg.markers"));
+        assert_eq!(name("v1.2").unwrap(), Path::new("v1.2.markers"));
+        for bad in [
+            "",
+            ".markers",
+            "/etc/raid",
+            "../raid",
+            "w1/../../raid",
+            "./raid",
+        ] {
+            assert!(name(bad).is_err(), "{bad:?}");
         }
     }
 }
@@ -1,2 +1,6 @@
+pub mod agent;
 pub mod atomic;
+pub mod convert;
 pub mod format;
+pub mod placement;
+pub mod share;
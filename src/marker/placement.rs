@@ -0,0 +1,476 @@
+use {
+    crate::marker::{
+        atomic::{
+            CurrentPerspective, FakeBound, LocalPoint, MapPoint, MarkerInputData, ScreenPoint,
+        },
+        format::{MarkerEntry, MarkerType},
+    },
+    glam::{Vec3, Vec3Swizzles},
+    glamour::{TransformMap, Vector2},
+};
+
+// squad markers can't be put down further than this from the player
+pub const MAX_PLACE_DISTANCE: f32 = 127.0;
+// placements of the same marker before it's given up on
+pub const MAX_PLACE_ATTEMPTS: usize = 3;
+// drags towards one view before the markers it was for are given up on
+pub const MAX_PANS: usize = 10;
+// fakespace pixels kept clear at the edges of the map, where clicks can miss
+const VIEW_MARGIN: f32 = 8.0;
+// how far off a squad marker can land and still count, in screen pixels
+const VERIFY_PIXELS: f32 = 4.0;
+// and never tighter than this, in local units
+const VERIFY_MINIMUM: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlacementOutcome {
+    Placed,
+    // put down, but there's no RTAPI to check it landed
+    Unverified,
+    TooFar,
+    // the map couldn't be dragged to show it
+    OutOfView,
+    // put down every attempt, and never where it was meant to be
+    Missed,
+}
+
+impl PlacementOutcome {
+    pub fn success(&self) -> bool {
+        matches!(self, Self::Placed | Self::Unverified)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PlacementReport {
+    // MarkerSet::id() of the set that was placed
+    pub set: String,
+    pub results: Vec<(MarkerType, PlacementOutcome)>,
+}
+
+impl PlacementReport {
+    pub fn failures(&self) -> usize {
+        self.results.iter().filter(|(_, o)| !o.success()).count()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlacementStep {
+    // every pending marker that's on screen right now, by index into the set
+    Place(Vec<(usize, ScreenPoint)>),
+    // drag the map from one point to the other
+    Pan { from: ScreenPoint, to: ScreenPoint },
+    Done,
+}
+
+#[derive(Debug, Clone)]
+struct PendingMarker {
+    idx: usize,
+    slot: usize,
+    position: Vec3,
+    attempts: usize,
+}
+
+/*
+ * Works out the order marker placement goes in, from nothing more than the
+ * current view as MarkerInputData has it. The controller does whatever the
+ * step says, then comes back for the next one with the view as it is now.
+ */
+#[derive(Debug, Clone)]
+pub struct PlacementPlanner {
+    // the ones that can be placed, by index into the set
+    markers: Vec<(usize, MarkerType)>,
+    pending: Vec<PendingMarker>,
+    outcomes: Vec<Option<PlacementOutcome>>,
+    pans: usize,
+}
+
+impl PlacementPlanner {
+    // blank and clear markers aren't anything that can be put down, so they're left out
+    pub fn new(markers: &[MarkerEntry], player: Vec3) -> Self {
+        let mut outcomes = vec![None; markers.len()];
+        let mut pending = Vec::new();
+        let mut placeable = Vec::new();
+        for (idx, marker) in markers.iter().enumerate() {
+            let Some(slot) = marker.marker.squad_slot() else {
+                continue;
+            };
+            placeable.push((idx, marker.marker.clone()));
+            let position = Vec3::from(marker.position.clone());
+            if player.distance(position) >= MAX_PLACE_DISTANCE {
+                outcomes[idx] = Some(PlacementOutcome::TooFar);
+            } else {
+                pending.push(PendingMarker {
+                    idx,
+                    slot,
+                    position,
+                    attempts: 0,
+                });
+            }
+        }
+        Self {
+            markers: placeable,
+            pending,
+            outcomes,
+            pans: 0,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn next_step(&mut self, view: &MarkerInputData) -> PlacementStep {
+        if self.pending.is_empty() {
+            return PlacementStep::Done;
+        }
+        let visible: Vec<_> = self
+            .pending
+            .iter()
+            .filter_map(|p| Some((p.idx, Self::on_screen(view, Self::map_point(view, p))?)))
+            .collect();
+        if !visible.is_empty() {
+            self.pans = 0;
+            return PlacementStep::Place(visible);
+        }
+        let group = self.best_view(view);
+        if self.pans >= MAX_PANS {
+            self.pans = 0;
+            self.finish(&group, PlacementOutcome::OutOfView);
+            return self.next_step(view);
+        }
+        self.pans += 1;
+        let points: Vec<_> = group
+            .iter()
+            .filter_map(|idx| self.pending.iter().find(|p| p.idx == *idx))
+            .map(|p| Self::map_point(view, p))
+            .collect();
+        let (min, max) = Self::extent(&points);
+        let centre = MapPoint::new((min.x + max.x) / 2.0, (min.y + max.y) / 2.0);
+        let (from, to) = Self::pan(view, centre);
+        PlacementStep::Pan { from, to }
+    }
+
+    /*
+     * Once a batch has been put down, checks where the squad markers really
+     * are. Anything that landed is done, anything that didn't goes back in
+     * for another try, and without RTAPI there's nothing to check against.
+     */
+    pub fn verify(
+        &mut self,
+        view: &MarkerInputData,
+        batch: &[usize],
+        observed: Option<[Option<Vec3>; 8]>,
+    ) {
+        let tolerance = Self::tolerance(view);
+        let mut placed = Vec::new();
+        let mut exhausted = Vec::new();
+        for pending in self.pending.iter_mut().filter(|p| batch.contains(&p.idx)) {
+            pending.attempts += 1;
+            let Some(observed) = &observed else {
+                placed.push((pending.idx, PlacementOutcome::Unverified));
+                continue;
+            };
+            match observed[pending.slot] {
+                Some(at) if at.xz().distance(pending.position.xz()) <= tolerance => {
+                    placed.push((pending.idx, PlacementOutcome::Placed))
+                }
+                _ if pending.attempts >= MAX_PLACE_ATTEMPTS => exhausted.push(pending.idx),
+                _ => (),
+            }
+        }
+        for (idx, outcome) in placed {
+            self.finish(&[idx], outcome);
+        }
+        self.finish(&exhausted, PlacementOutcome::Missed);
+    }
+
+    // whatever's left, when placement can't carry on
+    pub fn abandon(&mut self) {
+        let left: Vec<_> = self.pending.iter().map(|p| p.idx).collect();
+        self.finish(&left, PlacementOutcome::Missed);
+    }
+
+    pub fn report(&self, set: &str) -> PlacementReport {
+        PlacementReport {
+            set: set.to_string(),
+            results: self
+                .markers
+                .iter()
+                .map(|(idx, marker)| {
+                    let outcome = self.outcomes[*idx].unwrap_or(PlacementOutcome::Missed);
+                    (marker.clone(), outcome)
+                })
+                .collect(),
+        }
+    }
+
+    fn finish(&mut self, idxs: &[usize], outcome: PlacementOutcome) {
+        self.pending.retain(|p| !idxs.contains(&p.idx));
+        for idx in idxs {
+            self.outcomes[*idx] = Some(outcome);
+        }
+    }
+
+    fn map_point(view: &MarkerInputData, pending: &PendingMarker) -> MapPoint {
+        let local: LocalPoint = pending.position.into();
+        view.map_local_to_map(local)
+    }
+
+    // where the map can be clicked, in fakespace
+    fn usable_bound(view: &MarkerInputData) -> FakeBound {
+        match view.perspective {
+            CurrentPerspective::Global => view.fakespace_worldmap_bound(),
+            CurrentPerspective::Minimap => view.fakespace_minimap_drag_bound(),
+        }
+    }
+
+    pub fn on_screen(view: &MarkerInputData, point: MapPoint) -> Option<ScreenPoint> {
+        let fake = view.map_map_to_fake(point);
+        let bound = Self::usable_bound(view);
+        let (min, max) = (bound.min(), bound.max());
+        let inside = fake.x > min.x + VIEW_MARGIN
+            && fake.x < max.x - VIEW_MARGIN
+            && fake.y > min.y + VIEW_MARGIN
+            && fake.y < max.y - VIEW_MARGIN;
+        inside.then(|| view.screen_to_fake().inverse().map(fake))
+    }
+
+    /*
+     * The view for the first pending marker that takes in as many of the
+     * others as fit on one screen, as the indices of the markers in it. The
+     * minimap can be rotated, so only its shorter side is trusted.
+     */
+    fn best_view(&self, view: &MarkerInputData) -> Vec<usize> {
+        let bound = Self::usable_bound(view);
+        let side = match view.perspective {
+            CurrentPerspective::Global => Vector2::new(bound.size.width, bound.size.height),
+            CurrentPerspective::Minimap => Vector2::splat(bound.size.width.min(bound.size.height)),
+        };
+        let span_x = (side.x - VIEW_MARGIN * 4.0).max(0.0) * view.map_scale;
+        let span_y = (side.y - VIEW_MARGIN * 4.0).max(0.0) * view.map_scale;
+        let Some(first) = self.pending.first() else {
+            return Vec::new();
+        };
+        let first_point = Self::map_point(view, first);
+        let mut others: Vec<_> = self.pending[1..]
+            .iter()
+            .map(|p| (p.idx, Self::map_point(view, p)))
+            .collect();
+        others.sort_by(|(_, a), (_, b)| {
+            first_point
+                .distance(*a)
+                .total_cmp(&first_point.distance(*b))
+        });
+        let mut group = vec![first.idx];
+        let mut points = vec![first_point];
+        for (idx, point) in others {
+            points.push(point);
+            let (min, max) = Self::extent(&points);
+            if max.x - min.x <= span_x && max.y - min.y <= span_y {
+                group.push(idx);
+            } else {
+                points.pop();
+            }
+        }
+        group
+    }
+
+    fn extent(points: &[MapPoint]) -> (MapPoint, MapPoint) {
+        let mut min = MapPoint::new(f32::INFINITY, f32::INFINITY);
+        let mut max = MapPoint::new(f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for point in points {
+            min = MapPoint::new(min.x.min(point.x), min.y.min(point.y));
+            max = MapPoint::new(max.x.max(point.x), max.y.max(point.y));
+        }
+        (min, max)
+    }
+
+    /*
+     * The drag that moves the view's centre to the target. Starting half the
+     * distance out from the middle leaves the most room for the drag, and
+     * anything that doesn't fit gets finished off by the next one.
+     */
+    pub fn pan(view: &MarkerInputData, target: MapPoint) -> (ScreenPoint, ScreenPoint) {
+        let centre: MapPoint = view.global_map.into();
+        let fake_to_screen = view.screen_to_fake().inverse();
+        let difference = fake_to_screen.map(view.map_to_fake_tf().map(target - centre));
+        let bound = Self::usable_bound(view);
+        let min = fake_to_screen.map(bound.min());
+        let max = fake_to_screen.map(bound.max());
+        let inset = VIEW_MARGIN * view.scaling;
+        let clamp = |x: f32, y: f32| {
+            ScreenPoint::new(
+                x.clamp(min.x + inset, (max.x - inset).max(min.x + inset)),
+                y.clamp(min.y + inset, (max.y - inset).max(min.y + inset)),
+            )
+        };
+        let middle = fake_to_screen.map(bound.center());
+        let from = clamp(middle.x + difference.x / 2.0, middle.y + difference.y / 2.0);
+        let screen = view.screen_bound();
+        let to = ScreenPoint::new(
+            (from.x - difference.x).clamp(screen.min().x, screen.max().x),
+            (from.y - difference.y).clamp(screen.min().y, screen.max().y),
+        );
+        (from, to)
+    }
+
+    // a few pixels of the current view, in local units
+    fn tolerance(view: &MarkerInputData) -> f32 {
+        let sign = view.sign_obtainer.sign().abs();
+        let local_per_pixel = view.map_scale / view.scaling.max(f32::EPSILON) * sign.max_element();
+        (VERIFY_PIXELS * local_per_pixel).max(VERIFY_MINIMUM)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, glam::Vec2};
+
+    /*
+     * The world map open on a 1000 pixel square screen, centred on the
+     * player, who's stood at the origin. With the default map axes, a metre
+     * east is 1/0.6096 map units to the right, and a map unit is
+     * 1/map_scale pixels.
+     */
+    fn view(map_scale: f32) -> MarkerInputData {
+        MarkerInputData {
+            scaling: 1.0,
+            map_scale,
+            perspective: CurrentPerspective::Global,
+            display_size: Vec2::splat(1000.0),
+            ..Default::default()
+        }
+    }
+
+    // local x and z, which is how far east and north of the player it is
+    fn entry(marker: MarkerType, x: f32, z: f32) -> MarkerEntry {
+        MarkerEntry {
+            marker,
+            id: None,
+            position: Vec3::new(x, 0.0, z).into(),
+        }
+    }
+
+    fn outcomes(planner: &PlacementPlanner) -> Vec<(MarkerType, PlacementOutcome)> {
+        planner.report("set").results
+    }
+
+    #[test]
+    fn only_real_markers_are_planned() {
+        let markers = [
+            entry(MarkerType::Blank, 1.0, 1.0),
+            entry(MarkerType::Arrow, 10.0, 0.0),
+            entry(MarkerType::ClearMarkers, 1.0, 1.0),
+            entry(MarkerType::Circle, 200.0, 0.0),
+        ];
+        let mut planner = PlacementPlanner::new(&markers, Vec3::ZERO);
+        let PlacementStep::Place(batch) = planner.next_step(&view(1.0)) else {
+            panic!("the arrow should be on screen");
+        };
+        assert_eq!(batch.iter().map(|(idx, _)| *idx).collect::<Vec<_>>(), [1]);
+        planner.verify(&view(1.0), &[1], None);
+        assert_eq!(planner.next_step(&view(1.0)), PlacementStep::Done);
+        assert_eq!(
+            outcomes(&planner),
+            [
+                (MarkerType::Arrow, PlacementOutcome::Unverified),
+                (MarkerType::Circle, PlacementOutcome::TooFar),
+            ]
+        );
+    }
+
+    #[test]
+    fn places_what_is_on_screen() {
+        let markers = [
+            entry(MarkerType::Arrow, 10.0, 0.0),
+            entry(MarkerType::Circle, -10.0, 5.0),
+        ];
+        let mut planner = PlacementPlanner::new(&markers, Vec3::ZERO);
+        let PlacementStep::Place(batch) = planner.next_step(&view(1.0)) else {
+            panic!("both markers should be on screen");
+        };
+        assert_eq!(batch.len(), 2);
+        let (idx, point) = batch[0];
+        assert_eq!(idx, 0);
+        assert!(
+            (point.x - (500.0 + 10.0 / 0.6096)).abs() < 0.01,
+            "{point:?}"
+        );
+        assert!((point.y - 500.0).abs() < 0.01, "{point:?}");
+    }
+
+    #[test]
+    fn pans_towards_markers_off_screen() {
+        // zoomed in, so 100m east is well past the right edge
+        let markers = [entry(MarkerType::Star, 100.0, 0.0)];
+        let mut planner = PlacementPlanner::new(&markers, Vec3::ZERO);
+        for _ in 0..MAX_PANS {
+            let PlacementStep::Pan { from, to } = planner.next_step(&view(0.2)) else {
+                panic!("the star should be off screen");
+            };
+            // dragging right to left brings the east into view
+            assert!(from.x > to.x, "{from:?} -> {to:?}");
+            assert_eq!(from.y, to.y);
+        }
+        // the view never changed, so it's given up on
+        assert_eq!(planner.next_step(&view(0.2)), PlacementStep::Done);
+        assert_eq!(
+            outcomes(&planner),
+            [(MarkerType::Star, PlacementOutcome::OutOfView)]
+        );
+    }
+
+    #[test]
+    fn best_view_takes_in_what_fits() {
+        let markers = [
+            entry(MarkerType::Arrow, 100.0, 0.0),
+            entry(MarkerType::Circle, -100.0, 0.0),
+            entry(MarkerType::Heart, 110.0, 10.0),
+        ];
+        let planner = PlacementPlanner::new(&markers, Vec3::ZERO);
+        // about 190 map units across, and the circle's over 300 away
+        assert_eq!(planner.best_view(&view(0.2)), [0, 2]);
+        // zoomed out, everything fits
+        assert_eq!(planner.best_view(&view(1.0)), [0, 2, 1]);
+    }
+
+    #[test]
+    fn verify_checks_where_they_landed() {
+        let markers = [
+            entry(MarkerType::Arrow, 10.0, 0.0),
+            entry(MarkerType::Circle, -10.0, 5.0),
+        ];
+        let mut planner = PlacementPlanner::new(&markers, Vec3::ZERO);
+        let mut observed = [None; 8];
+        // right where it was meant to go, give or take a little
+        observed[0] = Some(Vec3::new(10.5, 3.0, 0.0));
+        // and the circle's somewhere else entirely
+        observed[1] = Some(Vec3::new(30.0, 0.0, 30.0));
+        for _ in 0..MAX_PLACE_ATTEMPTS {
+            planner.verify(&view(1.0), &[0, 1], Some(observed));
+        }
+        assert!(planner.is_done());
+        assert_eq!(
+            outcomes(&planner),
+            [
+                (MarkerType::Arrow, PlacementOutcome::Placed),
+                (MarkerType::Circle, PlacementOutcome::Missed),
+            ]
+        );
+    }
+
+    #[test]
+    fn misses_get_another_try() {
+        let markers = [entry(MarkerType::Square, 10.0, 0.0)];
+        let mut planner = PlacementPlanner::new(&markers, Vec3::ZERO);
+        planner.verify(&view(1.0), &[0], Some([None; 8]));
+        assert!(!planner.is_done());
+        let mut observed = [None; 8];
+        observed[3] = Some(Vec3::new(10.0, 0.0, 0.0));
+        planner.verify(&view(1.0), &[0], Some(observed));
+        assert_eq!(
+            outcomes(&planner),
+            [(MarkerType::Square, PlacementOutcome::Placed)]
+        );
+    }
+}
@@ -0,0 +1,568 @@
+use {
+    crate::marker::{
+        agent::AgentTarget,
+        format::{AgentMarkerEntry, MarkerEntry, MarkerPosition, MarkerSet, MarkerType},
+    },
+    anyhow::anyhow,
+    base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _},
+    ordered_float::OrderedFloat,
+};
+
+/*
+ * Share codes look like "taimi1:<base64>:<check>", so they survive being
+ * pasted into squad chat or Discord. The number is the payload version, the
+ * check is the start of the payload's md5 so a mangled paste gets caught
+ * rather than imported half-broken.
+ *
+ * Chat only takes 199 characters, so the payload is packed by hand rather
+ * than being JSON: positions are whole centimetres, and the markers are
+ * relative to the trigger, which keeps them down to a couple of bytes each.
+ */
+const SHARE_PREFIX: &str = "taimi";
+pub const SHARE_VERSION: u32 = 1;
+const CHECK_LENGTH: usize = 8;
+
+// what's in the flags byte at the start of every set
+const ENABLED: u8 = 1;
+const REQUIRE_ENTRY: u8 = 1 << 1;
+const REQUIRE_DEPARTURE: u8 = 1 << 2;
+const HAS_CATEGORY: u8 = 1 << 3;
+const HAS_AUTHOR: u8 = 1 << 4;
+const HAS_RADIUS: u8 = 1 << 5;
+const HAS_AREA: u8 = 1 << 6;
+// and on a marker's type, if it has an id after it
+const HAS_ID: u8 = 1 << 7;
+
+#[derive(Debug, Clone)]
+pub struct SharedMarkers {
+    // set when a whole category was shared
+    pub category: Option<String>,
+    pub sets: Vec<MarkerSet>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportProblem {
+    NoMapId,
+    Duplicate,
+}
+
+// finer than anyone can put a marker down by hand
+fn centimetres(m: f32) -> i64 {
+    (m as f64 * 100.0).round() as i64
+}
+
+fn metres(cm: i64) -> OrderedFloat<f32> {
+    OrderedFloat(cm as f32 / 100.0)
+}
+
+fn marker_type(byte: u8) -> anyhow::Result<MarkerType> {
+    MarkerType::from_repr(byte)
+        .ok_or_else(|| anyhow!("The share code has a marker that doesn't exist in it"))
+}
+
+fn centimetre_position(position: &MarkerPosition) -> [i64; 3] {
+    [position.x, position.y, position.z].map(|c| centimetres(*c))
+}
+
+// little endian, with lengths and whole numbers as LEB128 varints
+#[derive(Default)]
+struct Packer(Vec<u8>);
+
+impl Packer {
+    fn byte(&mut self, byte: u8) {
+        self.0.push(byte);
+    }
+
+    fn varint(&mut self, mut n: u64) {
+        while n >= 0x80 {
+            self.0.push(n as u8 | 0x80);
+            n >>= 7;
+        }
+        self.0.push(n as u8);
+    }
+
+    // zigzagged, so small negative numbers stay small
+    fn signed(&mut self, n: i64) {
+        self.varint(((n << 1) ^ (n >> 63)) as u64);
+    }
+
+    fn string(&mut self, s: &str) {
+        self.varint(s.len() as u64);
+        self.0.extend_from_slice(s.as_bytes());
+    }
+
+    fn set(&mut self, ms: &MarkerSet) -> anyhow::Result<()> {
+        let mut flags = 0;
+        for (set, flag) in [
+            (ms.enabled, ENABLED),
+            (ms.require_entry, REQUIRE_ENTRY),
+            (ms.require_departure, REQUIRE_DEPARTURE),
+            (ms.category.is_some(), HAS_CATEGORY),
+            (ms.author.is_some(), HAS_AUTHOR),
+            (ms.trigger_radius.is_some(), HAS_RADIUS),
+            (ms.area.is_some(), HAS_AREA),
+        ] {
+            if set {
+                flags |= flag;
+            }
+        }
+        self.byte(flags);
+        for s in [&ms.category, &ms.author].into_iter().flatten() {
+            self.string(s);
+        }
+        self.string(&ms.name);
+        self.string(&ms.description);
+        self.varint(ms.map_id as u64);
+        let trigger = centimetre_position(&ms.trigger);
+        trigger.iter().for_each(|&c| self.signed(c));
+        if let Some(radius) = ms.trigger_radius {
+            self.signed(centimetres(*radius));
+        }
+        // rare enough, and complicated enough, to not be worth packing
+        if let Some(area) = &ms.area {
+            self.string(&serde_json::to_string(area)?);
+        }
+        self.varint(ms.markers.len() as u64);
+        for entry in &ms.markers {
+            let marker = entry.marker.clone() as u8;
+            match &entry.id {
+                Some(id) => {
+                    self.byte(marker | HAS_ID);
+                    self.string(id);
+                }
+                None => self.byte(marker),
+            }
+            let position = centimetre_position(&entry.position);
+            for (c, t) in position.iter().zip(trigger) {
+                self.signed(c.saturating_sub(t));
+            }
+        }
+        self.varint(ms.agent_markers.len() as u64);
+        for entry in &ms.agent_markers {
+            self.byte(entry.marker.clone() as u8);
+            match &entry.target {
+                AgentTarget::CurrentTarget => self.byte(0),
+                AgentTarget::Species { id } => {
+                    self.byte(1);
+                    self.varint(*id as u64);
+                }
+                AgentTarget::Account { name } => {
+                    self.byte(2);
+                    self.string(name);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Unpacker<'a>(&'a [u8]);
+
+impl<'a> Unpacker<'a> {
+    fn take(&mut self, n: usize) -> anyhow::Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(anyhow!("The share code ends too soon"));
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> anyhow::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> anyhow::Result<u64> {
+        let mut n = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            n |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(anyhow!("The share code has a number that's far too long"))
+    }
+
+    fn signed(&mut self) -> anyhow::Result<i64> {
+        let n = self.varint()?;
+        Ok((n >> 1) as i64 ^ -((n & 1) as i64))
+    }
+
+    fn small(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::try_from(self.varint()?)?)
+    }
+
+    fn string(&mut self) -> anyhow::Result<String> {
+        let len = usize::try_from(self.varint()?)?;
+        Ok(String::from_utf8(self.take(len)?.to_vec())?)
+    }
+
+    fn position(&mut self, relative_to: [i64; 3]) -> anyhow::Result<MarkerPosition> {
+        let [x, y, z] = relative_to;
+        Ok(MarkerPosition {
+            x: metres(x.saturating_add(self.signed()?)),
+            y: metres(y.saturating_add(self.signed()?)),
+            z: metres(z.saturating_add(self.signed()?)),
+        })
+    }
+
+    fn set(&mut self) -> anyhow::Result<MarkerSet> {
+        let flags = self.byte()?;
+        let category = match flags & HAS_CATEGORY {
+            0 => None,
+            _ => Some(self.string()?),
+        };
+        let author = match flags & HAS_AUTHOR {
+            0 => None,
+            _ => Some(self.string()?),
+        };
+        let name = self.string()?;
+        let description = self.string()?;
+        let map_id = self.small()?;
+        let trigger = [self.signed()?, self.signed()?, self.signed()?];
+        let trigger_radius = match flags & HAS_RADIUS {
+            0 => None,
+            _ => Some(metres(self.signed()?)),
+        };
+        let area = match flags & HAS_AREA {
+            0 => None,
+            _ => Some(serde_json::from_str(&self.string()?)?),
+        };
+        let mut markers = Vec::new();
+        for _ in 0..self.varint()? {
+            let byte = self.byte()?;
+            let marker = marker_type(byte & !HAS_ID)?;
+            let id = match byte & HAS_ID {
+                0 => None,
+                _ => Some(self.string()?),
+            };
+            let position = self.position(trigger)?;
+            markers.push(MarkerEntry {
+                marker,
+                id,
+                position,
+            });
+        }
+        let mut agent_markers = Vec::new();
+        for _ in 0..self.varint()? {
+            let byte = self.byte()?;
+            let marker = match marker_type(byte)? {
+                MarkerType::Blank => {
+                    return Err(anyhow!("The share code has a blank agent marker"))
+                }
+                marker => marker,
+            };
+            let target = match self.byte()? {
+                0 => AgentTarget::CurrentTarget,
+                1 => AgentTarget::Species { id: self.small()? },
+                2 => AgentTarget::Account {
+                    name: self.string()?,
+                },
+                _ => return Err(anyhow!("The share code has an agent marker for nobody")),
+            };
+            agent_markers.push(AgentMarkerEntry { marker, target });
+        }
+        Ok(MarkerSet {
+            enabled: flags & ENABLED != 0,
+            uuid: None,
+            category,
+            author,
+            name,
+            description,
+            map_id,
+            trigger: MarkerPosition {
+                x: metres(trigger[0]),
+                y: metres(trigger[1]),
+                z: metres(trigger[2]),
+            },
+            trigger_radius,
+            area,
+            require_entry: flags & REQUIRE_ENTRY != 0,
+            require_departure: flags & REQUIRE_DEPARTURE != 0,
+            markers,
+            agent_markers,
+            path: None,
+            idx: None,
+        })
+    }
+}
+
+impl SharedMarkers {
+    pub fn set(ms: &MarkerSet) -> Self {
+        Self {
+            category: None,
+            sets: vec![ms.clone()],
+        }
+    }
+
+    pub fn category(name: &str, sets: &[impl AsRef<MarkerSet>]) -> Self {
+        Self {
+            category: Some(name.to_string()),
+            sets: sets.iter().map(|ms| ms.as_ref().clone()).collect(),
+        }
+    }
+
+    // uuids stay behind, whoever imports the sets gets their own
+    fn pack(&self) -> anyhow::Result<Vec<u8>> {
+        let mut packer = Packer::default();
+        match &self.category {
+            Some(category) => {
+                packer.byte(1);
+                packer.string(category);
+            }
+            None => packer.byte(0),
+        }
+        packer.varint(self.sets.len() as u64);
+        for ms in &self.sets {
+            packer.set(ms)?;
+        }
+        Ok(packer.0)
+    }
+
+    fn unpack(payload: &[u8]) -> anyhow::Result<Self> {
+        let mut unpacker = Unpacker(payload);
+        let category = match unpacker.byte()? {
+            0 => None,
+            _ => Some(unpacker.string()?),
+        };
+        let mut sets = Vec::new();
+        for _ in 0..unpacker.varint()? {
+            sets.push(unpacker.set()?);
+        }
+        if !unpacker.0.is_empty() {
+            return Err(anyhow!("The share code has more in it than it should"));
+        }
+        Ok(Self { category, sets })
+    }
+
+    pub fn encode(&self) -> anyhow::Result<String> {
+        let payload = self.pack()?;
+        Ok(format!(
+            "{SHARE_PREFIX}{SHARE_VERSION}:{}:{}",
+            URL_SAFE_NO_PAD.encode(&payload),
+            Self::check(&payload),
+        ))
+    }
+
+    fn check(payload: &[u8]) -> String {
+        let digest = format!("{:x}", md5::compute(payload));
+        digest[..CHECK_LENGTH].to_string()
+    }
+
+    // finds the code in whatever was pasted, chat timestamps and all
+    pub fn decode(text: &str) -> anyhow::Result<Self> {
+        let code = text
+            .match_indices(SHARE_PREFIX)
+            .map(|(start, _)| &text[start + SHARE_PREFIX.len()..])
+            .find(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+            .and_then(|rest| rest.split_whitespace().next())
+            .ok_or_else(|| anyhow!("That isn't a marker share code"))?;
+        let mut pieces = code.split(':');
+        let (Some(version), Some(body), Some(check), None) =
+            (pieces.next(), pieces.next(), pieces.next(), pieces.next())
+        else {
+            return Err(anyhow!("The share code is incomplete"));
+        };
+        let version: u32 = version
+            .parse()
+            .map_err(|_| anyhow!("The share code has no version"))?;
+        if version != SHARE_VERSION {
+            return Err(anyhow!(
+                "The share code is version {version}, this only understands version {SHARE_VERSION}"
+            ));
+        }
+        let payload = URL_SAFE_NO_PAD.decode(body)?;
+        if Self::check(&payload) != check.to_lowercase() {
+            return Err(anyhow!(
+                "The share code doesn't match its checksum, it may not have been pasted whole"
+            ));
+        }
+        let shared = Self::unpack(&payload)?;
+        if shared.sets.is_empty() {
+            return Err(anyhow!("The share code has no marker sets in it"));
+        }
+        Ok(shared)
+    }
+
+    // the set as it'd come out of a share code, positions rounded and all
+    fn shared_copy(ms: &MarkerSet) -> Option<MarkerSet> {
+        let mut packer = Packer::default();
+        packer.set(ms).ok()?;
+        Unpacker(&packer.0).set().ok()
+    }
+
+    /*
+     * What might stop each set being imported, against the sets already
+     * loaded. Nothing that comes out of a share code has a uuid, so the loaded
+     * sets are matched by what's in them too, not just by id.
+     */
+    pub fn problems(&self, existing: &[impl AsRef<MarkerSet>]) -> Vec<Vec<ImportProblem>> {
+        let existing: Vec<(String, Option<MarkerSet>)> = existing
+            .iter()
+            .map(|ms| (ms.as_ref().id(), Self::shared_copy(ms.as_ref())))
+            .collect();
+        self.sets
+            .iter()
+            .map(|ms| {
+                let mut problems = Vec::new();
+                if ms.map_id == 0 {
+                    problems.push(ImportProblem::NoMapId);
+                }
+                let id = ms.id();
+                if existing.iter().any(|(existing_id, copy)| {
+                    *existing_id == id || copy.as_ref().is_some_and(|copy| copy.same_as(ms))
+                }) {
+                    problems.push(ImportProblem::Duplicate);
+                }
+                problems
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::sync::Arc};
+
+    const SET: &str = r#"{
+        "uuid": "0b5bd2a5-5bcf-4a57-9c0e-3f1f5f8a8f43",
+        "category": "Spirit Vale",
+        "author": "Kat",
+        "name": "Sabetha",
+        "description": "cannons",
+        "mapId": 1062,
+        "trigger": { "x": -1200.5, "y": 250.25, "z": 30.0 },
+        "triggerRadius": 20.5,
+        "requireDeparture": true,
+        "markers": [
+            { "i": 1, "x": -1195.25, "y": 251.0, "z": 30.1 },
+            { "i": 2, "d": "north cannon", "x": -1210.0, "y": 240.75, "z": 29.5 }
+        ],
+        "agentMarkers": [
+            { "i": 5, "target": { "type": "species", "id": 15238 } },
+            { "i": 9, "target": { "type": "account", "name": "Taimi.1234" } }
+        ]
+    }"#;
+
+    fn set() -> MarkerSet {
+        serde_json::from_str(SET).unwrap()
+    }
+
+    fn code_for(version: u32, payload: &[u8]) -> String {
+        format!(
+            "{SHARE_PREFIX}{version}:{}:{}",
+            URL_SAFE_NO_PAD.encode(payload),
+            SharedMarkers::check(payload)
+        )
+    }
+
+    #[test]
+    fn round_trips_without_the_uuid() {
+        let shared = SharedMarkers::category("Spirit Vale", &[Arc::new(set())]);
+        let decoded = SharedMarkers::decode(&shared.encode().unwrap()).unwrap();
+        let expected = MarkerSet {
+            uuid: None,
+            ..set()
+        };
+        assert_eq!(decoded.category.as_deref(), Some("Spirit Vale"));
+        assert_eq!(decoded.sets, [expected]);
+    }
+
+    #[test]
+    fn fits_in_squad_chat() {
+        let mut ms = set();
+        ms.agent_markers.clear();
+        ms.trigger_radius = None;
+        ms.author = None;
+        ms.markers = MarkerType::iter_real_values()
+            .enumerate()
+            .map(|(i, marker)| MarkerEntry {
+                marker,
+                id: None,
+                position: MarkerPosition {
+                    x: OrderedFloat(-1200.5 + i as f32 * 1.25),
+                    y: OrderedFloat(250.25 - i as f32 * 2.5),
+                    z: OrderedFloat(30.0),
+                },
+            })
+            .collect();
+        let code = SharedMarkers::set(&ms).encode().unwrap();
+        assert!(code.len() < 200, "{} characters: {code}", code.len());
+        assert_eq!(
+            SharedMarkers::decode(&code).unwrap().sets[0].markers,
+            ms.markers
+        );
+    }
+
+    #[test]
+    fn found_in_a_chat_line() {
+        let code = SharedMarkers::set(&set()).encode().unwrap();
+        let line = format!("[21:04] Kat.1234: {code} for sab");
+        assert_eq!(SharedMarkers::decode(&line).unwrap().sets.len(), 1);
+    }
+
+    #[test]
+    fn only_known_versions() {
+        let payload = SharedMarkers::set(&set()).pack().unwrap();
+        for version in [0, SHARE_VERSION + 1] {
+            let err = SharedMarkers::decode(&code_for(version, &payload)).unwrap_err();
+            assert!(err.to_string().contains("version"), "{err}");
+        }
+    }
+
+    #[test]
+    fn mangled_codes_are_caught() {
+        let code = SharedMarkers::set(&set()).encode().unwrap();
+        let mut mangled: Vec<char> = code.chars().collect();
+        mangled[10] = if mangled[10] == 'A' { 'B' } else { 'A' };
+        let mangled: String = mangled.into_iter().collect();
+        assert!(SharedMarkers::decode(&mangled).is_err());
+
+        // checksummed, but cut short before it was checksummed
+        let payload = SharedMarkers::set(&set()).pack().unwrap();
+        let short = code_for(SHARE_VERSION, &payload[..payload.len() - 3]);
+        let err = SharedMarkers::decode(&short).unwrap_err();
+        assert!(err.to_string().contains("too soon"), "{err}");
+    }
+
+    #[test]
+    fn loaded_sets_are_duplicates_by_what_is_in_them() {
+        let mut loaded = set();
+        // finer than a share code keeps
+        loaded.trigger.x = OrderedFloat(-1200.5012);
+        let code = SharedMarkers::set(&loaded).encode().unwrap();
+        let shared = SharedMarkers::decode(&code).unwrap();
+        assert_eq!(
+            shared.problems(&[Arc::new(loaded)]),
+            [[ImportProblem::Duplicate]]
+        );
+
+        // an older set with no uuid, matched against the same set saved with one
+        let legacy = MarkerSet {
+            uuid: None,
+            ..set()
+        };
+        let shared = SharedMarkers::set(&legacy);
+        assert_eq!(
+            shared.problems(&[Arc::new(set())]),
+            [[ImportProblem::Duplicate]]
+        );
+
+        let other = MarkerSet {
+            name: "Gorseval".to_string(),
+            ..set()
+        };
+        assert_eq!(
+            shared.problems(&[Arc::new(other)]),
+            [Vec::<ImportProblem>::new()]
+        );
+    }
+
+    #[test]
+    fn sets_without_a_map_are_flagged() {
+        let shared = SharedMarkers::set(&MarkerSet { map_id: 0, ..set() });
+        let loaded: [Arc<MarkerSet>; 0] = [];
+        assert_eq!(shared.problems(&loaded), [[ImportProblem::NoMapId]]);
+    }
+}
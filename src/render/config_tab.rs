@@ -28,7 +28,7 @@ impl ConfigTabState {
 
     pub fn draw(&mut self, ui: &Ui, timer_window_state: &mut TimerWindowState) {
         if let Some(settings) = SETTINGS.get().and_then(|settings| settings.try_read().ok()) {
-            self.katrender = settings.enable_katrender;
+            self.katrender = settings.config.enable_katrender;
         };
         ui.text_wrapped(&fl!("imgui-notice"));
         ui.dummy([4.0, 4.0]);
@@ -45,7 +45,7 @@ impl ConfigTabState {
             ui.text_wrapped(&fl!("autoplace-warning"));
             ui.dummy([4.0, 4.0]);
             if let Some(settings) = SETTINGS.get().and_then(|settings| settings.try_read().ok()) {
-                self.marker_autoplace = settings.marker_autoplace.clone();
+                self.marker_autoplace = settings.config.marker_autoplace.clone();
                 self.marker_autoplace_inner = match &self.marker_autoplace {
                     MarkerAutoPlaceSettings::OpenWindow(t) => Some(t.clone()),
                     MarkerAutoPlaceSettings::Place(t) => Some(t.clone()),
@@ -112,7 +112,7 @@ impl ConfigTabState {
         let timers_window_closure = || {
             ui.dummy([4.0, 4.0]);
             if let Some(settings) = SETTINGS.get().and_then(|settings| settings.try_read().ok()) {
-                timer_window_state.progress_bar.stock = settings.progress_bar.stock;
+                timer_window_state.progress_bar.stock = settings.config.progress_bar.stock;
             };
             if ui.checkbox(
                 &fl!("stock-imgui-progress-bar"),
@@ -0,0 +1,159 @@
+use {
+    crate::{
+        fl,
+        marker::format::{MarkerFiletype, RuntimeMarkers},
+        util::UiExt,
+        ControllerEvent, CONTROLLER_SENDER,
+    },
+    nexus::imgui::{ComboBox, Selectable, Ui, Window},
+    std::path::{Path, PathBuf},
+    strum::IntoEnumIterator,
+};
+
+pub struct ConvertMarkersWindowState {
+    pub open: bool,
+    filenames: Vec<PathBuf>,
+    chosen: Vec<PathBuf>,
+    filetype: Option<MarkerFiletype>,
+    dedupe: bool,
+    set_aside: bool,
+    output: String,
+    problems: Vec<String>,
+}
+
+impl ConvertMarkersWindowState {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            filenames: Default::default(),
+            chosen: Default::default(),
+            filetype: Default::default(),
+            dedupe: true,
+            set_aside: false,
+            output: Default::default(),
+            problems: Default::default(),
+        }
+    }
+
+    pub fn open(&mut self) {
+        if !self.open {
+            self.chosen.clear();
+            self.problems.clear();
+            self.request_filenames();
+            self.open = true;
+        }
+    }
+
+    fn request_filenames(&self) {
+        let sender = CONTROLLER_SENDER.get().unwrap();
+        let event_send = sender.try_send(ControllerEvent::GetMarkerPaths);
+        drop(event_send);
+    }
+
+    pub fn set_filenames(&mut self, filenames: Vec<PathBuf>) {
+        self.chosen.retain(|path| filenames.contains(path));
+        self.filenames = filenames;
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.chosen.is_empty() {
+            problems.push(fl!("convert-no-files"));
+        }
+        if self.filetype.is_none() {
+            problems.push(fl!("convert-no-filetype"));
+        }
+        if self.output.is_empty() {
+            problems.push(fl!("filename-empty"));
+        } else if RuntimeMarkers::file_name(Path::new(&self.output)).is_err() {
+            problems.push(fl!("filename-outside"));
+        }
+        problems
+    }
+
+    fn convert(&self) {
+        let Some(filetype) = self.filetype.clone() else {
+            return;
+        };
+        let sender = CONTROLLER_SENDER.get().unwrap();
+        let event_send = sender.try_send(ControllerEvent::ConvertMarkers {
+            inputs: self.chosen.clone(),
+            filetype,
+            dedupe: self.dedupe,
+            output: PathBuf::from(&self.output),
+            set_aside: self.set_aside,
+        });
+        drop(event_send);
+    }
+
+    pub fn draw(&mut self, ui: &Ui) {
+        let mut open = self.open;
+        if open {
+            let closed = Window::new(&fl!("convert-markers"))
+                .size([300.0, 200.0], nexus::imgui::Condition::FirstUseEver)
+                .opened(&mut open)
+                .build(ui, || {
+                    ui.text_wrapped(&fl!("convert-explanation"));
+                    ui.dummy([4.0; 2]);
+                    for path in &self.filenames {
+                        let mut chosen = self.chosen.contains(path);
+                        if ui.checkbox(format!("{}", path.display()), &mut chosen) {
+                            match chosen {
+                                true => self.chosen.push(path.clone()),
+                                false => self.chosen.retain(|p| p != path),
+                            }
+                        }
+                    }
+                    if ui.button(fl!("refresh-files")) {
+                        self.request_filenames();
+                    }
+                    ui.dummy([4.0; 2]);
+                    let filetype_closure = || {
+                        let mut selected = self.filetype.clone();
+                        for item in MarkerFiletype::iter() {
+                            if Selectable::new(item.to_string())
+                                .selected(Some(&item) == self.filetype.as_ref())
+                                .build(ui)
+                            {
+                                selected = Some(item.clone());
+                            }
+                        }
+                        selected
+                    };
+                    let combo_box_text = match &self.filetype {
+                        Some(s) => s.to_string(),
+                        None => "".to_string(),
+                    };
+                    if let Some(Some(selection)) = ComboBox::new(fl!("filetype"))
+                        .preview_value(combo_box_text)
+                        .build(ui, filetype_closure)
+                    {
+                        self.filetype = Some(selection);
+                    }
+                    ui.help_marker(|| {
+                        ui.tooltip_text(fl!("marker-filetype-explanation"));
+                    });
+                    ui.input_text(fl!("filename"), &mut self.output).build();
+                    ui.checkbox(fl!("convert-dedupe"), &mut self.dedupe);
+                    ui.checkbox(fl!("convert-set-aside"), &mut self.set_aside);
+                    ui.dummy([4.0; 2]);
+                    for problem in &self.problems {
+                        ui.bullet();
+                        ui.text_colored([1.0, 0.0, 0.0, 1.0], problem);
+                    }
+                    if ui.button(fl!("convert")) {
+                        self.problems = self.validate();
+                        if self.problems.is_empty() {
+                            self.convert();
+                            return true;
+                        }
+                    }
+                    false
+                });
+            self.open = match closed {
+                Some(true) => false,
+                _ => open,
+            };
+        }
+    }
+}
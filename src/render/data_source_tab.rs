@@ -3,26 +3,251 @@ use {
         controller::ControllerEvent,
         fl,
         render::RenderState,
-        settings::{NeedsUpdate, RemoteState, Source},
+        settings::{
+            Channel, NeedsUpdate, ReleaseNotes, RemoteState, Source, SourceKind, TimerChanges,
+            UpdateAction, UpdateCheckPolicy, UpdateSettings,
+        },
+        timer::TimerPack,
         CONTROLLER_SENDER, SETTINGS,
     },
-    nexus::imgui::{PopupModal, StyleColor, TableColumnSetup, TableFlags, Ui},
-    std::collections::HashMap,
+    nexus::imgui::{
+        ComboBox, PopupModal, Selectable, StyleColor, TableColumnSetup, TableFlags, TreeNode, Ui,
+    },
+    std::{collections::HashMap, mem::discriminant},
 };
 
+// what "every N hours" starts out as when it's picked
+const DEFAULT_CHECK_HOURS: u32 = 6;
+
 pub struct DataSourceTabState {
     pub checking_for_updates: bool,
+    // channels being edited, by source, until they're applied
+    pending_channels: HashMap<String, Channel>,
+    github_token: String,
+}
+
+fn channel_name(channel: &Channel) -> String {
+    use Channel::*;
+    match channel {
+        Latest => fl!("channel-latest"),
+        Prerelease => fl!("channel-prerelease"),
+        Tag(_) => fl!("channel-tag"),
+        Commit(_) => fl!("channel-commit"),
+        Branch(_) => fl!("channel-branch"),
+    }
 }
 
 impl DataSourceTabState {
     pub fn new() -> Self {
         Self {
             checking_for_updates: false,
+            pending_channels: Default::default(),
+            github_token: Default::default(),
+        }
+    }
+
+    pub fn draw_channel(&mut self, ui: &Ui, rs: &RemoteState) {
+        let pending = self
+            .pending_channels
+            .entry(rs.source.to_string())
+            .or_insert_with(|| rs.channel.clone());
+        let channel_closure = || {
+            let git_ref = pending.git_ref().unwrap_or_default();
+            let mut selected = None;
+            for item in Channel::variants(git_ref) {
+                if Selectable::new(channel_name(&item))
+                    .selected(discriminant(&item) == discriminant(pending))
+                    .build(ui)
+                {
+                    selected = Some(item);
+                }
+            }
+            selected
+        };
+        if let Some(Some(selection)) = ComboBox::new(fl!("channel"))
+            .preview_value(channel_name(pending))
+            .build(ui, channel_closure)
+        {
+            *pending = selection;
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text(fl!("channel-tooltip"));
+        }
+        if let Some(git_ref) = pending.git_ref_mut() {
+            ui.input_text(fl!("channel-ref"), git_ref).build();
+        }
+        let applicable = pending.git_ref().is_none_or(|r| !r.is_empty());
+        if *pending != rs.channel && applicable {
+            if ui.button(fl!("channel-apply")) {
+                let sender = CONTROLLER_SENDER.get().unwrap();
+                let event_send = sender.try_send(ControllerEvent::SetDataSourceChannel {
+                    source: rs.source.clone(),
+                    channel: pending.clone(),
+                });
+                drop(event_send);
+            }
+        }
+    }
+
+    pub fn draw_update_settings(&self, ui: &Ui, updates: &UpdateSettings) {
+        let mut changed = updates.clone();
+        let hours = match updates.check {
+            UpdateCheckPolicy::Every { hours } => hours,
+            _ => DEFAULT_CHECK_HOURS,
+        };
+        let check_closure = || {
+            let mut selected = None;
+            for item in UpdateCheckPolicy::variants(hours) {
+                if Selectable::new(item.to_string())
+                    .selected(discriminant(&item) == discriminant(&updates.check))
+                    .build(ui)
+                {
+                    selected = Some(item);
+                }
+            }
+            selected
+        };
+        if let Some(Some(selection)) = ComboBox::new(fl!("update-check"))
+            .preview_value(updates.check.to_string())
+            .build(ui, check_closure)
+        {
+            changed.check = selection;
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text(fl!("update-check-tooltip"));
+        }
+        if let UpdateCheckPolicy::Every { hours } = &mut changed.check {
+            let mut hours_input = *hours as i32;
+            if ui
+                .input_int(fl!("update-check-hours"), &mut hours_input)
+                .build()
+            {
+                *hours = hours_input.max(1) as u32;
+            }
+        }
+        let action_closure = || {
+            let mut selected = None;
+            for item in UpdateAction::all() {
+                if Selectable::new(item.to_string())
+                    .selected(item == updates.action)
+                    .build(ui)
+                {
+                    selected = Some(item);
+                }
+            }
+            selected
+        };
+        if let Some(Some(selection)) = ComboBox::new(fl!("update-action"))
+            .preview_value(updates.action.to_string())
+            .build(ui, action_closure)
+        {
+            changed.action = selection;
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text(fl!("update-action-tooltip"));
+        }
+        if changed != *updates {
+            let sender = CONTROLLER_SENDER.get().unwrap();
+            let event_send = sender.try_send(ControllerEvent::UpdateSettings(changed));
+            drop(event_send);
         }
     }
 
+    pub fn draw_github_token(&mut self, ui: &Ui, token: Option<&str>) {
+        ui.input_text(fl!("github-token"), &mut self.github_token)
+            .password(true)
+            .build();
+        if ui.is_item_hovered() {
+            ui.tooltip_text(fl!("github-token-tooltip"));
+        }
+        let sender = CONTROLLER_SENDER.get().unwrap();
+        if !self.github_token.trim().is_empty() {
+            ui.same_line();
+            if ui.button(fl!("github-token-set")) {
+                let token = std::mem::take(&mut self.github_token);
+                let event_send = sender.try_send(ControllerEvent::SetGitHubToken(Some(token)));
+                drop(event_send);
+            }
+        } else if token.is_some() {
+            ui.same_line();
+            if ui.button(fl!("github-token-clear")) {
+                let event_send = sender.try_send(ControllerEvent::SetGitHubToken(None));
+                drop(event_send);
+            }
+        }
+    }
+
+    pub fn draw_pack(&self, ui: &Ui, pack: &TimerPack) {
+        let manifest = &pack.manifest;
+        if let Some(name) = &manifest.name {
+            let version = manifest.version.clone().unwrap_or_default();
+            ui.text_disabled(fl!("pack-name", name = name.clone(), version = version));
+        }
+        if let Some(required) = pack.unmet_requirement() {
+            ui.text_colored(
+                [1.0, 0.0, 0.0, 1.0],
+                fl!("pack-needs-newer", version = required),
+            );
+        }
+        if !manifest.disabled.is_empty() {
+            ui.text_disabled(fl!(
+                "pack-default-disabled",
+                count = manifest.disabled.len()
+            ));
+        }
+    }
+
+    pub fn draw_release_notes(&self, ui: &Ui, notes: &ReleaseNotes) {
+        let title = fl!("release-notes", version = notes.version.clone());
+        TreeNode::new(&title).build(ui, || {
+            if let Some(name) = &notes.name {
+                ui.text_wrapped(name);
+            }
+            if let Some(published_at) = &notes.published_at {
+                let time_display = published_at.format("%F %T %Z").to_string();
+                ui.text_disabled(fl!("release-published", time = time_display));
+            }
+            match &notes.body {
+                Some(body) if !body.trim().is_empty() => ui.text_wrapped(body),
+                _ => ui.text_wrapped(fl!("release-notes-empty")),
+            }
+        });
+    }
+
+    pub fn draw_changes(&self, ui: &Ui, changes: &TimerChanges) {
+        let title = fl!(
+            "timer-changes",
+            added = changes.added.len(),
+            removed = changes.removed.len(),
+            changed = changes.changed.len()
+        );
+        TreeNode::new(&title).build(ui, || {
+            let from = changes
+                .from
+                .clone()
+                .unwrap_or_else(|| fl!("timer-changes-nothing"));
+            let to = changes
+                .to
+                .clone()
+                .unwrap_or_else(|| fl!("timer-changes-nothing"));
+            ui.text_disabled(fl!("timer-changes-versions", from = from, to = to));
+            if changes.is_empty() {
+                ui.text_wrapped(fl!("timer-changes-none"));
+            }
+            for (ids, prefix, colour) in [
+                (&changes.added, "+", [0.0, 1.0, 0.0, 1.0]),
+                (&changes.removed, "-", [1.0, 0.0, 0.0, 1.0]),
+                (&changes.changed, "~", [1.0, 0.6, 0.0, 1.0]),
+            ] {
+                for id in ids {
+                    ui.text_colored(colour, format!("{prefix} {id}"));
+                }
+            }
+        });
+    }
+
     pub fn draw_uninstall(&self, ui: &Ui, rs: &RemoteState) {
-        let source_text = &rs.source.source().repo_string();
+        let source_text = &rs.source.to_string();
         let modal_name = fl!("addon-uninstall-modal-title", source = source_text);
         if ui.button(&fl!("addon-uninstall-modal-button")) {
             ui.open_popup(&modal_name);
@@ -85,18 +310,28 @@ impl DataSourceTabState {
                     ui.tooltip_text(fl!("reload-data-sources-tooltip"));
                 }
                 ui.same_line();
-                if let Some(last_checked) = &settings.last_checked {
+                if let Some(last_checked) = &settings.state.last_checked {
                     let time_display = last_checked.format("%F %T %Z").to_string();
                     ui.text(fl!("checked-for-updates-last", time = time_display));
                 } else {
                     ui.text(fl!("checked-for-updates-last", time = "Never"));
                 }
+                let available = settings.updates_available().len();
+                if available > 0 {
+                    ui.text_colored(
+                        [1.0, 0.6, 0.0, 1.0],
+                        fl!("updates-available", count = available),
+                    );
+                }
+                self.draw_update_settings(ui, &settings.config.updates);
+                self.draw_github_token(ui, settings.config.github_token.as_deref());
                 ui.dummy([8.0, 8.0]);
                 let table_flags = TableFlags::RESIZABLE | TableFlags::ROW_BG | TableFlags::BORDERS;
                 let table_token = ui.begin_table_header_with_flags(
                     "remotes",
                     [
                         TableColumnSetup::new(fl!("remote")),
+                        TableColumnSetup::new(fl!("source-contents")),
                         TableColumnSetup::new(fl!("description")),
                         TableColumnSetup::new(fl!("update-status")),
                         TableColumnSetup::new(fl!("actions")),
@@ -104,18 +339,29 @@ impl DataSourceTabState {
                     table_flags,
                 );
                 ui.table_next_column();
-                for download_data in &settings.remotes {
+                for download_data in &settings.state.remotes {
                     let source_arc = download_data.source.clone();
-                    let source = source_arc.source();
-                    let source_text = source.to_string();
+                    let source_text = source_arc.to_string();
                     let pushy = ui.push_id(&source_text);
-                    ui.text(format!("{}", source));
+                    ui.text(&source_text);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text(fl!("source-kind", kind = source_arc.kind()));
+                    }
                     ui.table_next_column();
-                    if let Some(description) = &source.description {
+                    ui.text(match download_data.kind {
+                        SourceKind::Timers => fl!("timers"),
+                        SourceKind::Markers => fl!("markers"),
+                        SourceKind::Pathing => fl!("pathing"),
+                    });
+                    ui.table_next_column();
+                    if let Some(description) = source_arc.description() {
                         ui.text_wrapped(description);
                     } else {
                         ui.text_wrapped(fl!("no-description"));
                     }
+                    if let Some(pack) = download_data.pack.as_ref().filter(|p| p.has_manifest) {
+                        self.draw_pack(ui, pack);
+                    }
                     ui.table_next_column();
                     if let Some(installed) = &download_data.installed_tag {
                         ui.text_wrapped(fl!("version-installed", version = installed));
@@ -123,28 +369,55 @@ impl DataSourceTabState {
                         ui.text_wrapped(fl!("version-not-installed"));
                     }
                     download_data.needs_update.draw(ui);
+                    if let Some(notes) = &download_data.release_notes {
+                        self.draw_release_notes(ui, notes);
+                    }
+                    if let Some(changes) = &download_data.changes {
+                        self.draw_changes(ui, changes);
+                    }
                     ui.table_next_column();
                     use NeedsUpdate::*;
                     let button_text = match &download_data.needs_update {
                         Unknown => Some(fl!("attempt-update")),
                         Known(true, _id) => Some(fl!("update")),
                         Known(false, _id) => None,
+                        Pinned(true, _id) => Some(fl!("install-pinned")),
+                        Pinned(false, _id) => None,
                         Error(_err) => None,
+                        RateLimited(_reset) => None,
+                        Unauthorized(_host) => None,
                     };
                     if let Some(button_text) = button_text {
                         if ui.button(button_text) {
                             let sender = CONTROLLER_SENDER.get().unwrap();
                             let event_send = sender.try_send(ControllerEvent::DoDataSourceUpdate {
-                                source: source_arc,
+                                source: source_arc.clone(),
                             });
                             drop(event_send);
                         }
                     }
+                    if let (true, Some(previous)) =
+                        (download_data.can_roll_back(), &download_data.previous_tag)
+                    {
+                        if ui.button(fl!("roll-back", version = previous)) {
+                            let sender = CONTROLLER_SENDER.get().unwrap();
+                            let event_send = sender.try_send(ControllerEvent::RollBackDataSource {
+                                source: source_arc.clone(),
+                            });
+                            drop(event_send);
+                        }
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text(fl!("roll-back-tooltip"));
+                        }
+                    }
+                    if source_arc.supports_channels() {
+                        self.draw_channel(ui, download_data);
+                    }
                     RenderState::draw_open_button(
                         state_errors,
                         ui,
                         fl!("open-button", kind = "repository"),
-                        source.view_url(),
+                        source_arc.view_url(),
                     );
                     if let Some(path) = &download_data.installed_path {
                         if let Some(path) = path.to_str() {
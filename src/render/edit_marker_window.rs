@@ -4,8 +4,12 @@ use {
         fl,
         marker::{
             atomic::MarkerInputData,
-            format::{MarkerEntry, MarkerFiletype, MarkerSet, MarkerType},
+            format::{
+                AgentMarkerEntry, MarkerEntry, MarkerFiletype, MarkerSet, MarkerType,
+                DEFAULT_TRIGGER_RADIUS,
+            },
         },
+        timer::BlishArea,
         util::{ComboInput, PositionInput, UiExt},
         ControllerEvent, ACCOUNT_NAME_CELL, CONTROLLER_SENDER,
     },
@@ -19,6 +23,7 @@ use {
     },
     std::{f32, mem, path::PathBuf},
     strum::IntoEnumIterator,
+    uuid::Uuid,
 };
 
 /*
@@ -35,6 +40,13 @@ pub struct EditMarkerWindowState {
     pub author: String,
     pub category: ComboInput,
     pub trigger: PositionInput,
+    pub trigger_radius: f32,
+    pub require_entry: bool,
+    pub require_departure: bool,
+    // not editable here yet, just carried through so edits don't lose them
+    pub area: Option<BlishArea>,
+    pub agent_markers: Vec<AgentMarkerEntry>,
+    pub uuid: Option<Uuid>,
     pub map_id: i32,
     pub markers: [IndividualMarkerState; 8],
     pub path: Option<String>,
@@ -108,6 +120,12 @@ impl EditMarkerWindowState {
             formatted_name: Default::default(),
             name: Default::default(),
             trigger: Default::default(),
+            trigger_radius: DEFAULT_TRIGGER_RADIUS,
+            require_entry: true,
+            require_departure: false,
+            area: Default::default(),
+            agent_markers: Default::default(),
+            uuid: Default::default(),
             category: ComboInput::new(&fl!("category")),
             description: Default::default(),
             map_id: Default::default(),
@@ -147,6 +165,13 @@ impl EditMarkerWindowState {
         if pos_count == 0 {
             conditions.push(fl!("no-positions"));
         }
+        if self
+            .agent_markers
+            .iter()
+            .any(|entry| entry.marker.to_set_agent_gamebind().is_none())
+        {
+            conditions.push(fl!("agent-marker-blank"));
+        }
         conditions
     }
 
@@ -221,9 +246,16 @@ impl EditMarkerWindowState {
             .collect();
         Some(MarkerSet {
             enabled,
+            uuid: self.uuid,
             category: self.category.result(),
             markers,
             trigger: self.trigger.position?.into(),
+            trigger_radius: (self.trigger_radius != DEFAULT_TRIGGER_RADIUS)
+                .then_some(self.trigger_radius.into()),
+            area: self.area.clone(),
+            agent_markers: self.agent_markers.clone(),
+            require_entry: self.require_entry,
+            require_departure: self.require_departure,
             name: self.name.clone(),
             author: Some(self.author.clone()),
             map_id: self.map_id as u32, // thanks imgui types o.o
@@ -249,6 +281,12 @@ impl EditMarkerWindowState {
             self.category.entry = ms.category;
             self.name = ms.name;
             self.trigger.position = Some(trigger_position);
+            self.trigger_radius = ms.trigger_radius();
+            self.area = ms.area;
+            self.agent_markers = ms.agent_markers;
+            self.uuid = ms.uuid;
+            self.require_entry = ms.require_entry;
+            self.require_departure = ms.require_departure;
             self.description = ms.description;
             self.author = ms.author.unwrap_or("".to_string());
             self.map_id = ms.map_id as i32;
@@ -322,6 +360,18 @@ impl EditMarkerWindowState {
                     self.trigger.draw_display(ui, true);
                     self.trigger.draw_take_current(ui);
                     self.trigger.draw_edit_manual(ui, true);
+                    if self.area.is_none() {
+                        let radius_name = fl!("trigger-radius");
+                        let radius_input = ui.input_float(&radius_name, &mut self.trigger_radius);
+                        if radius_input.build() {
+                            self.trigger_radius = self.trigger_radius.max(0.1);
+                        }
+                    } else {
+                        ui.text_wrapped(&fl!("trigger-area-set"));
+                    }
+                    ui.checkbox(&fl!("require-entry"), &mut self.require_entry);
+                    ui.same_line();
+                    ui.checkbox(&fl!("require-departure"), &mut self.require_departure);
                     ui.dummy([4.0; 2]);
                     if let Some(rtapi) = RealTimeApi::get() {
                         if let Some(group) = rtapi.read_group() {
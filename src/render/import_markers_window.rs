@@ -0,0 +1,277 @@
+use {
+    crate::{
+        controller::MarkerSaveEvent,
+        fl,
+        marker::{
+            format::{MarkerFiletype, MarkerSet},
+            share::{ImportProblem, SharedMarkers},
+        },
+        render::edit_marker_window::MarkerSaveMode,
+        util::UiExt,
+        ControllerEvent, CONTROLLER_SENDER,
+    },
+    nexus::imgui::{ComboBox, Selectable, Ui, Window},
+    std::{path::PathBuf, sync::Arc},
+    strum::IntoEnumIterator,
+};
+
+pub struct ImportMarkersWindowState {
+    pub open: bool,
+    code: String,
+    shared: Option<SharedMarkers>,
+    problems: Vec<Vec<ImportProblem>>,
+    chosen: Vec<bool>,
+    error: Option<String>,
+    save_mode: Option<MarkerSaveMode>,
+    filetype: Option<MarkerFiletype>,
+    path: Option<String>,
+    filenames: Vec<PathBuf>,
+    // everything loaded, for the duplicate check
+    existing: Vec<Arc<MarkerSet>>,
+}
+
+impl ImportMarkersWindowState {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            code: Default::default(),
+            shared: Default::default(),
+            problems: Default::default(),
+            chosen: Default::default(),
+            error: Default::default(),
+            save_mode: Default::default(),
+            filetype: Default::default(),
+            path: Default::default(),
+            filenames: Default::default(),
+            existing: Default::default(),
+        }
+    }
+
+    pub fn open(&mut self) {
+        if !self.open {
+            self.code.clear();
+            self.shared = None;
+            self.error = None;
+            let sender = CONTROLLER_SENDER.get().unwrap();
+            let event_send = sender.try_send(ControllerEvent::GetMarkerPaths);
+            drop(event_send);
+            self.open = true;
+        }
+    }
+
+    pub fn set_filenames(&mut self, filenames: Vec<PathBuf>) {
+        self.filenames = filenames;
+    }
+
+    pub fn markers_update<'a>(&mut self, sets: impl Iterator<Item = &'a Arc<MarkerSet>>) {
+        self.existing = sets.cloned().collect();
+        // something may have been imported since it was decoded
+        if let Some(shared) = &self.shared {
+            self.problems = shared.problems(&self.existing);
+        }
+    }
+
+    fn decode(&mut self) {
+        match SharedMarkers::decode(&self.code) {
+            Ok(shared) => {
+                self.problems = shared.problems(&self.existing);
+                // anything with a problem has to be asked for
+                self.chosen = self.problems.iter().map(Vec::is_empty).collect();
+                self.shared = Some(shared);
+                self.error = None;
+            }
+            Err(err) => {
+                self.shared = None;
+                self.error = Some(err.to_string());
+            }
+        }
+    }
+
+    fn chosen_sets(&self) -> Vec<MarkerSet> {
+        let Some(shared) = &self.shared else {
+            return Vec::new();
+        };
+        shared
+            .sets
+            .iter()
+            .zip(&self.chosen)
+            .zip(&self.problems)
+            .filter(|((_, chosen), problems)| {
+                **chosen && !problems.contains(&ImportProblem::NoMapId)
+            })
+            .map(|((ms, _), _)| ms.clone())
+            .collect()
+    }
+
+    fn import(&mut self) -> bool {
+        let sets = self.chosen_sets();
+        let path = self.path.clone().filter(|path| !path.is_empty());
+        let (Some(path), false) = (path, sets.is_empty()) else {
+            return false;
+        };
+        let evt = match (&self.save_mode, &self.filetype) {
+            (Some(MarkerSaveMode::Create), Some(filetype)) => {
+                MarkerSaveEvent::Import(sets, path.into(), Some(filetype.clone()))
+            }
+            (Some(MarkerSaveMode::Append), _) => MarkerSaveEvent::Import(sets, path.into(), None),
+            _ => return false,
+        };
+        let sender = CONTROLLER_SENDER.get().unwrap();
+        let event_send = sender.try_send(ControllerEvent::SaveMarker(evt));
+        drop(event_send);
+        true
+    }
+
+    pub fn draw(&mut self, ui: &Ui) {
+        let mut open = self.open;
+        if open {
+            let closed = Window::new(&fl!("import-markers"))
+                .size([300.0, 200.0], nexus::imgui::Condition::FirstUseEver)
+                .opened(&mut open)
+                .build(ui, || {
+                    ui.input_text_multiline(&fl!("share-code"), &mut self.code, [0.0, 0.0])
+                        .build();
+                    if ui.button(&fl!("paste")) {
+                        if let Some(text) = ui.clipboard_text() {
+                            self.code = text;
+                            self.decode();
+                        }
+                    }
+                    ui.same_line();
+                    if ui.button(&fl!("share-code-read")) {
+                        self.decode();
+                    }
+                    if let Some(error) = &self.error {
+                        ui.text_colored([1.0, 0.0, 0.0, 1.0], error);
+                    }
+                    let Some(shared) = &self.shared else {
+                        return false;
+                    };
+                    ui.dummy([4.0; 2]);
+                    if let Some(category) = &shared.category {
+                        ui.text(&fl!("category-arg", category = category.clone()));
+                    }
+                    for (i, ms) in shared.sets.iter().enumerate() {
+                        let problems = &self.problems[i];
+                        let no_map = problems.contains(&ImportProblem::NoMapId);
+                        let label = format!("{}##import{i}", ms.name);
+                        if ui.checkbox(&label, &mut self.chosen[i]) && no_map {
+                            self.chosen[i] = false;
+                        }
+                        ui.same_line();
+                        ui.text_disabled(&fl!("map-id-arg", id = ms.map_id));
+                        for problem in problems {
+                            let text = match problem {
+                                ImportProblem::NoMapId => fl!("import-no-map-id"),
+                                ImportProblem::Duplicate => fl!("import-duplicate"),
+                            };
+                            ui.bullet();
+                            ui.text_colored([1.0, 1.0, 0.0, 1.0], text);
+                        }
+                    }
+                    ui.dummy([4.0; 2]);
+                    self.draw_target(ui);
+                    ui.dummy([4.0; 2]);
+                    if ui.button(&fl!("import")) && self.import() {
+                        return true;
+                    }
+                    false
+                });
+            self.open = match closed {
+                Some(true) => false,
+                _ => open,
+            };
+        }
+    }
+
+    fn draw_target(&mut self, ui: &Ui) {
+        let msm_name = |item: &MarkerSaveMode| match item {
+            MarkerSaveMode::Create => fl!("save-standalone"),
+            MarkerSaveMode::Append => fl!("save-append"),
+            _ => "".to_string(),
+        };
+        let save_mode_closure = || {
+            let mut selected = self.save_mode.clone();
+            for item in [MarkerSaveMode::Create, MarkerSaveMode::Append].iter() {
+                if Selectable::new(msm_name(item))
+                    .selected(Some(item) == self.save_mode.as_ref())
+                    .build(ui)
+                {
+                    selected = Some(item.clone());
+                    // same as the edit window, the two kinds of path don't mix
+                    self.path = None;
+                }
+            }
+            selected
+        };
+        let combo_box_text = match &self.save_mode {
+            Some(s) => msm_name(s),
+            None => "".to_string(),
+        };
+        if let Some(Some(selection)) = ComboBox::new(fl!("save-mode"))
+            .preview_value(combo_box_text)
+            .build(ui, save_mode_closure)
+        {
+            self.save_mode = Some(selection);
+        }
+        match self.save_mode {
+            Some(MarkerSaveMode::Create) => {
+                let filetype_closure = || {
+                    let mut selected = self.filetype.clone();
+                    for item in MarkerFiletype::iter() {
+                        if Selectable::new(item.to_string())
+                            .selected(Some(&item) == self.filetype.as_ref())
+                            .build(ui)
+                        {
+                            selected = Some(item.clone());
+                        }
+                    }
+                    selected
+                };
+                let combo_box_text = match &self.filetype {
+                    Some(s) => s.to_string(),
+                    None => "".to_string(),
+                };
+                if let Some(Some(selection)) = ComboBox::new(fl!("filetype"))
+                    .preview_value(combo_box_text)
+                    .build(ui, filetype_closure)
+                {
+                    self.filetype = Some(selection);
+                }
+                ui.help_marker(|| {
+                    ui.tooltip_text(fl!("marker-filetype-explanation"));
+                });
+                let filename = self.path.get_or_insert_default();
+                ui.input_text(fl!("filename"), filename).build();
+            }
+            Some(MarkerSaveMode::Append) => {
+                let filename_closure = || {
+                    let mut selected = self.path.clone();
+                    for item in &self.filenames {
+                        let path_name = format!("{}", item.display());
+                        if Selectable::new(&path_name)
+                            .selected(Some(&path_name) == self.path.as_ref())
+                            .build(ui)
+                        {
+                            selected = Some(path_name);
+                        }
+                    }
+                    selected
+                };
+                let combo_box_text = self.path.clone().unwrap_or_default();
+                if let Some(Some(selection)) = ComboBox::new(fl!("filename"))
+                    .preview_value(combo_box_text)
+                    .build(ui, filename_closure)
+                {
+                    self.path = Some(selection);
+                }
+                if ui.button(fl!("refresh-files")) {
+                    let sender = CONTROLLER_SENDER.get().unwrap();
+                    let event_send = sender.try_send(ControllerEvent::GetMarkerPaths);
+                    drop(event_send);
+                }
+            }
+            _ => (),
+        }
+    }
+}
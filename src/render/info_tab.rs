@@ -72,7 +72,7 @@ impl InfoTabState {
     pub fn space_info(&self, ui: &Ui) {
         RenderState::font_text("big", ui, &fl!("engine"));
         if let Some(settings) = SETTINGS.get().and_then(|settings| settings.try_read().ok()) {
-            if settings.enable_katrender && ENGINE_INITIALIZED.get() {
+            if settings.config.enable_katrender && ENGINE_INITIALIZED.get() {
                 ENGINE.with_borrow(|e| {
                     if let Some(engine) = e {
                         RenderState::font_text("ui", ui, &fl!("ecs-data"));
@@ -6,6 +6,8 @@ use {
         marker::{
             atomic::{LocalPoint, MarkerInputData, ScreenPoint, SignObtainer},
             format::MarkerSet,
+            placement::PlacementReport,
+            share::SharedMarkers,
         },
         render::RenderState,
         settings::MarkerSettings,
@@ -29,6 +31,8 @@ use {
 pub struct MarkerTabState {
     markers: IndexMap<String, Vec<Arc<MarkerSet>>>,
     pub marker_selection: Option<Arc<MarkerSet>>,
+    // how the last placement went, shown with the set it was for
+    pub placement: Option<PlacementReport>,
     category_status: HashSet<String>,
     formatted_name: String,
 }
@@ -40,6 +44,7 @@ impl MarkerTabState {
         Self {
             markers: Default::default(),
             marker_selection: Default::default(),
+            placement: Default::default(),
             category_status: Default::default(),
             formatted_name: Default::default(),
         }
@@ -76,6 +81,22 @@ impl MarkerTabState {
                 .try_send(RenderEvent::OpenEditMarkers(None));
         }
         ui.same_line();
+        #[cfg(feature = "markers-edit")]
+        if ui.button(fl!("import-markers")) {
+            let _ = RENDER_SENDER
+                .get()
+                .unwrap()
+                .try_send(RenderEvent::OpenImportMarkers);
+        }
+        ui.same_line();
+        #[cfg(feature = "markers-edit")]
+        if ui.button(fl!("convert-markers")) {
+            let _ = RENDER_SENDER
+                .get()
+                .unwrap()
+                .try_send(RenderEvent::OpenConvertMarkers);
+        }
+        ui.same_line();
         if ui.button(fl!("reload-markers")) {
             let sender = CONTROLLER_SENDER.get().unwrap();
             let event_send = sender.try_send(ControllerEvent::ReloadMarkers);
@@ -122,6 +143,12 @@ impl MarkerTabState {
             .expect("given an incorrect index for the category");
         let category_closure = || {
             ui.dummy([0.0, 4.0]);
+            if ui.small_button(&fl!("share-category")) {
+                Self::copy_share_code(
+                    ui,
+                    &SharedMarkers::category(category_name, category.as_slice()),
+                );
+            }
             for marker in category {
                 let mut selected = false;
                 if let Some(selected_marker) = &self.marker_selection {
@@ -170,7 +197,7 @@ impl MarkerTabState {
             selected = true;
         }
         if let Some(settings) = SETTINGS.get().and_then(|settings| settings.try_read().ok()) {
-            let settings_for_marker = settings.markers.get(&marker.id());
+            let settings_for_marker = settings.config.markers.get(&marker.id());
             ui.same_line();
             let (color, text) = match settings_for_marker {
                 Some(MarkerSettings { disabled: true, .. }) => ([1.0, 0.0, 0.0, 1.0], "Disabled"),
@@ -191,6 +218,13 @@ impl MarkerTabState {
         selected
     }
 
+    fn copy_share_code(ui: &Ui, shared: &SharedMarkers) {
+        match shared.encode() {
+            Ok(code) => ui.set_clipboard_text(code),
+            Err(err) => log::error!("Couldn't make a share code: {err}"),
+        }
+    }
+
     fn draw_main(&mut self, ui: &Ui) {
         let child_window_flags = WindowFlags::HORIZONTAL_SCROLLBAR;
         ChildWindow::new("timer_main")
@@ -354,6 +388,20 @@ impl MarkerTabState {
                     if let Some(token) = table_token {
                         token.end();
                     }
+                    if !selected_marker_set.agent_markers.is_empty() {
+                        ui.dummy([4.0; 2]);
+                        ui.text(&fl!(
+                            "agent-markers-arg",
+                            count = selected_marker_set.agent_markers.len()
+                        ));
+                        for entry in &selected_marker_set.agent_markers {
+                            ui.bullet_text(&fl!(
+                                "agent-marker-on",
+                                marker = entry.marker.to_string(),
+                                target = entry.target.to_string()
+                            ));
+                        }
+                    }
                     ui.dummy([4.0; 2]);
                     let button_text = match selected_marker_set.status() {
                         true => fl!("autoplacement-disable"),
@@ -366,18 +414,51 @@ impl MarkerTabState {
                         drop(event_send);
                     }
                     ui.dummy([4.0; 2]);
+                    if ui.button(&fl!("share-copy")) {
+                        Self::copy_share_code(ui, &SharedMarkers::set(selected_marker_set));
+                    }
+                    ui.dummy([4.0; 2]);
                     if ui.button(&fl!("markers-place")) {
                         let sender = CONTROLLER_SENDER.get().unwrap();
                         let event_send = sender
                             .try_send(ControllerEvent::SetMarker(selected_marker_set.clone()));
                         drop(event_send);
                     }
+                    if let Some(report) = self
+                        .placement
+                        .as_ref()
+                        .filter(|report| report.set == selected_marker_set.id())
+                    {
+                        Self::draw_placement(ui, report);
+                    }
                     pushy.pop();
                 } else {
                     ui.text(&fl!("select-a-marker"));
                 }
             });
     }
+    fn draw_placement(ui: &Ui, report: &PlacementReport) {
+        use crate::marker::placement::PlacementOutcome::*;
+        ui.dummy([4.0; 2]);
+        ui.text(&fl!(
+            "placement-result",
+            failed = report.failures(),
+            count = report.results.len()
+        ));
+        for (marker, outcome) in &report.results {
+            let (color, text) = match outcome {
+                Placed => ([0.0, 1.0, 0.0, 1.0], fl!("placement-placed")),
+                Unverified => ([1.0, 1.0, 0.0, 1.0], fl!("placement-unverified")),
+                TooFar => ([1.0, 0.0, 0.0, 1.0], fl!("placement-too-far")),
+                OutOfView => ([1.0, 0.0, 0.0, 1.0], fl!("placement-out-of-view")),
+                Missed => ([1.0, 0.0, 0.0, 1.0], fl!("placement-missed")),
+            };
+            marker.icon(ui);
+            ui.same_line();
+            ui.text_colored(color, text);
+        }
+    }
+
     pub fn marker_update(&mut self, markers: HashMap<String, Vec<Arc<MarkerSet>>>) {
         self.markers.clear();
         for (category, markers) in markers {
@@ -28,7 +28,7 @@ impl MarkerWindowState {
     pub fn draw(&mut self, ui: &Ui) {
         let mut open = self.open;
         if let Some(settings) = SETTINGS.get().and_then(|settings| settings.try_read().ok()) {
-            open = settings.markers_window_open;
+            open = settings.state.markers_window_open;
         };
         if open {
             Window::new(fl!("markers"))
@@ -1,7 +1,11 @@
 pub mod config_tab;
+#[cfg(feature = "markers-edit")]
+pub mod convert_markers_window;
 pub mod data_source_tab;
 #[cfg(feature = "markers-edit")]
 pub mod edit_marker_window;
+#[cfg(feature = "markers-edit")]
+pub mod import_markers_window;
 pub mod info_tab;
 #[cfg(feature = "markers")]
 pub mod marker_tab;
@@ -44,7 +44,7 @@ impl PrimaryWindowState {
     ) {
         let mut open = self.open;
         if let Some(settings) = SETTINGS.get().and_then(|settings| settings.try_read().ok()) {
-            open = settings.primary_window_open;
+            open = settings.state.primary_window_open;
         };
         if open {
             Window::new(&fl!("primary-window"))
@@ -1,5 +1,8 @@
 #[cfg(feature = "markers")]
-use {crate::marker::atomic::MarkerInputData, crate::marker::format::MarkerSet};
+use {
+    crate::marker::atomic::MarkerInputData, crate::marker::format::MarkerSet,
+    crate::marker::placement::PlacementReport,
+};
 use {
     crate::{
         controller::ControllerEvent,
@@ -23,7 +26,7 @@ use {
     serde::{Deserialize, Serialize},
     std::{
         collections::HashMap,
-        path::PathBuf,
+        path::Path,
         sync::{Arc, MutexGuard},
     },
     strum_macros::{Display, EnumIter},
@@ -31,13 +34,21 @@ use {
 };
 
 #[cfg(feature = "markers-edit")]
-use super::edit_marker_window::EditMarkerWindowState;
+use {
+    super::{
+        convert_markers_window::ConvertMarkersWindowState,
+        edit_marker_window::EditMarkerWindowState, import_markers_window::ImportMarkersWindowState,
+    },
+    std::path::PathBuf,
+};
 
 pub enum RenderEvent {
     TimerData(Vec<Arc<TimerFile>>),
     #[cfg(feature = "markers")]
     MarkerData(HashMap<String, Vec<Arc<MarkerSet>>>),
     MarkerMap(Vec<Arc<MarkerSet>>),
+    #[cfg(feature = "markers")]
+    MarkerPlacement(PlacementReport),
     AlertFeed(PhaseState),
     OpenableError(String, anyhow::Error),
     AlertReset(Arc<TimerFile>),
@@ -49,6 +60,10 @@ pub enum RenderEvent {
     #[cfg(feature = "markers-edit")]
     OpenEditMarkers(Option<MarkerSet>),
     #[cfg(feature = "markers-edit")]
+    OpenImportMarkers,
+    #[cfg(feature = "markers-edit")]
+    OpenConvertMarkers,
+    #[cfg(feature = "markers-edit")]
     GiveMarkerPaths(Vec<PathBuf>),
     ProgressBarUpdate(ProgressBarSettings),
 }
@@ -68,6 +83,10 @@ pub struct RenderState {
     pub primary_window: PrimaryWindowState,
     #[cfg(feature = "markers-edit")]
     pub edit_marker_window: EditMarkerWindowState,
+    #[cfg(feature = "markers-edit")]
+    pub import_markers_window: ImportMarkersWindowState,
+    #[cfg(feature = "markers-edit")]
+    pub convert_markers_window: ConvertMarkersWindowState,
     #[cfg(feature = "markers")]
     pub marker_window: MarkerWindowState,
     timer_window: TimerWindowState,
@@ -86,6 +105,10 @@ impl RenderState {
             timer_window: TimerWindowState::new(),
             #[cfg(feature = "markers-edit")]
             edit_marker_window: EditMarkerWindowState::new(),
+            #[cfg(feature = "markers-edit")]
+            import_markers_window: ImportMarkersWindowState::new(),
+            #[cfg(feature = "markers-edit")]
+            convert_markers_window: ConvertMarkersWindowState::new(),
             #[cfg(feature = "markers")]
             marker_window: MarkerWindowState::new(),
             last_display_size: Default::default(),
@@ -115,12 +138,22 @@ impl RenderState {
                         None => self.edit_marker_window.open(),
                         Some(e) => self.edit_marker_window.open_edit(e),
                     },
+                    #[cfg(feature = "markers-edit")]
+                    OpenImportMarkers => self.import_markers_window.open(),
+                    #[cfg(feature = "markers-edit")]
+                    OpenConvertMarkers => self.convert_markers_window.open(),
+                    #[cfg(feature = "markers")]
+                    MarkerPlacement(report) => {
+                        self.primary_window.marker_tab.placement = Some(report);
+                    }
                     #[cfg(feature = "markers")]
                     MarkerMap(markers) => {
                         self.marker_window.new_map_markers(markers);
                     }
                     #[cfg(feature = "markers-edit")]
                     GiveMarkerPaths(paths) => {
+                        self.import_markers_window.set_filenames(paths.clone());
+                        self.convert_markers_window.set_filenames(paths.clone());
                         self.edit_marker_window.set_filenames(paths);
                     }
                     OpenableError(key, err) => {
@@ -146,6 +179,9 @@ impl RenderState {
                         let categories: Vec<_> = markers.keys().cloned().collect();
                         #[cfg(feature = "markers-edit")]
                         self.edit_marker_window.category_update(categories);
+                        #[cfg(feature = "markers-edit")]
+                        self.import_markers_window
+                            .markers_update(markers.values().flatten());
                         self.primary_window.marker_tab.marker_update(markers);
                     }
                     AlertStart(alert) => {
@@ -176,6 +212,10 @@ impl RenderState {
         self.marker_window.draw(ui);
         #[cfg(feature = "markers-edit")]
         self.edit_marker_window.draw(ui);
+        #[cfg(feature = "markers-edit")]
+        self.import_markers_window.draw(ui);
+        #[cfg(feature = "markers-edit")]
+        self.convert_markers_window.draw(ui);
         let mut items_to_delete = Vec::new();
         for (entry_name, errory) in &self.state_errors {
             ui.open_popup(entry_name);
@@ -221,10 +261,10 @@ impl RenderState {
         ui: &Ui,
         height: Option<f32>,
         alert_icon: Option<&RelativePathBuf>,
-        path: Option<&PathBuf>,
+        base: Option<&Path>,
     ) {
         if let Some(icon) = alert_icon {
-            if let Some(path) = path {
+            if let Some(base) = base {
                 let gooey = IMGUI_TEXTURES.get().unwrap();
                 let gooey_lock = gooey.read().unwrap();
                 let path_str = icon.as_str();
@@ -240,7 +280,7 @@ impl RenderState {
                     let sender = CONTROLLER_SENDER.get().unwrap();
                     let event_send = sender.try_send(ControllerEvent::LoadTexture(
                         icon.clone(),
-                        path.to_path_buf(),
+                        base.to_path_buf(),
                     ));
                     drop(event_send);
                 }
@@ -4,7 +4,7 @@ use {
         controller::ControllerEvent,
         fl,
         render::RenderState,
-        settings::{RemoteSource, TimerSettings},
+        settings::{RemoteSource, Settings, TimerScope},
         timer::TimerFile,
         CONTROLLER_SENDER, SETTINGS,
     },
@@ -165,7 +165,7 @@ impl TimerTabState {
         let widget_pos = Vec2::from(ui.cursor_pos());
         let window_size = Vec2::from(ui.window_content_region_max());
         let widget_size = window_size.with_y(height);
-        RenderState::icon(ui, Some(height), Some(&timer.icon), timer.path.as_ref());
+        RenderState::icon(ui, Some(height), Some(&timer.icon), timer.icon_base());
         if Selectable::new(&timer.combined())
             .selected(selected)
             .build(ui)
@@ -173,13 +173,10 @@ impl TimerTabState {
             selected = true;
         }
         if let Some(settings) = SETTINGS.get().and_then(|settings| settings.try_read().ok()) {
-            let settings_for_timer = settings.timers.get(&timer.id);
             ui.same_line();
-            let (color, text) = match settings_for_timer {
-                Some(TimerSettings { disabled: true, .. }) => {
-                    ([1.0, 0.0, 0.0, 1.0], &fl!("disabled"))
-                }
-                _ => ([0.0, 1.0, 0.0, 1.0], &fl!("enabled")),
+            let (color, text) = match settings.timer_enabled(timer) {
+                false => ([1.0, 0.0, 0.0, 1.0], &fl!("disabled")),
+                true => ([0.0, 1.0, 0.0, 1.0], &fl!("enabled")),
             };
             let text_size = Vec2::from(ui.calc_text_size(text));
             Alignment::set_cursor(
@@ -207,7 +204,7 @@ impl TimerTabState {
                         ui,
                         None,
                         Some(&selected_timer.icon),
-                        selected_timer.path.as_ref(),
+                        selected_timer.icon_base(),
                     );
                     ui.same_line();
                     let split_name = selected_timer.name.split("\n");
@@ -258,23 +255,55 @@ impl TimerTabState {
                     if let Some(settings) =
                         SETTINGS.get().and_then(|settings| settings.try_read().ok())
                     {
-                        let settings_for_timer = settings.timers.get(&selected_timer.id);
-                        let button_text = match settings_for_timer {
-                            Some(TimerSettings { disabled: true, .. }) => &fl!("enable"),
-                            _ => &fl!("disable"),
-                        };
-                        if ui.button(button_text) {
-                            let sender = CONTROLLER_SENDER.get().unwrap();
-                            let event_send = sender
-                                .try_send(ControllerEvent::TimerToggle(selected_timer.id.clone()));
-                            drop(event_send);
-                        }
+                        Self::draw_switches(ui, &settings, selected_timer);
                     }
                 } else {
                     ui.text(&fl!("select-a-timer"));
                 }
             });
     }
+
+    /*
+     * The timer's own switch, then the ones for everything else it came with.
+     * Those only apply to timers that haven't been switched themselves.
+     */
+    fn draw_switches(ui: &Ui, settings: &Settings, timer: &TimerFile) {
+        let button_text = match settings.timer_enabled(timer) {
+            false => fl!("enable"),
+            true => fl!("disable"),
+        };
+        if ui.button(button_text) {
+            let sender = CONTROLLER_SENDER.get().unwrap();
+            let event_send =
+                sender.try_send(ControllerEvent::TimerToggle(TimerScope::timer(timer)));
+            drop(event_send);
+        }
+        let scopes = [
+            (
+                TimerScope::category(timer),
+                fl!("timer-category-enable", category = timer.category.clone()),
+                fl!("timer-category-disable", category = timer.category.clone()),
+            ),
+            (
+                TimerScope::source(timer),
+                fl!("timer-source-enable"),
+                fl!("timer-source-disable"),
+            ),
+        ];
+        for (scope, enable_text, disable_text) in scopes {
+            let enabled = settings.scope_enabled(&scope);
+            let (button_text, event) = match enabled {
+                true => (disable_text, ControllerEvent::TimerDisable(scope)),
+                false => (enable_text, ControllerEvent::TimerEnable(scope)),
+            };
+            if ui.button(button_text) {
+                let sender = CONTROLLER_SENDER.get().unwrap();
+                let event_send = sender.try_send(event);
+                drop(event_send);
+            }
+        }
+    }
+
     pub fn timers_update(&mut self, timers: Vec<Arc<TimerFile>>) {
         self.timers = timers;
         self.sources_to_timers.clear();
@@ -3,7 +3,7 @@ use {
     crate::{
         fl,
         settings::ProgressBarSettings,
-        timer::{PhaseState, TimerAlert, TimerFile},
+        timer::{PhaseState, TimerAlert, TimerDirection, TimerFile},
         ControllerEvent, CONTROLLER_SENDER, SETTINGS,
     },
     glam::Vec2,
@@ -29,8 +29,8 @@ impl TimerWindowState {
     pub fn draw(&mut self, ui: &Ui) {
         let mut open = self.open;
         if let Some(settings) = SETTINGS.get().and_then(|settings| settings.try_read().ok()) {
-            open = settings.timers_window_open;
-            self.progress_bar = settings.progress_bar.clone();
+            open = settings.state.timers_window_open;
+            self.progress_bar = settings.config.progress_bar.clone();
         };
         if open {
             Window::new(fl!("timers"))
@@ -58,6 +58,9 @@ impl TimerWindowState {
                                 Self::progress_bar(&self.progress_bar, alert, ui, ps);
                             }
                         }
+                        for direction in ps.directions.iter() {
+                            Self::direction(&self.progress_bar, direction, ui, ps);
+                        }
                     }
                 });
         }
@@ -81,12 +84,7 @@ impl TimerWindowState {
             if !settings.centre_after {
                 widget_pos = Vec2::from(ui.cursor_pos());
             }
-            RenderState::icon(
-                ui,
-                Some(height),
-                alert.icon.as_ref(),
-                ps.timer.path.as_ref(),
-            );
+            RenderState::icon(ui, Some(height), alert.icon.as_ref(), ps.timer.icon_base());
             if settings.centre_after {
                 widget_pos = Vec2::from(ui.cursor_pos());
             }
@@ -129,12 +127,7 @@ impl TimerWindowState {
         let start = ps.start;
         let height = settings.height;
         if let Some(percent) = alert.percentage(start) {
-            RenderState::icon(
-                ui,
-                Some(height),
-                alert.icon.as_ref(),
-                ps.timer.path.as_ref(),
-            );
+            RenderState::icon(ui, Some(height), alert.icon.as_ref(), ps.timer.icon_base());
             let mut colour_tokens = Vec::new();
             if let Some(fill_colour) = alert.fill_colour {
                 colour_tokens
@@ -153,6 +146,39 @@ impl TimerWindowState {
         }
     }
 
+    fn direction(
+        settings: &ProgressBarSettings,
+        direction: &TimerDirection,
+        ui: &Ui,
+        ps: &PhaseState,
+    ) {
+        if !direction.is_active(ps.start) {
+            return;
+        }
+        let height = settings.height;
+        RenderState::icon(
+            ui,
+            Some(height),
+            Some(&direction.texture),
+            ps.timer.icon_base(),
+        );
+        #[cfg(feature = "markers")]
+        let distance = crate::marker::atomic::MarkerInputData::read()
+            .map(|mid| direction.distance(mid.local_player_pos));
+        #[cfg(not(feature = "markers"))]
+        let distance: Option<f32> = None;
+        let text = match distance {
+            Some(distance) => fl!(
+                "direction-distance",
+                name = direction.name.clone(),
+                distance = format!("{:.0}", distance)
+            ),
+            None => direction.name.clone(),
+        };
+        ui.text(text);
+        ui.dummy([0.0, height / 4.0]);
+    }
+
     pub fn new_phase(&mut self, phase_state: PhaseState) {
         self.phase_states.push(phase_state);
     }
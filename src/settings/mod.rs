@@ -3,7 +3,9 @@ mod progress_bar_config;
 mod settings_struct;
 mod source;
 mod sources;
+mod update_policy;
 mod v1;
+mod v2;
 
 pub use {
     progress_bar_config::ProgressBarSettings,
@@ -11,7 +13,15 @@ pub use {
         MarkerAutoPlaceSettings, MarkerSettings, NeedsUpdate, Settings, SettingsLock,
         SquadCondition,
     },
-    source::{GitHubSource, RemoteSource, Source},
+    source::{
+        Channel, GitHubSource, GitLabSource, GiteaSource, HttpSource, LocalSource, ReleaseNotes,
+        RemoteSource, Source,
+    },
     sources::{SourceKind, SourcesFile},
-    v1::{RemoteState, TimerSettings},
+    update_policy::{UpdateAction, UpdateCheckPolicy, UpdateSettings},
+    v1::{
+        RemoteState, SettingsV1, TimerChanges, TimerScope, TimerSettings, TimerSourceSettings,
+        LEGACY_SETTINGS_NAME,
+    },
+    v2::{ConfigV2, StateFile},
 };
@@ -1,22 +1,27 @@
+#[cfg(feature = "markers")]
+use crate::marker::format::MarkerSet;
 use {
-    super::{ProgressBarSettings, RemoteSource, RemoteState, Source, SourceKind, TimerSettings},
-    crate::{controller::ProgressBarStyleChange, SETTINGS, SOURCES},
+    super::{
+        source::{install_atomically, Channel, RequestError},
+        v2::{set_aside, CONFIG_NAME, STATE_NAME},
+        ConfigV2, GitHubSource, ProgressBarSettings, ReleaseNotes, RemoteSource, RemoteState,
+        SettingsV1, Source, SourceKind, StateFile, TimerChanges, TimerScope, TimerSettings,
+        UpdateSettings, LEGACY_SETTINGS_NAME,
+    },
+    crate::{controller::ProgressBarStyleChange, timer::TimerFile, SETTINGS, SOURCES},
     anyhow::anyhow,
-    chrono::{DateTime, Utc},
+    chrono::{DateTime, Local, Utc},
     futures::stream::StreamExt,
-    magic_migrate::TryMigrate,
     nexus::imgui::Ui,
     serde::{Deserialize, Serialize},
     std::{
-        collections::{HashMap, HashSet},
         fmt::{self},
         path::{Path, PathBuf},
         sync::Arc,
     },
     strum_macros::EnumIter,
     tokio::{
-        fs::{create_dir_all, read_to_string, try_exists, File},
-        io::AsyncWriteExt,
+        fs::{read_to_string, rename, try_exists},
         sync::RwLock,
     },
 };
@@ -28,6 +33,12 @@ pub enum NeedsUpdate {
     Unknown,
     Error(String),
     Known(bool, String),
+    // pinned to the given version, and whether it still needs installing
+    Pinned(bool, String),
+    // asked too often, until the given time if we were told it
+    RateLimited(Option<DateTime<Utc>>),
+    // the access token for the given host didn't work
+    Unauthorized(String),
 }
 
 impl fmt::Display for NeedsUpdate {
@@ -38,19 +49,43 @@ impl fmt::Display for NeedsUpdate {
             Error(e) => write!(f, "Error: {e}!"),
             Known(true, id) => write!(f, "Available: {}", id),
             Known(false, _id) => write!(f, "Up to date!"),
+            Pinned(true, id) => write!(f, "Pinned to {}, not installed yet", id),
+            Pinned(false, id) => write!(f, "Pinned to {}", id),
+            RateLimited(Some(reset)) => {
+                let reset = reset.with_timezone(&Local).format("%T");
+                write!(f, "Rate limited, try again after {}", reset)
+            }
+            RateLimited(None) => write!(f, "Rate limited, try again later"),
+            Unauthorized(host) => write!(f, "{} didn't accept the access token", host),
         }
     }
 }
 
 impl NeedsUpdate {
+    pub fn from_error(err: anyhow::Error) -> Self {
+        use NeedsUpdate::*;
+        match err.downcast_ref::<RequestError>() {
+            Some(RequestError::RateLimited(reset)) => RateLimited(*reset),
+            Some(RequestError::Unauthorized(host)) => Unauthorized(host.clone()),
+            None => Error(err.to_string()),
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        use NeedsUpdate::*;
+        matches!(self, Error(_) | RateLimited(_) | Unauthorized(_))
+    }
+
     pub fn draw(&self, ui: &Ui) {
         let text = self.to_string();
         use NeedsUpdate::*;
         match &self {
             Unknown => ui.text_colored([1.0, 1.0, 0.0, 1.0], text),
-            Error(_e) => ui.text_colored([1.0, 0.0, 0.0, 1.0], text),
-            Known(true, _id) => ui.text_colored([1.0, 0.6, 0.0, 1.0], text),
+            Error(_e) | Unauthorized(_e) => ui.text_colored([1.0, 0.0, 0.0, 1.0], text),
+            RateLimited(_reset) => ui.text_colored([1.0, 0.4, 0.0, 1.0], text),
+            Known(true, _id) | Pinned(true, _id) => ui.text_colored([1.0, 0.6, 0.0, 1.0], text),
             Known(false, _id) => ui.text_colored([0.0, 1.0, 0.0, 1.0], text),
+            Pinned(false, _id) => ui.text_colored([0.4, 0.8, 1.0, 1.0], text),
         }
     }
 }
@@ -114,31 +149,16 @@ impl fmt::Display for MarkerAutoPlaceSettings {
     }
 }
 
-#[derive(Deserialize, Serialize, TryMigrate, Default, Debug, Clone)]
-#[try_migrate(from = None)]
+/*
+ * The config and state files, as everything else sees them. They're kept
+ * apart on disk so that a state file that's gone wrong never takes anyone's
+ * choices with it.
+ */
+#[derive(Default, Debug, Clone)]
 pub struct Settings {
-    #[serde(default)]
-    pub last_checked: Option<DateTime<Utc>>,
-    #[serde(skip)]
     addon_dir: PathBuf,
-    #[serde(default)]
-    pub timers: HashMap<String, TimerSettings>,
-    #[serde(default)]
-    pub markers: HashMap<String, MarkerSettings>,
-    #[serde(default)]
-    pub remotes: Vec<RemoteState>,
-    #[serde(default)]
-    pub primary_window_open: bool,
-    #[serde(default)]
-    pub timers_window_open: bool,
-    #[serde(default)]
-    pub markers_window_open: bool,
-    #[serde(default)]
-    pub progress_bar: ProgressBarSettings,
-    #[serde(default)]
-    pub enable_katrender: bool,
-    #[serde(default)]
-    pub marker_autoplace: MarkerAutoPlaceSettings,
+    pub config: ConfigV2,
+    pub state: StateFile,
 }
 
 impl Settings {
@@ -146,28 +166,26 @@ impl Settings {
         log::debug!("Preparing to handle sources changes for settings");
         let sources = SOURCES.get().unwrap();
         let sources_lock = sources.read().unwrap();
-        if let Some(timer_sources) = sources_lock.0.get(&SourceKind::Timers) {
-            let sources_hashset: HashSet<&RemoteSource> = HashSet::from_iter(timer_sources.iter());
-            let mut found_sources = HashSet::new();
-            for source in timer_sources {
-                let inner_source_local = source.source();
-                if let Some(matching_remote) = self.remotes.iter_mut().find(|r| {
-                    found_sources.insert(source);
-                    let inner_source_remote = r.source();
-                    inner_source_local.owner == inner_source_remote.owner
-                        && inner_source_local.repository == inner_source_remote.repository
-                }) {
-                    if inner_source_local != matching_remote.source() {
-                        matching_remote.update(Arc::new(source.clone()));
+        for (&kind, kind_sources) in sources_lock.0.iter() {
+            for source in kind_sources {
+                // sources are the same one if they'd install to the same place
+                let matching_remote = self
+                    .state
+                    .remotes
+                    .iter_mut()
+                    .find(|r| r.kind == kind && source.install_dir() == r.source.install_dir());
+                match matching_remote {
+                    Some(matching_remote) => {
+                        if *source != *matching_remote.source {
+                            matching_remote.update(Arc::new(source.clone()));
+                        }
                     }
+                    None => self
+                        .state
+                        .remotes
+                        .push(RemoteState::new_from_source(source, kind)),
                 }
             }
-            let remaining = sources_hashset.symmetric_difference(&found_sources);
-            let remaining_vec: Vec<_> = remaining
-                .into_iter()
-                .map(|s| RemoteState::new_from_source(s))
-                .collect();
-            self.remotes.extend(remaining_vec);
         }
         drop(sources_lock);
     }
@@ -177,9 +195,8 @@ impl Settings {
         let all_sources = RemoteState::hardcoded_sources();
         let mut all_sources_data = RemoteState::hardcoded_sources();
         for (owner, repository, description) in all_sources {
-            for remote in &mut self.remotes {
-                let source = remote.source.source();
-                if owner == source.owner && repository == source.repository {
+            for remote in &mut self.state.remotes {
+                if remote.source.to_string() == format!("{owner}/{repository}") {
                     //*remote = remote.clone().update(description);
                     all_sources_data.retain(|x| *x != (owner, repository, description));
                     //description));
@@ -187,29 +204,42 @@ impl Settings {
             }
         }
         for (owner, repository, description) in all_sources_data {
-            self.remotes
+            self.state
+                .remotes
                 .push(RemoteState::new(owner, repository, description))
         }
     }
 
-    #[allow(dead_code)]
     pub fn count_disabled_timers(&self) -> usize {
-        self.timers.values().filter(|x| x.disabled).count()
+        let unscoped = self.config.timers.values().filter(|x| x.disabled).count();
+        let scoped: usize = self
+            .config
+            .timer_sources
+            .values()
+            .map(|s| s.count_disabled())
+            .sum();
+        unscoped + scoped
     }
 
     #[allow(dead_code)]
     pub fn get_paths(&self) -> Vec<&PathBuf> {
-        self.remotes
+        self.get_paths_for(SourceKind::Timers)
+    }
+
+    pub fn get_paths_for(&self, kind: SourceKind) -> Vec<&PathBuf> {
+        self.state
+            .remotes
             .iter()
+            .filter(|dd| dd.kind == kind)
             .filter_map(|dd| dd.installed_path.as_ref())
             .collect()
     }
 
     pub async fn set_window_state(&mut self, window: &str, state: Option<bool>) {
         let window_open = match window {
-            "primary" => &mut self.primary_window_open,
-            "timers" => &mut self.timers_window_open,
-            "markers" => &mut self.markers_window_open,
+            "primary" => &mut self.state.primary_window_open,
+            "timers" => &mut self.state.timers_window_open,
+            "markers" => &mut self.state.markers_window_open,
             _ => unreachable!("unsupported window"),
         };
 
@@ -224,67 +254,199 @@ impl Settings {
         let _ = self.save(&self.addon_dir).await;
     }
 
-    pub async fn toggle_timer(&mut self, timer: String) -> bool {
-        let entry = self.timers.entry(timer.clone()).or_default();
-        let new_state = entry.toggle();
-        let irrelevant = entry == &Default::default();
-        if irrelevant {
-            self.timers.remove(&timer);
+    /*
+     * A timer's own setting wins, then the source or its category being
+     * switched off, then whatever the pack says.
+     */
+    pub fn timer_enabled(&self, timer: &TimerFile) -> bool {
+        let source = self.config.timer_sources.get(&timer.source_key());
+        match source.and_then(|s| s.timers.get(&timer.id)) {
+            Some(setting) => !setting.disabled,
+            None => self.timer_inherited(timer),
         }
-        let _ = self.save(&self.addon_dir).await;
-        new_state
     }
-    pub async fn disable_timer(&mut self, timer: String) {
-        if let Some(entry_mut) = self.timers.get_mut(&timer) {
-            entry_mut.disable();
-        } else {
-            self.timers.insert(timer, TimerSettings { disabled: true });
+
+    // what the timer would be if it didn't have a setting of its own
+    fn timer_inherited(&self, timer: &TimerFile) -> bool {
+        // from back when ids were shared between sources
+        if let Some(setting) = self.config.timers.get(&timer.id) {
+            return !setting.disabled;
+        }
+        let source = self.config.timer_sources.get(&timer.source_key());
+        source.is_none_or(|s| s.inherited(timer)) && !timer.default_disabled
+    }
+
+    // for a source or a category, whether it's been left switched on
+    pub fn scope_enabled(&self, scope: &TimerScope) -> bool {
+        let Some(source) = self.config.timer_sources.get(scope.source_key()) else {
+            return true;
+        };
+        use TimerScope::*;
+        match scope {
+            Source(_) => !source.disabled,
+            Category { category, .. } => !source.disabled_categories.contains(category),
+            Timer { id, .. } => source.timers.get(id).is_none_or(|t| !t.disabled),
+        }
+    }
+
+    /*
+     * Sources and categories are just on or off, the timer's the one that
+     * needs to know what it'd be otherwise, so that going back to that
+     * leaves nothing behind to remember.
+     */
+    pub async fn set_scope_enabled(
+        &mut self,
+        scope: &TimerScope,
+        timer: Option<&TimerFile>,
+        enabled: bool,
+    ) {
+        let inherited = timer.map(|timer| self.timer_inherited(timer));
+        let key = scope.source_key().to_string();
+        let source = self.config.timer_sources.entry(key.clone()).or_default();
+        use TimerScope::*;
+        match scope {
+            Source(_) => source.disabled = !enabled,
+            Category { category, .. } => match enabled {
+                true => {
+                    source.disabled_categories.remove(category);
+                }
+                false => {
+                    source.disabled_categories.insert(category.clone());
+                }
+            },
+            Timer { id, .. } => match inherited == Some(enabled) {
+                true => {
+                    source.timers.remove(id);
+                }
+                false => {
+                    source
+                        .timers
+                        .insert(id.clone(), TimerSettings { disabled: !enabled });
+                }
+            },
+        }
+        if source.is_empty() {
+            self.config.timer_sources.remove(&key);
         }
         let _ = self.save(&self.addon_dir).await;
     }
-    pub async fn enable_timer(&mut self, timer: String) {
-        if let Some(entry_mut) = self.timers.get_mut(&timer) {
-            entry_mut.enable();
-        } else {
-            self.timers.insert(timer, TimerSettings::default());
+
+    /*
+     * Settings from before they were kept per source get copied to every
+     * loaded timer with that id, keeping whatever those did before. Ones
+     * for timers that aren't around right now stay where they are.
+     */
+    pub async fn adopt_unscoped_timers(&mut self, timers: &[Arc<TimerFile>]) {
+        if self.config.timers.is_empty() {
+            return;
+        }
+        let mut adopted = Vec::new();
+        for timer in timers {
+            let Some(setting) = self.config.timers.get(&timer.id).cloned() else {
+                continue;
+            };
+            self.config
+                .timer_sources
+                .entry(timer.source_key())
+                .or_default()
+                .timers
+                .insert(timer.id.clone(), setting);
+            adopted.push(timer.id.clone());
         }
+        if adopted.is_empty() {
+            return;
+        }
+        self.config.timers.retain(|id, _| !adopted.contains(id));
+        log::info!(
+            "Moved the settings for {} timers into their sources.",
+            adopted.len()
+        );
         let _ = self.save(&self.addon_dir).await;
     }
+    /*
+     * Marker sets used to be kept under a string built from their category,
+     * author, description and name. Once a set has a uuid, whatever was kept
+     * under that string moves over to it.
+     */
+    #[cfg(feature = "markers")]
+    pub async fn adopt_marker_uuids<'a>(&mut self, sets: impl Iterator<Item = &'a MarkerSet>) {
+        let mut adopted = 0;
+        for set in sets {
+            let Some(uuid) = set.uuid else {
+                continue;
+            };
+            let uuid = uuid.to_string();
+            if self.config.markers.contains_key(&uuid) {
+                continue;
+            }
+            if let Some(setting) = self.config.markers.remove(&set.legacy_id()) {
+                self.config.markers.insert(uuid, setting);
+                adopted += 1;
+            }
+        }
+        if adopted > 0 {
+            log::info!("Moved the settings for {adopted} marker sets onto their uuids.");
+            let _ = self.save(&self.addon_dir).await;
+        }
+    }
+
+    // an edit can change a set's id, its settings go with it
+    #[cfg(feature = "markers-edit")]
+    pub async fn rekey_marker(&mut self, from: &str, to: &str) {
+        if from == to {
+            return;
+        }
+        if let Some(setting) = self.config.markers.remove(from) {
+            self.config.markers.insert(to.to_string(), setting);
+            let _ = self.save(&self.addon_dir).await;
+        }
+    }
+
     pub async fn toggle_marker(&mut self, marker: String) -> bool {
-        let entry = self.markers.entry(marker.clone()).or_default();
+        let entry = self.config.markers.entry(marker.clone()).or_default();
         let new_state = entry.toggle();
         let _ = self.save(&self.addon_dir).await;
         new_state
     }
     pub async fn disable_marker(&mut self, marker: String) {
-        if let Some(entry_mut) = self.markers.get_mut(&marker) {
+        if let Some(entry_mut) = self.config.markers.get_mut(&marker) {
             entry_mut.disable();
         } else {
-            self.markers
+            self.config
+                .markers
                 .insert(marker, MarkerSettings { disabled: true });
         }
         let _ = self.save(&self.addon_dir).await;
     }
     pub async fn enable_marker(&mut self, marker: String) {
-        if let Some(entry_mut) = self.markers.get_mut(&marker) {
+        if let Some(entry_mut) = self.config.markers.get_mut(&marker) {
             entry_mut.enable();
         } else {
-            self.markers.insert(marker, MarkerSettings::default());
+            self.config
+                .markers
+                .insert(marker, MarkerSettings::default());
         }
         let _ = self.save(&self.addon_dir).await;
     }
 
-    #[allow(dead_code)]
     pub async fn get_status_for(&self, source: &RemoteSource) -> Option<&RemoteState> {
-        self.remotes.iter().find(|dd| *dd.source == *source)
+        self.state.remotes.iter().find(|dd| *dd.source == *source)
     }
 
     pub async fn get_status_for_mut(&mut self, source: &RemoteSource) -> Option<&mut RemoteState> {
-        self.remotes.iter_mut().find(|dd| *dd.source == *source)
+        self.state
+            .remotes
+            .iter_mut()
+            .find(|dd| *dd.source == *source)
     }
 
     pub async fn uninstall_remote(&mut self, source: &RemoteSource) -> anyhow::Result<()> {
-        if let Some(remote) = self.remotes.iter_mut().find(|dd| *dd.source == *source) {
+        if let Some(remote) = self
+            .state
+            .remotes
+            .iter_mut()
+            .find(|dd| *dd.source == *source)
+        {
             remote.uninstall().await?;
         }
         let _ = self.save(&self.addon_dir).await;
@@ -295,23 +457,31 @@ impl Settings {
         &mut self,
         maps: &MarkerAutoPlaceSettings,
     ) -> anyhow::Result<()> {
-        self.marker_autoplace = maps.clone();
+        self.config.marker_autoplace = maps.clone();
         let _ = self.save(&self.addon_dir).await;
         Ok(())
     }
 
     pub async fn download_latest(source: &RemoteSource) -> anyhow::Result<()> {
-        let underlying_source = source.source();
         let settings_arc = SETTINGS
             .get()
             .expect("SettingsLock should've been initialized by now!");
-        let install_dir = {
+        let (install_dir, channel) = {
             let settings_read_lock = settings_arc.read().await;
-            settings_read_lock
-                .addon_dir
-                .join(underlying_source.install_dir())
+            let remote = settings_read_lock
+                .get_status_for(source)
+                .await
+                .ok_or_else(|| anyhow!("Source \"{}\" not found.", source))?;
+            (
+                remote.install_path(&settings_read_lock.addon_dir),
+                remote.channel.clone(),
+            )
+        };
+        let tag_name = match source.owns_install() {
+            true => install_atomically(source, &install_dir, &channel).await?,
+            // not ours to stage and swap, it's used in place
+            false => source.download_latest(&install_dir, &channel).await?,
         };
-        let tag_name = underlying_source.download_latest().await?;
         {
             let mut settings_write_lock = settings_arc.write().await;
             if let Some(dd_mut) = settings_write_lock.get_status_for_mut(source).await {
@@ -321,50 +491,131 @@ impl Settings {
                     .await;
                 res
             } else {
-                Err(anyhow!("GitHub repository \"{}\" not found.", source))
+                Err(anyhow!("Source \"{}\" not found.", source))
             }
         }?;
         Ok(())
     }
 
+    pub async fn set_channel(&mut self, source: &RemoteSource, channel: Channel) {
+        if let Some(remote) = self.get_status_for_mut(source).await {
+            remote.set_channel(channel);
+        }
+        let _ = self.save(&self.addon_dir).await;
+    }
+
+    pub async fn roll_back(source: &RemoteSource) -> anyhow::Result<()> {
+        let settings_arc = SETTINGS
+            .get()
+            .expect("SettingsLock should've been initialized by now!");
+        let mut settings_write_lock = settings_arc.write().await;
+        if let Some(dd_mut) = settings_write_lock.get_status_for_mut(source).await {
+            dd_mut.roll_back().await?;
+        } else {
+            return Err(anyhow!("Source \"{}\" not found.", source));
+        }
+        settings_write_lock
+            .save(&settings_write_lock.addon_dir)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn record_timer_changes(&mut self, source: &RemoteSource, changes: TimerChanges) {
+        if let Some(remote) = self.get_status_for_mut(source).await {
+            if remote.kind == SourceKind::Timers {
+                log::info!(
+                    "Updating {} added {}, removed {} and changed {} timers.",
+                    source,
+                    changes.added.len(),
+                    changes.removed.len(),
+                    changes.changed.len(),
+                );
+                remote.changes = Some(TimerChanges {
+                    from: remote.previous_tag.clone(),
+                    to: remote.installed_tag.clone(),
+                    ..changes
+                });
+            }
+        }
+        let _ = self.save(&self.addon_dir).await;
+    }
+
+    pub async fn set_github_token(&mut self, token: Option<String>) {
+        self.config.github_token = token.filter(|t| !t.trim().is_empty());
+        GitHubSource::set_token(self.config.github_token.as_deref());
+        let _ = self.save(&self.addon_dir).await;
+    }
+
+    pub async fn set_update_settings(&mut self, updates: UpdateSettings) {
+        self.config.updates = updates;
+        let _ = self.save(&self.addon_dir).await;
+    }
+
+    // installed sources with something newer to install, as of the last check
+    pub fn updates_available(&self) -> Vec<Arc<RemoteSource>> {
+        use NeedsUpdate::*;
+        self.state
+            .remotes
+            .iter()
+            .filter(|r| r.installed_tag.is_some())
+            .filter(|r| matches!(r.needs_update, Known(true, _) | Pinned(true, _)))
+            .map(|r| r.source.clone())
+            .collect()
+    }
+
+    pub fn update_check_failed(&self) -> bool {
+        self.state.remotes.iter().any(|r| r.needs_update.is_error())
+    }
+
     pub async fn set_progress_bar(&mut self, style: ProgressBarStyleChange) -> ProgressBarSettings {
         use ProgressBarStyleChange::*;
         match style {
-            Centre(t) => self.progress_bar.set_centre_after(t),
-            Stock(t) => self.progress_bar.set_stock(t),
-            Shadow(t) => self.progress_bar.set_shadow(t),
-            Height(h) => self.progress_bar.set_height(h),
-            Font(f) => self.progress_bar.set_font(f),
+            Centre(t) => self.config.progress_bar.set_centre_after(t),
+            Stock(t) => self.config.progress_bar.set_stock(t),
+            Shadow(t) => self.config.progress_bar.set_shadow(t),
+            Height(h) => self.config.progress_bar.set_height(h),
+            Font(f) => self.config.progress_bar.set_font(f),
         }
         let _ = self.save(&self.addon_dir).await;
-        self.progress_bar.clone()
+        self.config.progress_bar.clone()
     }
 
     pub async fn toggle_katrender(&mut self) {
-        self.enable_katrender = !self.enable_katrender;
+        self.config.enable_katrender = !self.config.enable_katrender;
     }
 
     pub async fn check_for_updates() -> anyhow::Result<()> {
         let settings_arc = SETTINGS
             .get()
             .expect("SettingsLock should've been initialized by now!");
-        let sources: Vec<(Arc<RemoteSource>, NeedsUpdate)> = {
+        let sources: Vec<(Arc<RemoteSource>, NeedsUpdate, Option<ReleaseNotes>)> = {
             let settings_read_lock = settings_arc.read().await;
-            tokio_stream::iter(settings_read_lock.remotes.iter())
-                .then(|r| async move { (r.source.clone(), r.needs_update().await) })
+            tokio_stream::iter(settings_read_lock.state.remotes.iter())
+                .then(|r| async move {
+                    let nu = r.needs_update().await;
+                    let notes = match nu.is_error() {
+                        true => None,
+                        false => r.release_notes().await,
+                    };
+                    (r.source.clone(), nu, notes)
+                })
                 .collect()
                 .await
         };
         {
             let mut settings_write_lock = settings_arc.write().await;
-            for (source, nu) in sources {
+            for (source, nu, notes) in sources {
                 log::debug!("{} update state: {:?}", source, nu);
                 if let Some(dd) = settings_write_lock.get_status_for_mut(&source).await {
                     log::debug!("Found dd {} update state: {:?}", dd.source, nu);
+                    // a failed check says nothing about the notes we already had
+                    if !nu.is_error() {
+                        dd.release_notes = notes;
+                    }
                     dd.needs_update = nu;
                 }
             }
-            settings_write_lock.last_checked = Some(Utc::now());
+            settings_write_lock.state.last_checked = Some(Utc::now());
             settings_write_lock
                 .save(&settings_write_lock.addon_dir)
                 .await?;
@@ -374,29 +625,84 @@ impl Settings {
 
     pub async fn new(addon_dir: &Path) -> Self {
         Self {
-            last_checked: None,
             addon_dir: addon_dir.to_path_buf(),
-            timers: Default::default(),
-            markers: Default::default(),
-            remotes: RemoteState::suggested_sources().collect(),
-            progress_bar: Default::default(),
-            timers_window_open: false,
-            markers_window_open: false,
-            primary_window_open: false,
-            enable_katrender: false,
-            marker_autoplace: Default::default(),
+            config: Default::default(),
+            state: StateFile::stock(),
         }
     }
+
+    /*
+     * config.toml and state.toml, or an old settings.json split up into
+     * them. Anything that won't read is moved out of the way before the
+     * defaults are saved where it was.
+     */
     pub async fn load(addon_dir: &Path) -> anyhow::Result<Self> {
-        let settings_path = addon_dir.join("settings.json");
-        if try_exists(&settings_path).await? {
-            let file_data = read_to_string(settings_path).await?;
-            let mut settings = serde_json::from_str::<Self>(&file_data)?;
-            settings.addon_dir = addon_dir.to_path_buf();
-            settings.handle_sources_changes();
-            return Ok(settings);
+        let config_path = addon_dir.join(CONFIG_NAME);
+        let legacy_path = addon_dir.join(LEGACY_SETTINGS_NAME);
+        let migrating = !try_exists(&config_path).await? && try_exists(&legacy_path).await?;
+        let mut settings = match migrating {
+            true => match Self::migrate(addon_dir).await {
+                Ok(settings) => settings,
+                Err(err) => {
+                    Self::give_up_on(&legacy_path, err).await;
+                    Self::new(addon_dir).await
+                }
+            },
+            false => {
+                let config = match ConfigV2::load(addon_dir).await {
+                    Ok(config) => config.unwrap_or_default(),
+                    Err(err) => {
+                        Self::give_up_on(&config_path, err).await;
+                        Default::default()
+                    }
+                };
+                let state = match StateFile::load(addon_dir).await {
+                    Ok(state) => state.unwrap_or_else(StateFile::stock),
+                    Err(err) => {
+                        Self::give_up_on(&addon_dir.join(STATE_NAME), err).await;
+                        StateFile::stock()
+                    }
+                };
+                Self {
+                    addon_dir: addon_dir.to_path_buf(),
+                    config,
+                    state,
+                }
+            }
+        };
+        settings.handle_sources_changes();
+        GitHubSource::set_token(settings.config.github_token.as_deref());
+        Ok(settings)
+    }
+
+    async fn give_up_on(path: &Path, err: anyhow::Error) {
+        log::error!("Settings: Couldn't load {path:?}: {err}");
+        if let Some(aside) = set_aside(path).await {
+            log::warn!("Settings: Moved it to {aside:?}, starting from the defaults.");
         }
-        Ok(Self::new(addon_dir).await)
+    }
+
+    /*
+     * The old file's kept as settings.json.bak rather than deleted, for
+     * anyone going back to a version from before the split.
+     */
+    async fn migrate(addon_dir: &Path) -> anyhow::Result<Self> {
+        let legacy_path = addon_dir.join(LEGACY_SETTINGS_NAME);
+        let old = SettingsV1::from_json(&read_to_string(&legacy_path).await?)?;
+        let settings = Self {
+            addon_dir: addon_dir.to_path_buf(),
+            state: StateFile::from(&old),
+            config: ConfigV2::from(old),
+        };
+        settings.save(addon_dir).await?;
+        let mut backup = legacy_path.clone().into_os_string();
+        backup.push(".bak");
+        rename(&legacy_path, &backup).await?;
+        log::info!(
+            "Settings: Split {legacy_path:?} into {CONFIG_NAME} and {STATE_NAME}, keeping {} disabled timers.",
+            settings.count_disabled_timers(),
+        );
+        Ok(settings)
     }
 
     pub async fn load_default(addon_dir: &Path) -> Self {
@@ -414,12 +720,87 @@ impl Settings {
     }
 
     pub async fn save(&self, addon_dir: &Path) -> anyhow::Result<()> {
-        create_dir_all(addon_dir).await?;
-        let settings_path = addon_dir.join("settings.json");
-        log::debug!("Settings: Saving to \"{:?}\".", settings_path);
-        let settings_str = serde_json::to_string(self)?;
-        let mut file = File::create(settings_path).await?;
-        file.write_all(settings_str.as_bytes()).await?;
-        Ok(())
+        self.config.save(addon_dir).await?;
+        self.state.save(addon_dir).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/settings");
+
+    // a fresh addon dir with the fixture in it as its settings.json
+    fn addon_dir(fixture: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::copy(
+            Path::new(FIXTURES).join(fixture),
+            dir.path().join(LEGACY_SETTINGS_NAME),
+        )
+        .unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn migrates_a_v1_settings_file() {
+        let dir = addon_dir("v1-settings.json");
+        let settings = Settings::migrate(dir.path()).await.unwrap();
+        assert_eq!(settings.count_disabled_timers(), 3);
+        assert!(settings.config.timers["sabetha"].disabled);
+        assert!(!settings.config.timers["vale-guardian"].disabled);
+        assert!(settings.config.markers["Sabetha cannons"].disabled);
+        assert_eq!(settings.config.progress_bar.height, 30.0);
+        assert!(settings.config.enable_katrender);
+        assert_eq!(
+            settings.config.marker_autoplace,
+            MarkerAutoPlaceSettings::Place(SquadCondition::IfCommander)
+        );
+        assert!(settings.state.primary_window_open && settings.state.markers_window_open);
+        let remotes: Vec<_> = settings
+            .state
+            .remotes
+            .iter()
+            .map(|r| (r.source.to_string(), r.installed_tag.as_deref()))
+            .collect();
+        assert_eq!(
+            remotes,
+            [
+                ("kittywitch/Hero-Timers".to_string(), Some("v0.3.2")),
+                ("QuitarHero/Hero-Timers".to_string(), None),
+            ]
+        );
+
+        // what got written reads back the same, and the old file's kept around
+        assert!(dir.path().join("settings.json.bak").exists());
+        assert!(!dir.path().join(LEGACY_SETTINGS_NAME).exists());
+        let config = ConfigV2::load(dir.path()).await.unwrap().unwrap();
+        assert_eq!(config.timers, settings.config.timers);
+        assert_eq!(config.markers, settings.config.markers);
+        let state = StateFile::load(dir.path()).await.unwrap().unwrap();
+        assert_eq!(state.remotes, settings.state.remotes);
+    }
+
+    #[tokio::test]
+    async fn damaged_settings_keep_their_disabled_timers() {
+        let dir = addon_dir("v1-settings-damaged.json");
+        let settings = Settings::migrate(dir.path()).await.unwrap();
+        assert_eq!(settings.count_disabled_timers(), 2);
+        assert_eq!(settings.state.last_checked, None);
+        assert_eq!(settings.config.progress_bar.height, 24.0);
+        assert_eq!(settings.config.marker_autoplace, Default::default());
+        // the source that isn't one we ever had is the only one lost
+        assert_eq!(settings.state.remotes.len(), 1);
+        assert_eq!(
+            settings.state.remotes[0].installed_tag.as_deref(),
+            Some("v2.0.0")
+        );
+    }
+
+    #[test]
+    fn only_unreadable_json_is_given_up_on() {
+        assert!(SettingsV1::from_json("{ \"timers\": ").is_err());
+        assert!(SettingsV1::from_json("[]").is_err());
+        assert!(SettingsV1::from_json("{}").is_ok());
     }
 }
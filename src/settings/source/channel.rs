@@ -0,0 +1,73 @@
+use {
+    serde::{Deserialize, Serialize},
+    std::fmt,
+};
+
+/*
+ * Which version of a source to follow. Pins never move, so checking them
+ * for updates doesn't need the network.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[serde(tag = "type", content = "ref", rename_all = "camelCase")]
+pub enum Channel {
+    // the newest full release
+    #[default]
+    Latest,
+    // the newest release, prereleases included
+    Prerelease,
+    Tag(String),
+    Commit(String),
+    // whatever the branch's head is, no releases involved
+    Branch(String),
+}
+
+impl Channel {
+    // one of each, sharing whatever ref they need
+    pub fn variants(git_ref: &str) -> [Self; 5] {
+        use Channel::*;
+        [
+            Latest,
+            Prerelease,
+            Tag(git_ref.to_string()),
+            Commit(git_ref.to_string()),
+            Branch(git_ref.to_string()),
+        ]
+    }
+
+    pub fn git_ref(&self) -> Option<&str> {
+        use Channel::*;
+        match self {
+            Tag(r) | Commit(r) | Branch(r) => Some(r),
+            Latest | Prerelease => None,
+        }
+    }
+
+    pub fn git_ref_mut(&mut self) -> Option<&mut String> {
+        use Channel::*;
+        match self {
+            Tag(r) | Commit(r) | Branch(r) => Some(r),
+            Latest | Prerelease => None,
+        }
+    }
+
+    pub fn pin(&self) -> Option<&str> {
+        use Channel::*;
+        match self {
+            Tag(r) | Commit(r) => Some(r),
+            Latest | Prerelease | Branch(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Channel::*;
+        match self {
+            Latest => write!(f, "latest release"),
+            Prerelease => write!(f, "latest prerelease"),
+            Tag(tag) => write!(f, "tag {tag}"),
+            Commit(commit) => write!(f, "commit {commit}"),
+            Branch(branch) => write!(f, "branch {branch}"),
+        }
+    }
+}
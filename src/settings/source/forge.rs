@@ -0,0 +1,96 @@
+use {
+    super::{Channel, ReleaseNotes, Source},
+    anyhow::anyhow,
+    serde::de::DeserializeOwned,
+    std::path::Path,
+    tokio::fs::create_dir_all,
+    url::Url,
+};
+
+// the bits of a release every forge gives us, whatever it calls them
+pub(super) trait ForgeRelease {
+    fn tag(&self) -> &str;
+    // the source tarball the release points at, if it points at one at all
+    fn tarball(&self) -> Option<Url>;
+    fn into_notes(self) -> ReleaseNotes;
+}
+
+/*
+ * GitHub, Gitea and GitLab only really differ in where things live in their
+ * APIs and what they call them, so the channel handling lives here once and
+ * they fill in the lookups.
+ */
+pub(super) trait Forge: Source {
+    type Release: ForgeRelease + DeserializeOwned;
+
+    fn ref_tarball(&self, git_ref: &str) -> anyhow::Result<Url>;
+    async fn latest_release(&self) -> anyhow::Result<Self::Release>;
+    // newest of any release, prereleases included, or none if there's none at all
+    async fn newest_release(&self) -> anyhow::Result<Option<Self::Release>>;
+    async fn release_by_tag(&self, tag: &str) -> anyhow::Result<Self::Release>;
+    // the commit id the branch is on
+    async fn branch_head(&self, branch: &str) -> anyhow::Result<String>;
+
+    async fn get_json<T: DeserializeOwned>(url: Url) -> anyhow::Result<T> {
+        let json_data = Self::get_text(url).await?;
+        Ok(serde_json::from_str::<T>(&json_data)?)
+    }
+
+    fn release_tarball(&self, release: Self::Release) -> anyhow::Result<(String, Url)> {
+        let tarball = match release.tarball() {
+            Some(tarball) => tarball,
+            None => self.ref_tarball(release.tag())?,
+        };
+        Ok((release.tag().to_string(), tarball))
+    }
+
+    // the version the channel is on right now, and where to get it from
+    async fn resolve(&self, channel: &Channel) -> anyhow::Result<(String, Url)> {
+        use Channel::*;
+        match channel {
+            Latest => self.release_tarball(self.latest_release().await?),
+            Prerelease => {
+                let release = self
+                    .newest_release()
+                    .await?
+                    .ok_or_else(|| anyhow!("{self} has no releases"))?;
+                self.release_tarball(release)
+            }
+            Tag(git_ref) | Commit(git_ref) => Ok((git_ref.clone(), self.ref_tarball(git_ref)?)),
+            Branch(branch) => {
+                let commit = self.branch_head(branch).await?;
+                let tarball = self.ref_tarball(&commit)?;
+                Ok((commit, tarball))
+            }
+        }
+    }
+
+    async fn download_channel(
+        &self,
+        install_dir: &Path,
+        channel: &Channel,
+    ) -> anyhow::Result<String> {
+        create_dir_all(install_dir).await?;
+        let (id, tarball_url) = self.resolve(channel).await?;
+        Self::get_and_extract_tar(install_dir, tarball_url).await?;
+        Ok(id)
+    }
+
+    async fn channel_notes(&self, channel: &Channel) -> anyhow::Result<Option<ReleaseNotes>> {
+        use Channel::*;
+        let release = match channel {
+            Latest => self.latest_release().await?,
+            Prerelease => match self.newest_release().await? {
+                Some(release) => release,
+                None => return Ok(None),
+            },
+            // plenty of tags never got a release made for them
+            Tag(tag) => match self.release_by_tag(tag).await {
+                Ok(release) => release,
+                Err(_) => return Ok(None),
+            },
+            Commit(_) | Branch(_) => return Ok(None),
+        };
+        Ok(Some(release.into_notes()))
+    }
+}
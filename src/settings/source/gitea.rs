@@ -0,0 +1,187 @@
+use {
+    super::{
+        dir_name,
+        forge::{Forge, ForgeRelease},
+        Channel, ReleaseNotes,
+    },
+    crate::settings::Source,
+    anyhow::anyhow,
+    chrono::{DateTime, Utc},
+    serde::{Deserialize, Serialize},
+    std::{fmt, path::Path},
+    url::Url,
+};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GiteaLatestRelease {
+    tag_name: String,
+    tarball_url: Option<Url>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    published_at: Option<DateTime<Utc>>,
+}
+
+impl ForgeRelease for GiteaLatestRelease {
+    fn tag(&self) -> &str {
+        &self.tag_name
+    }
+    fn tarball(&self) -> Option<Url> {
+        self.tarball_url.clone()
+    }
+    fn into_notes(self) -> ReleaseNotes {
+        ReleaseNotes {
+            version: self.tag_name,
+            name: self.name,
+            body: self.body,
+            published_at: self.published_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GiteaCommit {
+    id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GiteaBranch {
+    commit: GiteaCommit,
+}
+
+/*
+ * Gitea, and everything forked from it (Forgejo, and so Codeberg), share
+ * the same release API, so one source covers the lot.
+ */
+#[derive(Deserialize, Serialize, Debug, Hash, Eq, Clone, PartialEq)]
+pub struct GiteaSource {
+    #[serde(default = "GiteaSource::default_instance")]
+    pub instance: Url,
+    pub owner: String,
+    pub repository: String,
+    pub description: Option<String>,
+}
+
+impl fmt::Display for GiteaSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.instance.host_str() {
+            Some(host) => write!(f, "{}/{}/{}", host, self.owner, self.repository),
+            None => write!(f, "{}/{}", self.owner, self.repository),
+        }
+    }
+}
+
+impl GiteaSource {
+    fn default_instance() -> Url {
+        Url::parse("https://codeberg.org").expect("that's a url")
+    }
+
+    fn api_url(&self, rest: &[&str]) -> anyhow::Result<Url> {
+        let mut url = self.instance.clone();
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("Gitea instance {} can't be a base URL", self.instance))?
+            .pop_if_empty()
+            .extend(["api", "v1", "repos", &self.owner, &self.repository])
+            .extend(rest);
+        Ok(url)
+    }
+}
+
+impl Forge for GiteaSource {
+    type Release = GiteaLatestRelease;
+
+    fn ref_tarball(&self, git_ref: &str) -> anyhow::Result<Url> {
+        let archive = format!("{git_ref}.tar.gz");
+        self.api_url(&["archive", &archive])
+    }
+
+    async fn latest_release(&self) -> anyhow::Result<GiteaLatestRelease> {
+        Self::get_json(self.api_url(&["releases", "latest"])?).await
+    }
+
+    // newest first, and unlike releases/latest this doesn't skip prereleases
+    async fn newest_release(&self) -> anyhow::Result<Option<GiteaLatestRelease>> {
+        let mut url = self.api_url(&["releases"])?;
+        url.query_pairs_mut().append_pair("limit", "20");
+        let releases: Vec<GiteaLatestRelease> = Self::get_json(url).await?;
+        Ok(releases.into_iter().find(|r| !r.draft))
+    }
+
+    async fn release_by_tag(&self, tag: &str) -> anyhow::Result<GiteaLatestRelease> {
+        Self::get_json(self.api_url(&["releases", "tags", tag])?).await
+    }
+
+    async fn branch_head(&self, branch: &str) -> anyhow::Result<String> {
+        let branch: GiteaBranch = Self::get_json(self.api_url(&["branches", branch])?).await?;
+        Ok(branch.commit.id)
+    }
+}
+
+impl Source for GiteaSource {
+    fn install_dir(&self) -> String {
+        dir_name(&[
+            "gitea",
+            self.instance.host_str().unwrap_or_default(),
+            &self.owner,
+            &self.repository,
+        ])
+    }
+    fn view_url(&self) -> String {
+        let mut url = self.instance.clone();
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments
+                .pop_if_empty()
+                .extend([&self.owner, &self.repository]);
+        }
+        url.to_string()
+    }
+    async fn download_latest(
+        &self,
+        install_dir: &Path,
+        channel: &Channel,
+    ) -> anyhow::Result<String> {
+        self.download_channel(install_dir, channel).await
+    }
+
+    async fn latest_id(&self, channel: &Channel) -> anyhow::Result<String> {
+        Ok(self.resolve(channel).await?.0)
+    }
+
+    async fn release_notes(&self, channel: &Channel) -> anyhow::Result<Option<ReleaseNotes>> {
+        self.channel_notes(channel).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls() {
+        let gitea = GiteaSource {
+            instance: Url::parse("https://git.example.com/forge/").unwrap(),
+            owner: "kittywitch".to_string(),
+            repository: "hud-timers".to_string(),
+            description: None,
+        };
+        assert_eq!(
+            gitea.ref_tarball("v1.0").unwrap().as_str(),
+            "https://git.example.com/forge/api/v1/repos/kittywitch/hud-timers/archive/v1.0.tar.gz"
+        );
+        assert_eq!(
+            gitea
+                .api_url(&["releases", "tags", "v1.0"])
+                .unwrap()
+                .as_str(),
+            "https://git.example.com/forge/api/v1/repos/kittywitch/hud-timers/releases/tags/v1.0"
+        );
+        assert_eq!(
+            gitea.view_url(),
+            "https://git.example.com/forge/kittywitch/hud-timers"
+        );
+    }
+}
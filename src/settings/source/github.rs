@@ -1,14 +1,21 @@
 use {
+    super::{
+        forge::{Forge, ForgeRelease},
+        Channel, ReleaseNotes,
+    },
     crate::settings::Source,
+    anyhow::anyhow,
     chrono::{DateTime, Utc},
-    nexus::paths::get_addon_dir,
+    reqwest::RequestBuilder,
     serde::{Deserialize, Serialize},
     serde_json::Value,
-    std::fmt,
-    tokio::fs::create_dir_all,
+    std::{fmt, path::Path, sync::RwLock},
     url::Url,
 };
 
+// a personal access token from the settings, for the much higher rate limit
+static TOKEN: RwLock<Option<String>> = RwLock::new(None);
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GitHubLatestRelease {
     url: Url,
@@ -34,8 +41,38 @@ pub struct GitHubLatestRelease {
     assets: Value,
 }
 
+impl ForgeRelease for GitHubLatestRelease {
+    fn tag(&self) -> &str {
+        &self.tag_name
+    }
+    fn tarball(&self) -> Option<Url> {
+        self.tarball_url.clone()
+    }
+    fn into_notes(self) -> ReleaseNotes {
+        ReleaseNotes {
+            version: self.tag_name,
+            name: self.name,
+            body: self.body,
+            published_at: Some(self.published_at),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GitHubCommit {
+    sha: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GitHubBranch {
+    commit: GitHubCommit,
+}
+
 #[derive(Deserialize, Serialize, Debug, Hash, Eq, Clone, PartialEq)]
 pub struct GitHubSource {
+    // only worth changing for GitHub Enterprise, or to point at a mock server
+    #[serde(default = "GitHubSource::default_api")]
+    pub api: Url,
     pub owner: String,
     pub repository: String,
     pub description: Option<String>,
@@ -52,15 +89,52 @@ impl GitHubSource {
         format!("{}", self)
     }
 
-    pub async fn latest_release(&self) -> anyhow::Result<GitHubLatestRelease> {
-        let url = format!(
-            "https://api.github.com/repos/{}/releases/latest",
-            self.repo_string()
-        );
-        let response = Self::get(url).await?;
-        let json_data = response.text().await?;
-        let data = serde_json::from_str::<GitHubLatestRelease>(&json_data)?;
-        Ok(data)
+    pub fn set_token(token: Option<&str>) {
+        let token = token.map(str::trim).filter(|t| !t.is_empty());
+        *TOKEN.write().unwrap() = token.map(str::to_string);
+    }
+
+    pub fn default_api() -> Url {
+        Url::parse("https://api.github.com").expect("that's a url")
+    }
+
+    fn api_url(&self, rest: &[&str]) -> anyhow::Result<Url> {
+        let mut url = self.api.clone();
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("GitHub API {} can't be a base URL", self.api))?
+            .pop_if_empty()
+            .extend(["repos", &self.owner, &self.repository])
+            .extend(rest);
+        Ok(url)
+    }
+}
+
+impl Forge for GitHubSource {
+    type Release = GitHubLatestRelease;
+
+    fn ref_tarball(&self, git_ref: &str) -> anyhow::Result<Url> {
+        self.api_url(&["tarball", git_ref])
+    }
+
+    async fn latest_release(&self) -> anyhow::Result<GitHubLatestRelease> {
+        Self::get_json(self.api_url(&["releases", "latest"])?).await
+    }
+
+    // newest first, drafts included if you can see them, which we can't
+    async fn newest_release(&self) -> anyhow::Result<Option<GitHubLatestRelease>> {
+        let mut url = self.api_url(&["releases"])?;
+        url.query_pairs_mut().append_pair("per_page", "20");
+        let releases: Vec<GitHubLatestRelease> = Self::get_json(url).await?;
+        Ok(releases.into_iter().find(|r| !r.draft))
+    }
+
+    async fn release_by_tag(&self, tag: &str) -> anyhow::Result<GitHubLatestRelease> {
+        Self::get_json(self.api_url(&["releases", "tags", tag])?).await
+    }
+
+    async fn branch_head(&self, branch: &str) -> anyhow::Result<String> {
+        let branch: GitHubBranch = Self::get_json(self.api_url(&["branches", branch])?).await?;
+        Ok(branch.commit.sha)
     }
 }
 
@@ -69,21 +143,90 @@ impl Source for GitHubSource {
         format!("{}_{}", self.owner, self.repository)
     }
     fn view_url(&self) -> String {
-        format!("https://github.com/{}", self.repo_string())
-    }
-    async fn download_latest(&self) -> anyhow::Result<String> {
-        let addon_dir = get_addon_dir("Taimi").expect("Invalid addon dir");
-        let install_dir = addon_dir.join(self.install_dir());
-        create_dir_all(&install_dir).await?;
-        let latest = self.latest_release().await?;
-        if let Some(tarball_url) = latest.tarball_url {
-            Self::get_and_extract_tar(&install_dir, tarball_url).await?;
+        // api.github.com for github.com itself, <host>/api/v3 for Enterprise Server
+        let mut url = self.api.clone();
+        if let Some(host) = self.api.host_str().and_then(|h| h.strip_prefix("api.")) {
+            let _ = url.set_host(Some(host));
+        }
+        let mut path: Vec<&str> = self
+            .api
+            .path_segments()
+            .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+        if path.ends_with(&["api", "v3"]) {
+            path.truncate(path.len() - 2);
+        }
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments
+                .clear()
+                .extend(path)
+                .extend([&self.owner, &self.repository]);
         }
-        Ok(latest.tag_name)
+        url.to_string()
+    }
+    fn authorize(request: RequestBuilder) -> RequestBuilder {
+        match TOKEN.read().unwrap().as_deref() {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+    async fn download_latest(
+        &self,
+        install_dir: &Path,
+        channel: &Channel,
+    ) -> anyhow::Result<String> {
+        self.download_channel(install_dir, channel).await
     }
 
-    async fn latest_id(&self) -> anyhow::Result<String> {
-        let release = self.latest_release().await?;
-        Ok(release.tag_name)
+    async fn latest_id(&self, channel: &Channel) -> anyhow::Result<String> {
+        Ok(self.resolve(channel).await?.0)
+    }
+
+    async fn release_notes(&self, channel: &Channel) -> anyhow::Result<Option<ReleaseNotes>> {
+        self.channel_notes(channel).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(api: &str) -> GitHubSource {
+        GitHubSource {
+            api: Url::parse(api).unwrap(),
+            owner: "kittywitch".to_string(),
+            repository: "hud-timers".to_string(),
+            description: None,
+        }
+    }
+
+    #[test]
+    fn urls() {
+        let github = source("https://api.github.com");
+        assert_eq!(
+            github.ref_tarball("v1.0").unwrap().as_str(),
+            "https://api.github.com/repos/kittywitch/hud-timers/tarball/v1.0"
+        );
+        assert_eq!(
+            github
+                .api_url(&["releases", "tags", "a/b"])
+                .unwrap()
+                .as_str(),
+            "https://api.github.com/repos/kittywitch/hud-timers/releases/tags/a%2Fb"
+        );
+        assert_eq!(
+            github.view_url(),
+            "https://github.com/kittywitch/hud-timers"
+        );
+
+        let enterprise = source("https://git.example.com/api/v3/");
+        assert_eq!(
+            enterprise.ref_tarball("main").unwrap().as_str(),
+            "https://git.example.com/api/v3/repos/kittywitch/hud-timers/tarball/main"
+        );
+        assert_eq!(
+            enterprise.view_url(),
+            "https://git.example.com/kittywitch/hud-timers"
+        );
     }
 }
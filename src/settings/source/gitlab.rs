@@ -0,0 +1,194 @@
+use {
+    super::{
+        dir_name,
+        forge::{Forge, ForgeRelease},
+        Channel, ReleaseNotes,
+    },
+    crate::settings::Source,
+    anyhow::anyhow,
+    chrono::{DateTime, Utc},
+    serde::{Deserialize, Serialize},
+    std::{fmt, path::Path},
+    url::Url,
+};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GitLabReleaseSource {
+    format: String,
+    url: Url,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GitLabReleaseAssets {
+    #[serde(default)]
+    sources: Vec<GitLabReleaseSource>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GitLabLatestRelease {
+    tag_name: String,
+    assets: GitLabReleaseAssets,
+    #[serde(default)]
+    name: Option<String>,
+    // markdown, same as everyone else's body
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    released_at: Option<DateTime<Utc>>,
+}
+
+impl ForgeRelease for GitLabLatestRelease {
+    fn tag(&self) -> &str {
+        &self.tag_name
+    }
+    fn tarball(&self) -> Option<Url> {
+        self.assets
+            .sources
+            .iter()
+            .find(|s| s.format == "tar.gz")
+            .map(|s| s.url.clone())
+    }
+    fn into_notes(self) -> ReleaseNotes {
+        ReleaseNotes {
+            version: self.tag_name,
+            name: self.name,
+            body: self.description,
+            published_at: self.released_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GitLabCommit {
+    id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GitLabBranch {
+    commit: GitLabCommit,
+}
+
+#[derive(Deserialize, Serialize, Debug, Hash, Eq, Clone, PartialEq)]
+pub struct GitLabSource {
+    #[serde(default = "GitLabSource::default_instance")]
+    pub instance: Url,
+    // the full path, e.g. "group/subgroup/project"
+    pub project: String,
+    pub description: Option<String>,
+}
+
+impl fmt::Display for GitLabSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.instance.host_str() {
+            Some("gitlab.com") | None => write!(f, "{}", self.project),
+            Some(host) => write!(f, "{}/{}", host, self.project),
+        }
+    }
+}
+
+impl GitLabSource {
+    fn default_instance() -> Url {
+        Url::parse("https://gitlab.com").expect("that's a url")
+    }
+
+    fn api_url(&self, rest: &[&str]) -> anyhow::Result<Url> {
+        let mut url = self.instance.clone();
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("GitLab instance {} can't be a base URL", self.instance))?
+            .pop_if_empty()
+            // the project path is a single, escaped, segment as far as the api cares
+            .extend(["api", "v4", "projects", &self.project])
+            .extend(rest);
+        Ok(url)
+    }
+}
+
+impl Forge for GitLabSource {
+    type Release = GitLabLatestRelease;
+
+    fn ref_tarball(&self, git_ref: &str) -> anyhow::Result<Url> {
+        let mut url = self.api_url(&["repository", "archive.tar.gz"])?;
+        url.query_pairs_mut().append_pair("sha", git_ref);
+        Ok(url)
+    }
+
+    async fn latest_release(&self) -> anyhow::Result<GitLabLatestRelease> {
+        Self::get_json(self.api_url(&["releases", "permalink", "latest"])?).await
+    }
+
+    // GitLab doesn't have prereleases as such, so this is just the newest of any release
+    async fn newest_release(&self) -> anyhow::Result<Option<GitLabLatestRelease>> {
+        let mut url = self.api_url(&["releases"])?;
+        url.query_pairs_mut()
+            .append_pair("order_by", "released_at")
+            .append_pair("per_page", "1");
+        let releases: Vec<GitLabLatestRelease> = Self::get_json(url).await?;
+        Ok(releases.into_iter().next())
+    }
+
+    async fn release_by_tag(&self, tag: &str) -> anyhow::Result<GitLabLatestRelease> {
+        Self::get_json(self.api_url(&["releases", tag])?).await
+    }
+
+    async fn branch_head(&self, branch: &str) -> anyhow::Result<String> {
+        let url = self.api_url(&["repository", "branches", branch])?;
+        let branch: GitLabBranch = Self::get_json(url).await?;
+        Ok(branch.commit.id)
+    }
+}
+
+impl Source for GitLabSource {
+    fn install_dir(&self) -> String {
+        dir_name(&[
+            "gitlab",
+            self.instance.host_str().unwrap_or_default(),
+            &self.project,
+        ])
+    }
+    fn view_url(&self) -> String {
+        let mut url = self.instance.clone();
+        url.set_path(&self.project);
+        url.to_string()
+    }
+    async fn download_latest(
+        &self,
+        install_dir: &Path,
+        channel: &Channel,
+    ) -> anyhow::Result<String> {
+        self.download_channel(install_dir, channel).await
+    }
+
+    async fn latest_id(&self, channel: &Channel) -> anyhow::Result<String> {
+        Ok(self.resolve(channel).await?.0)
+    }
+
+    async fn release_notes(&self, channel: &Channel) -> anyhow::Result<Option<ReleaseNotes>> {
+        self.channel_notes(channel).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls() {
+        let gitlab = GitLabSource {
+            instance: GitLabSource::default_instance(),
+            project: "kittywitch/timers/hud".to_string(),
+            description: None,
+        };
+        assert_eq!(
+            gitlab.ref_tarball("v1.0").unwrap().as_str(),
+            "https://gitlab.com/api/v4/projects/kittywitch%2Ftimers%2Fhud/repository/archive.tar.gz?sha=v1.0"
+        );
+        assert_eq!(
+            gitlab.api_url(&["releases", "permalink", "latest"]).unwrap().as_str(),
+            "https://gitlab.com/api/v4/projects/kittywitch%2Ftimers%2Fhud/releases/permalink/latest"
+        );
+        assert_eq!(
+            gitlab.view_url(),
+            "https://gitlab.com/kittywitch/timers/hud"
+        );
+    }
+}
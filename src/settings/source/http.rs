@@ -0,0 +1,94 @@
+use {
+    super::{dir_name, Channel},
+    crate::settings::Source,
+    anyhow::anyhow,
+    reqwest::{
+        header::{CONTENT_TYPE, ETAG, LAST_MODIFIED},
+        Response,
+    },
+    serde::{Deserialize, Serialize},
+    std::{fmt, path::Path},
+    tokio::fs::create_dir_all,
+    url::Url,
+};
+
+/*
+ * A tarball or zip sitting at a fixed URL. There's no release to ask about,
+ * so the server's ETag, or failing that Last-Modified, stands in for a tag.
+ */
+#[derive(Deserialize, Serialize, Debug, Hash, Eq, Clone, PartialEq)]
+pub struct HttpSource {
+    pub url: Url,
+    pub description: Option<String>,
+}
+
+impl fmt::Display for HttpSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.url)
+    }
+}
+
+impl HttpSource {
+    fn version(response: &Response) -> anyhow::Result<String> {
+        let headers = response.headers();
+        let header = headers
+            .get(ETAG)
+            .or_else(|| headers.get(LAST_MODIFIED))
+            .ok_or_else(|| anyhow!("No ETag or Last-Modified header to version it by"))?;
+        Ok(header.to_str()?.trim_matches('"').to_string())
+    }
+
+    fn is_zip(&self, response: &Response) -> bool {
+        let content_type_zip = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|ct| ct.to_str().ok())
+            .is_some_and(|ct| ct.contains("zip") && !ct.contains("gzip"));
+        // .taco pathing packs are zips with a different hat on
+        let path = self.url.path();
+        path.ends_with(".zip") || path.ends_with(".taco") || content_type_zip
+    }
+}
+
+impl Source for HttpSource {
+    fn install_dir(&self) -> String {
+        dir_name(&[
+            "http",
+            self.url.host_str().unwrap_or_default(),
+            self.url.path(),
+        ])
+    }
+    fn view_url(&self) -> String {
+        self.url.to_string()
+    }
+    fn supports_channels(&self) -> bool {
+        false
+    }
+    async fn download_latest(
+        &self,
+        install_dir: &Path,
+        _channel: &Channel,
+    ) -> anyhow::Result<String> {
+        create_dir_all(install_dir).await?;
+        log::debug!(
+            "Beginning to fetch and extract into {install_dir:?} from {}",
+            self.url
+        );
+        let response = Self::get(self.url.clone()).await?;
+        let version = Self::version(&response)?;
+        match self.is_zip(&response) {
+            true => Self::extract_zip(install_dir, response).await?,
+            false => Self::extract_tar(install_dir, response).await?,
+        }
+        log::debug!(
+            "Completed fetching and extracting into {install_dir:?} from {}",
+            self.url
+        );
+        Ok(version)
+    }
+
+    async fn latest_id(&self, _channel: &Channel) -> anyhow::Result<String> {
+        let response = Self::head(self.url.clone()).await?;
+        Self::version(&response)
+    }
+}
@@ -0,0 +1,320 @@
+use {
+    super::{Channel, Source},
+    anyhow::{anyhow, bail},
+    std::path::{Path, PathBuf},
+    tokio::fs::{create_dir_all, read_dir, remove_dir_all, rename, try_exists},
+};
+
+/*
+ * Installs happen in three steps, so that a failed download never costs
+ * anyone the timers they already had:
+ *
+ * 1. the release is fetched into a staging dir next to the install dir,
+ * 2. the staging dir is checked to have actually got something in it,
+ * 3. the old install is moved aside to `<install>.previous` and the staging
+ *    dir renamed into its place.
+ *
+ * Windows won't rename over a directory, so 3 is two renames rather than
+ * one; if the second fails the first is undone.
+ */
+pub fn previous_install_path(install_dir: &Path) -> PathBuf {
+    let mut name = install_dir.file_name().unwrap_or_default().to_os_string();
+    name.push(".previous");
+    install_dir.with_file_name(name)
+}
+
+pub async fn install_atomically<S: Source>(
+    source: &S,
+    install_dir: &Path,
+    channel: &Channel,
+) -> anyhow::Result<String> {
+    let parent = install_dir
+        .parent()
+        .ok_or_else(|| anyhow!("{install_dir:?} has nowhere to stage into"))?;
+    create_dir_all(parent).await?;
+    // same parent, so the renames don't have to cross filesystems
+    let staging = tempfile::Builder::new()
+        .prefix(".taimi-staging-")
+        .tempdir_in(parent)?;
+    log::debug!("Staging {source} into {:?}.", staging.path());
+    let version = source.download_latest(staging.path(), channel).await?;
+    if read_dir(staging.path())
+        .await?
+        .next_entry()
+        .await?
+        .is_none()
+    {
+        bail!("{source} {version} didn't contain anything, keeping the current install");
+    }
+
+    let previous = previous_install_path(install_dir);
+    if try_exists(&previous).await? {
+        remove_dir_all(&previous).await?;
+    }
+    let had_install = try_exists(install_dir).await?;
+    if had_install {
+        rename(install_dir, &previous).await?;
+    }
+    let staging = staging.into_path();
+    if let Err(err) = rename(&staging, install_dir).await {
+        let _ = remove_dir_all(&staging).await;
+        if had_install {
+            rename(&previous, install_dir).await?;
+        }
+        bail!("Couldn't move {source} {version} into place: {err}");
+    }
+    log::info!("Installed {source} {version} into {install_dir:?}.");
+    Ok(version)
+}
+
+// swaps the install and the previous install around
+pub async fn roll_back(install_dir: &Path) -> anyhow::Result<()> {
+    let previous = previous_install_path(install_dir);
+    if !try_exists(&previous).await? {
+        bail!("There's no previous install of {install_dir:?} to roll back to");
+    }
+    let mut swap_name = install_dir.file_name().unwrap_or_default().to_os_string();
+    swap_name.push(".rollback");
+    let swap = install_dir.with_file_name(swap_name);
+    if try_exists(&swap).await? {
+        remove_dir_all(&swap).await?;
+    }
+    let had_install = try_exists(install_dir).await?;
+    if had_install {
+        rename(install_dir, &swap).await?;
+    }
+    if let Err(err) = rename(&previous, install_dir).await {
+        if had_install {
+            rename(&swap, install_dir).await?;
+        }
+        bail!("Couldn't roll back {install_dir:?}: {err}");
+    }
+    if had_install {
+        rename(&swap, &previous).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{super::ARCHIVE_SIZE_LIMIT, *},
+        crate::settings::HttpSource,
+        async_compression::tokio::bufread::GzipEncoder,
+        std::{
+            fs,
+            io::{Cursor, Write},
+        },
+        tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpListener,
+        },
+        tokio_tar::{EntryType, Header},
+        url::Url,
+        zip::{write::SimpleFileOptions, ZipWriter},
+    };
+
+    const TOO_BIG: u64 = ARCHIVE_SIZE_LIMIT as u64 + 1;
+
+    // serves the same archive to every request, claiming whatever length it's told to
+    async fn serve(name: &str, body: Vec<u8>, claimed_length: Option<u64>) -> HttpSource {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let length = claimed_length.unwrap_or(body.len() as u64);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request).await;
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\netag: \"v2\"\r\ncontent-length: {length}\r\nconnection: close\r\n\r\n"
+                );
+                let _ = stream.write_all(head.as_bytes()).await;
+                let _ = stream.write_all(&body).await;
+            }
+        });
+        HttpSource {
+            url: Url::parse(&format!("http://{addr}/{name}")).unwrap(),
+            description: None,
+        }
+    }
+
+    // by hand, so that paths go in exactly as given, however bad
+    fn header(path: &str, size: u64) -> Header {
+        let mut header = Header::new_gnu();
+        header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+        header.set_size(size);
+        header.set_mode(0o644);
+        header.set_uid(0);
+        header.set_gid(0);
+        header.set_mtime(0);
+        header.set_entry_type(EntryType::Regular);
+        header.set_cksum();
+        header
+    }
+
+    async fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut gzipped = Vec::new();
+        GzipEncoder::new(data)
+            .read_to_end(&mut gzipped)
+            .await
+            .unwrap();
+        gzipped
+    }
+
+    async fn tarball(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut tar = Vec::new();
+        for (path, contents) in files {
+            tar.extend_from_slice(header(path, contents.len() as u64).as_bytes());
+            tar.extend_from_slice(contents.as_bytes());
+            tar.resize(tar.len().next_multiple_of(512), 0);
+        }
+        tar.resize(tar.len() + 1024, 0);
+        gzip(&tar).await
+    }
+
+    fn zipball(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, contents) in files {
+            zip.start_file(*path, SimpleFileOptions::default()).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    // an install dir with something already in it
+    fn installed(root: &tempfile::TempDir) -> PathBuf {
+        let install = root.path().join("timers");
+        fs::create_dir_all(&install).unwrap();
+        fs::write(install.join("old.bhtimer"), "old").unwrap();
+        install
+    }
+
+    fn listing(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    // no staging dir left about, and the old install where it was
+    fn assert_untouched(root: &tempfile::TempDir, install: &Path) {
+        assert_eq!(listing(root.path()), ["timers"]);
+        assert_eq!(listing(install), ["old.bhtimer"]);
+    }
+
+    #[tokio::test]
+    async fn installs_and_keeps_the_previous() {
+        let root = tempfile::tempdir().unwrap();
+        let install = installed(&root);
+        let source = serve(
+            "pack.tar.gz",
+            tarball(&[("new.bhtimer", "new")]).await,
+            None,
+        )
+        .await;
+        let version = install_atomically(&source, &install, &Channel::Latest)
+            .await
+            .unwrap();
+        assert_eq!(version, "v2");
+        assert_eq!(listing(&install), ["new.bhtimer"]);
+        assert_eq!(listing(&previous_install_path(&install)), ["old.bhtimer"]);
+        assert_eq!(listing(root.path()), ["timers", "timers.previous"]);
+    }
+
+    #[tokio::test]
+    async fn unsafe_paths_install_nothing() {
+        for path in [
+            "../evil.bhtimer",
+            "/evil.bhtimer",
+            "C:\\evil.bhtimer",
+            "C:/evil.bhtimer",
+            "fine\\..\\..\\evil.bhtimer",
+        ] {
+            let files = [("fine.bhtimer", "fine"), (path, "evil")];
+            for (name, archive) in [
+                ("pack.tar.gz", tarball(&files).await),
+                ("pack.zip", zipball(&files)),
+            ] {
+                let root = tempfile::tempdir().unwrap();
+                let install = installed(&root);
+                let source = serve(name, archive, None).await;
+                let err = install_atomically(&source, &install, &Channel::Latest)
+                    .await
+                    .unwrap_err();
+                assert!(
+                    err.to_string().contains("leaves the install directory"),
+                    "{name} with {path}: {err}"
+                );
+                assert_untouched(&root, &install);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn oversized_archives_install_nothing() {
+        let files = [("new.bhtimer", "new")];
+        let sources = [
+            // the server says up front that it's too big
+            serve("pack.tar.gz", tarball(&files).await, Some(TOO_BIG)).await,
+            serve("pack.zip", zipball(&files), Some(TOO_BIG)).await,
+            // or it only turns out to be once it's unzipped
+            serve(
+                "pack.tar.gz",
+                gzip(header("huge.bhtimer", TOO_BIG).as_bytes()).await,
+                None,
+            )
+            .await,
+        ];
+        for source in sources {
+            let root = tempfile::tempdir().unwrap();
+            let install = installed(&root);
+            let err = install_atomically(&source, &install, &Channel::Latest)
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("bytes"), "{source}: {err}");
+            assert_untouched(&root, &install);
+        }
+    }
+
+    #[tokio::test]
+    async fn empty_archives_install_nothing() {
+        let root = tempfile::tempdir().unwrap();
+        let install = installed(&root);
+        let source = serve("pack.tar.gz", tarball(&[]).await, None).await;
+        let err = install_atomically(&source, &install, &Channel::Latest)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("keeping the current install"),
+            "{err}"
+        );
+        assert_untouched(&root, &install);
+    }
+
+    #[tokio::test]
+    async fn rolls_back_to_the_previous_install() {
+        let root = tempfile::tempdir().unwrap();
+        let install = installed(&root);
+        assert!(roll_back(&install).await.is_err());
+
+        let source = serve(
+            "pack.tar.gz",
+            tarball(&[("new.bhtimer", "new")]).await,
+            None,
+        )
+        .await;
+        install_atomically(&source, &install, &Channel::Latest)
+            .await
+            .unwrap();
+        roll_back(&install).await.unwrap();
+        assert_eq!(listing(&install), ["old.bhtimer"]);
+        assert_eq!(listing(&previous_install_path(&install)), ["new.bhtimer"]);
+
+        // and forward again, it's a swap
+        roll_back(&install).await.unwrap();
+        assert_eq!(listing(&install), ["new.bhtimer"]);
+        assert_eq!(listing(root.path()), ["timers", "timers.previous"]);
+    }
+}
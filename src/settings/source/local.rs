@@ -0,0 +1,85 @@
+use {
+    super::{dir_name, Channel},
+    crate::settings::Source,
+    anyhow::anyhow,
+    chrono::{DateTime, Utc},
+    serde::{Deserialize, Serialize},
+    std::{
+        fmt,
+        path::{Path, PathBuf},
+        time::SystemTime,
+    },
+};
+
+/*
+ * A folder (or a symlink to one) that something else keeps up to date, e.g.
+ * a git checkout you're writing timers in. It's used where it is; nothing is
+ * ever copied into, or deleted from, it.
+ */
+#[derive(Deserialize, Serialize, Debug, Hash, Eq, Clone, PartialEq)]
+pub struct LocalSource {
+    pub path: PathBuf,
+    pub description: Option<String>,
+}
+
+impl fmt::Display for LocalSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.path.display())
+    }
+}
+
+impl LocalSource {
+    fn newest_modification(dir: &Path) -> anyhow::Result<Option<SystemTime>> {
+        let mut newest = None;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            // metadata() follows symlinks, which is what we want here
+            let metadata = std::fs::metadata(&path)?;
+            let modified = match metadata.is_dir() {
+                true => Self::newest_modification(&path)?,
+                false => Some(metadata.modified()?),
+            };
+            newest = newest.max(modified);
+        }
+        Ok(newest)
+    }
+}
+
+impl Source for LocalSource {
+    fn install_dir(&self) -> String {
+        dir_name(&["local", &self.path.to_string_lossy()])
+    }
+    fn install_path(&self, _addon_dir: &Path) -> PathBuf {
+        self.path.clone()
+    }
+    fn owns_install(&self) -> bool {
+        false
+    }
+    fn view_url(&self) -> String {
+        self.path.display().to_string()
+    }
+    fn supports_channels(&self) -> bool {
+        false
+    }
+    async fn download_latest(
+        &self,
+        _install_dir: &Path,
+        channel: &Channel,
+    ) -> anyhow::Result<String> {
+        // nothing to fetch, "installing" is just pointing at it
+        self.latest_id(channel).await
+    }
+
+    async fn latest_id(&self, _channel: &Channel) -> anyhow::Result<String> {
+        let path = self.path.clone();
+        let newest = tokio::task::spawn_blocking(move || {
+            if !path.is_dir() {
+                return Err(anyhow!("{path:?} isn't a directory"));
+            }
+            Self::newest_modification(&path)
+        })
+        .await??;
+        let newest: DateTime<Utc> = newest.unwrap_or(SystemTime::UNIX_EPOCH).into();
+        Ok(newest.to_rfc3339())
+    }
+}
@@ -1,104 +1,456 @@
 use {
+    anyhow::{anyhow, bail},
     async_compression::tokio::bufread::GzipDecoder,
     futures::stream::{StreamExt, TryStreamExt},
-    reqwest::{Client, IntoUrl, Response},
+    reqwest::{
+        header::{ETAG, IF_NONE_MATCH},
+        Client, IntoUrl, RequestBuilder, Response, StatusCode,
+    },
     serde::{Deserialize, Serialize},
     std::{
         fmt::{self, Display},
-        io,
-        path::{Path, PathBuf},
+        io::{self, Cursor},
+        path::{Component, Path, PathBuf},
     },
-    tokio::fs::{create_dir_all, remove_dir_all},
+    tokio::fs::create_dir_all,
     tokio_tar::Archive,
     tokio_util::io::StreamReader,
+    zip::ZipArchive,
 };
 
+mod channel;
+mod forge;
+mod gitea;
 mod github;
+mod gitlab;
+mod http;
+mod install;
+mod local;
+mod release_notes;
+mod request;
 
-pub use github::GitHubSource;
+pub use {
+    channel::Channel,
+    gitea::GiteaSource,
+    github::GitHubSource,
+    gitlab::GitLabSource,
+    http::HttpSource,
+    install::{install_atomically, previous_install_path, roll_back},
+    local::LocalSource,
+    release_notes::ReleaseNotes,
+    request::RequestError,
+};
 
 #[derive(Deserialize, Serialize, Hash, Eq, PartialEq, Debug, Clone)]
 #[serde(tag = "type")]
 pub enum RemoteSource {
     GitHub(GitHubSource),
+    GitLab(GitLabSource),
+    // Codeberg and other Forgejo instances speak the same API
+    #[serde(alias = "Forgejo")]
+    Gitea(GiteaSource),
+    Http(HttpSource),
+    Local(LocalSource),
 }
 
 impl RemoteSource {
-    pub fn source(&self) -> GitHubSource {
+    pub fn description(&self) -> Option<&str> {
+        use RemoteSource::*;
         match self {
-            RemoteSource::GitHub(gs) => gs.clone(),
+            GitHub(s) => s.description.as_deref(),
+            GitLab(s) => s.description.as_deref(),
+            Gitea(s) => s.description.as_deref(),
+            Http(s) => s.description.as_deref(),
+            Local(s) => s.description.as_deref(),
         }
     }
 
-    #[allow(dead_code)]
-    pub async fn download_latest(&self) -> anyhow::Result<String> {
+    pub fn kind(&self) -> &'static str {
+        use RemoteSource::*;
         match self {
-            RemoteSource::GitHub(gs) => Ok(gs.download_latest().await?),
+            GitHub(_) => "GitHub",
+            GitLab(_) => "GitLab",
+            Gitea(_) => "Gitea",
+            Http(_) => "HTTP",
+            Local(_) => "local",
         }
     }
 }
 
 impl fmt::Display for RemoteSource {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let RemoteSource::GitHub(inner) = self;
-        /*let inner = match self {
-            RemoteSource::GitHub(gs) => gs,
-        };*/
+        use RemoteSource::*;
+        match self {
+            GitHub(s) => write!(f, "{}", s),
+            GitLab(s) => write!(f, "{}", s),
+            Gitea(s) => write!(f, "{}", s),
+            Http(s) => write!(f, "{}", s),
+            Local(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/*
+ * The enum just hands everything to whichever backend it is, so the rest of
+ * the addon only ever has to deal with RemoteSource.
+ */
+impl Source for RemoteSource {
+    fn install_dir(&self) -> String {
+        use RemoteSource::*;
+        match self {
+            GitHub(s) => s.install_dir(),
+            GitLab(s) => s.install_dir(),
+            Gitea(s) => s.install_dir(),
+            Http(s) => s.install_dir(),
+            Local(s) => s.install_dir(),
+        }
+    }
+
+    fn install_path(&self, addon_dir: &Path) -> PathBuf {
+        use RemoteSource::*;
+        match self {
+            GitHub(s) => s.install_path(addon_dir),
+            GitLab(s) => s.install_path(addon_dir),
+            Gitea(s) => s.install_path(addon_dir),
+            Http(s) => s.install_path(addon_dir),
+            Local(s) => s.install_path(addon_dir),
+        }
+    }
+
+    fn owns_install(&self) -> bool {
+        use RemoteSource::*;
+        match self {
+            GitHub(s) => s.owns_install(),
+            GitLab(s) => s.owns_install(),
+            Gitea(s) => s.owns_install(),
+            Http(s) => s.owns_install(),
+            Local(s) => s.owns_install(),
+        }
+    }
 
-        write!(f, "{}", inner)
+    fn view_url(&self) -> String {
+        use RemoteSource::*;
+        match self {
+            GitHub(s) => s.view_url(),
+            GitLab(s) => s.view_url(),
+            Gitea(s) => s.view_url(),
+            Http(s) => s.view_url(),
+            Local(s) => s.view_url(),
+        }
+    }
+
+    fn supports_channels(&self) -> bool {
+        use RemoteSource::*;
+        match self {
+            GitHub(s) => s.supports_channels(),
+            GitLab(s) => s.supports_channels(),
+            Gitea(s) => s.supports_channels(),
+            Http(s) => s.supports_channels(),
+            Local(s) => s.supports_channels(),
+        }
+    }
+
+    async fn download_latest(
+        &self,
+        install_dir: &Path,
+        channel: &Channel,
+    ) -> anyhow::Result<String> {
+        use RemoteSource::*;
+        match self {
+            GitHub(s) => s.download_latest(install_dir, channel).await,
+            GitLab(s) => s.download_latest(install_dir, channel).await,
+            Gitea(s) => s.download_latest(install_dir, channel).await,
+            Http(s) => s.download_latest(install_dir, channel).await,
+            Local(s) => s.download_latest(install_dir, channel).await,
+        }
+    }
+
+    async fn latest_id(&self, channel: &Channel) -> anyhow::Result<String> {
+        use RemoteSource::*;
+        match self {
+            GitHub(s) => s.latest_id(channel).await,
+            GitLab(s) => s.latest_id(channel).await,
+            Gitea(s) => s.latest_id(channel).await,
+            Http(s) => s.latest_id(channel).await,
+            Local(s) => s.latest_id(channel).await,
+        }
+    }
+
+    async fn release_notes(&self, channel: &Channel) -> anyhow::Result<Option<ReleaseNotes>> {
+        use RemoteSource::*;
+        match self {
+            GitHub(s) => s.release_notes(channel).await,
+            GitLab(s) => s.release_notes(channel).await,
+            Gitea(s) => s.release_notes(channel).await,
+            Http(s) => s.release_notes(channel).await,
+            Local(s) => s.release_notes(channel).await,
+        }
     }
 }
 
+/// Archives worth of timers should never get anywhere near this.
+const ARCHIVE_SIZE_LIMIT: usize = 256 * 1024 * 1024;
+
 pub trait Source: Display {
+    // directory name under the addon dir that this source installs into
     fn install_dir(&self) -> String;
+    fn install_path(&self, addon_dir: &Path) -> PathBuf {
+        addon_dir.join(self.install_dir())
+    }
+    // whether we put the files there, and so are allowed to delete them
+    fn owns_install(&self) -> bool {
+        true
+    }
     fn view_url(&self) -> String;
-    async fn download_latest(&self) -> anyhow::Result<String>;
-    async fn latest_id(&self) -> anyhow::Result<String>;
+    // whether there's more than one version to choose from at all
+    fn supports_channels(&self) -> bool {
+        true
+    }
+    // fetches into `dir`, which is empty, and gives back the version fetched
+    async fn download_latest(&self, dir: &Path, channel: &Channel) -> anyhow::Result<String>;
+    async fn latest_id(&self, channel: &Channel) -> anyhow::Result<String>;
+    // only releases have notes, so most channels and sources won't have any
+    async fn release_notes(&self, _channel: &Channel) -> anyhow::Result<Option<ReleaseNotes>> {
+        Ok(None)
+    }
+
+    fn client() -> anyhow::Result<Client> {
+        request::shared_client()
+    }
+
+    // adds whatever credentials the source has to a request
+    fn authorize(request: RequestBuilder) -> RequestBuilder {
+        request
+    }
+
+    async fn send(request: RequestBuilder) -> anyhow::Result<Response> {
+        let resp = Self::authorize(request).send().await?;
+        request::check_response(&resp)?;
+        Ok(resp.error_for_status()?)
+    }
 
     async fn get<U: IntoUrl>(url: U) -> anyhow::Result<Response> {
-        let name = env!("CARGO_PKG_NAME");
-        let authors = env!("CARGO_PKG_AUTHORS");
-        let user_agent = format!("{} by {}", name, authors);
-        let client = Client::builder().user_agent(user_agent).build()?;
-        let resp = client.get(url).send().await?.error_for_status()?;
-        Ok(resp)
+        Self::send(Self::client()?.get(url)).await
+    }
+
+    async fn head<U: IntoUrl>(url: U) -> anyhow::Result<Response> {
+        Self::send(Self::client()?.head(url)).await
+    }
+
+    // the body of an API response, asked for conditionally if we've seen it before
+    async fn get_text<U: IntoUrl>(url: U) -> anyhow::Result<String> {
+        let url = url.into_url()?;
+        let cached = request::cached(&url);
+        let mut builder = Self::client()?.get(url.clone());
+        if let Some((etag, _)) = &cached {
+            builder = builder.header(IF_NONE_MATCH, etag);
+        }
+        let resp = Self::send(builder).await?;
+        if let (StatusCode::NOT_MODIFIED, Some((_, body))) = (resp.status(), cached) {
+            log::debug!("{url} hasn't changed since it was last asked for.");
+            return Ok(body);
+        }
+        let etag = resp
+            .headers()
+            .get(ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(str::to_string);
+        let body = resp.text().await?;
+        if let Some(etag) = etag {
+            request::cache(url, etag, body.clone());
+        }
+        Ok(body)
     }
 
     async fn get_and_extract_tar<U: IntoUrl>(dir: &Path, url: U) -> anyhow::Result<()> {
         let url = url.into_url()?;
         log::debug!("Beginning to fetch and extract into {dir:?} from {:?}", url);
         let response = Self::get(url.clone()).await?;
+        Self::extract_tar(dir, response).await?;
+        log::debug!(
+            "Completed fetching and extracting into {dir:?} from {:?}",
+            url
+        );
+        Ok(())
+    }
+
+    async fn extract_tar(dir: &Path, response: Response) -> anyhow::Result<()> {
+        check_archive_size(&response)?;
         let bytes_stream = response.bytes_stream().map_err(io::Error::other);
         let stream_reader = StreamReader::new(bytes_stream);
         let gzip_decoder = GzipDecoder::new(stream_reader);
         let mut tar_file = Archive::new(gzip_decoder);
         let entries = tar_file.entries()?;
         let mut containing_directory: Option<PathBuf> = None;
+        // it's gzipped on the way in, so the download size says little about what we unpack
+        let mut unpacked: u64 = 0;
         let mut iterator = entries;
-        iterator.next().await; // skip pax_global_header
-        if dir.exists() {
-            log::info!("Directory {dir:?} exists already; removing prior to extraction.");
-            remove_dir_all(dir).await?;
-        }
         while let Some(file) = iterator.next().await {
             let mut f = file?;
-            let path = f.path()?;
-            if let Some(prefix) = &containing_directory {
-                let destination_suffix = path.strip_prefix(prefix)?;
-                let destination_path = dir.join(destination_suffix);
-                if let Some(destination_parent) = destination_path.parent() {
-                    create_dir_all(destination_parent).await?;
-                    f.unpack(destination_path).await?;
-                    //f.unpack_in(destination).await?;
+            let entry_type = f.header().entry_type();
+            // git archive puts the commit id in one of these, forges all use it
+            if entry_type.is_pax_global_extensions() {
+                continue;
+            }
+            let path = safe_relative_path(&f.path()?)?;
+            let destination_suffix = match &containing_directory {
+                Some(prefix) => path.strip_prefix(prefix).map_err(|_| {
+                    anyhow::anyhow!("{path:?} is outside of the archive's directory {prefix:?}")
+                })?,
+                None if entry_type.is_dir() => {
+                    containing_directory = Some(path);
+                    continue;
+                }
+                None => {
+                    // no wrapping directory, so everything goes in as is
+                    containing_directory = Some(PathBuf::new());
+                    &path
                 }
-            } else {
-                containing_directory = Some(path.into_owned());
+            };
+            // links could point anywhere, and timers have no business with devices
+            if !entry_type.is_file() && !entry_type.is_dir() {
+                log::warn!("Skipping {path:?} in archive, it isn't a file or directory.");
+                continue;
+            }
+            unpacked += f.header().size()?;
+            if unpacked > ARCHIVE_SIZE_LIMIT as u64 {
+                bail!("Archive unpacks to more than {ARCHIVE_SIZE_LIMIT} bytes");
+            }
+            let destination_path = dir.join(destination_suffix);
+            if let Some(destination_parent) = destination_path.parent() {
+                create_dir_all(destination_parent).await?;
+                f.unpack(destination_path).await?;
             }
         }
-        log::debug!(
-            "Completed fetching and extracting into {dir:?} from {:?}",
-            url
-        );
         Ok(())
     }
+
+    async fn extract_zip(dir: &Path, response: Response) -> anyhow::Result<()> {
+        check_archive_size(&response)?;
+        let bytes = response.bytes().await?;
+        let dir = dir.to_path_buf();
+        tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
+            let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+            // strip a single wrapping directory, like the tarballs
+            let names: Vec<PathBuf> = (0..archive.len())
+                .filter_map(|i| {
+                    safe_relative_path(Path::new(archive.by_index(i).ok()?.name())).ok()
+                })
+                .collect();
+            let prefix = names
+                .first()
+                .and_then(|p| p.components().next())
+                .map(|c| PathBuf::from(c.as_os_str()))
+                .filter(|prefix| names.len() > 1 && names.iter().all(|p| p.starts_with(prefix)));
+            let mut unpacked: u64 = 0;
+            for i in 0..archive.len() {
+                let mut file = archive.by_index(i)?;
+                let name = safe_relative_path(Path::new(file.name()))?;
+                if file.is_symlink() {
+                    log::warn!("Skipping {name:?} in archive, it's a link.");
+                    continue;
+                }
+                let name = match &prefix {
+                    Some(prefix) => name.strip_prefix(prefix)?.to_path_buf(),
+                    None => name,
+                };
+                unpacked += file.size();
+                if unpacked > ARCHIVE_SIZE_LIMIT as u64 {
+                    bail!("Archive unpacks to more than {ARCHIVE_SIZE_LIMIT} bytes");
+                }
+                let destination_path = dir.join(name);
+                if file.is_dir() {
+                    std::fs::create_dir_all(&destination_path)?;
+                } else {
+                    if let Some(destination_parent) = destination_path.parent() {
+                        std::fs::create_dir_all(destination_parent)?;
+                    }
+                    let mut out = std::fs::File::create(&destination_path)?;
+                    io::copy(&mut file, &mut out)?;
+                }
+            }
+            Ok(())
+        })
+        .await?
+    }
+}
+
+// going by what the server says, when it bothers to say
+fn check_archive_size(response: &Response) -> anyhow::Result<()> {
+    if response
+        .content_length()
+        .is_some_and(|len| len as usize > ARCHIVE_SIZE_LIMIT)
+    {
+        bail!("Archive is larger than {ARCHIVE_SIZE_LIMIT} bytes");
+    }
+    Ok(())
+}
+
+/*
+ * Archive paths, but only if they stay inside wherever they're extracted to.
+ * Split by hand on both separators, so a Windows path is caught the same
+ * wherever this runs.
+ */
+fn safe_relative_path(path: &Path) -> anyhow::Result<PathBuf> {
+    let leaves = || anyhow!("Refusing to extract {path:?}, it leaves the install directory");
+    let text = path
+        .to_str()
+        .ok_or_else(|| anyhow!("Refusing to extract {path:?}, it isn't unicode"))?;
+    if text.starts_with(['/', '\\']) {
+        return Err(leaves());
+    }
+    let mut relative = PathBuf::new();
+    for part in text.split(['/', '\\']) {
+        match part {
+            "" | "." => (),
+            ".." => return Err(leaves()),
+            // drive letters, and alternate data streams while we're at it
+            part if part.contains(':') => return Err(leaves()),
+            part => relative.push(part),
+        }
+    }
+    Ok(relative)
+}
+
+// squashes anything that isn't safe in a directory name down to underscores
+fn dir_name(parts: &[&str]) -> String {
+    parts
+        .iter()
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            p.chars()
+                .map(|c| match c.is_ascii_alphanumeric() || c == '-' {
+                    true => c,
+                    false => '_',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archive_paths_stay_inside() {
+        for path in [
+            "../x",
+            "a/../../x",
+            "/etc/x",
+            "\\x",
+            "C:\\x",
+            "C:/x",
+            "c:x",
+            "a\\..\\x",
+            "x:stream",
+        ] {
+            assert!(safe_relative_path(Path::new(path)).is_err(), "{path}");
+        }
+        assert_eq!(
+            safe_relative_path(Path::new("./a//b\\c")).unwrap(),
+            Path::new("a").join("b").join("c")
+        );
+    }
 }
@@ -0,0 +1,16 @@
+use {
+    chrono::{DateTime, Utc},
+    serde::{Deserialize, Serialize},
+};
+
+// what a forge had to say about a release, for reading before installing it
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
+pub struct ReleaseNotes {
+    pub version: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub published_at: Option<DateTime<Utc>>,
+}
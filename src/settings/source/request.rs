@@ -0,0 +1,105 @@
+use {
+    chrono::{DateTime, TimeDelta, Utc},
+    reqwest::{Client, Response, StatusCode},
+    std::{
+        collections::HashMap,
+        error::Error,
+        fmt,
+        sync::{LazyLock, Mutex, OnceLock},
+    },
+    url::Url,
+};
+
+static CLIENT: OnceLock<Client> = OnceLock::new();
+
+/*
+ * The last response for every API URL that came with an ETag, so the next
+ * ask can be a conditional one. Every source asks about its own URLs, so
+ * this is as good as a cache per source. A 304 doesn't count against
+ * GitHub's rate limit, which is the main point of it.
+ */
+static ETAGS: LazyLock<Mutex<HashMap<Url, (String, String)>>> = LazyLock::new(Default::default);
+
+// one client for everything, so connections get reused between sources
+pub fn shared_client() -> anyhow::Result<Client> {
+    if let Some(client) = CLIENT.get() {
+        return Ok(client.clone());
+    }
+    let name = env!("CARGO_PKG_NAME");
+    let authors = env!("CARGO_PKG_AUTHORS");
+    let user_agent = format!("{} by {}", name, authors);
+    let client = Client::builder().user_agent(user_agent).build()?;
+    Ok(CLIENT.get_or_init(|| client).clone())
+}
+
+pub fn cached(url: &Url) -> Option<(String, String)> {
+    ETAGS.lock().unwrap().get(url).cloned()
+}
+
+pub fn cache(url: Url, etag: String, body: String) {
+    ETAGS.lock().unwrap().insert(url, (etag, body));
+}
+
+/*
+ * Failures worth telling apart from everything else, as there's something
+ * the user can do about them. They travel inside anyhow, so check with
+ * downcast_ref.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum RequestError {
+    // no more requests until the reset, if the server told us when that is
+    RateLimited(Option<DateTime<Utc>>),
+    // the token's wrong, expired, or missing a scope
+    Unauthorized(String),
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use RequestError::*;
+        match self {
+            RateLimited(Some(reset)) => write!(f, "Rate limited until {reset}"),
+            RateLimited(None) => write!(f, "Rate limited"),
+            Unauthorized(host) => write!(f, "{host} refused the access token"),
+        }
+    }
+}
+
+impl Error for RequestError {}
+
+/*
+ * GitHub says 403 with no requests remaining, everyone says 429 when
+ * they're being polite about it. The reset's an epoch timestamp from the
+ * forges, Retry-After is seconds from now.
+ */
+pub fn check_response(response: &Response) -> Result<(), RequestError> {
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<i64>().ok())
+    };
+    let remaining = header("x-ratelimit-remaining").or_else(|| header("ratelimit-remaining"));
+    if let Some(remaining) = remaining {
+        log::debug!("{} has {remaining} requests left.", response.url());
+    }
+    let status = response.status();
+    let rate_limited = status == StatusCode::TOO_MANY_REQUESTS
+        || (status == StatusCode::FORBIDDEN && remaining == Some(0));
+    if rate_limited {
+        let reset = header("x-ratelimit-reset")
+            .or_else(|| header("ratelimit-reset"))
+            .and_then(|reset| DateTime::from_timestamp(reset, 0))
+            .or_else(|| {
+                header("retry-after")
+                    .and_then(TimeDelta::try_seconds)
+                    .map(|after| Utc::now() + after)
+            });
+        return Err(RequestError::RateLimited(reset));
+    }
+    if status == StatusCode::UNAUTHORIZED {
+        let host = response.url().host_str().unwrap_or_default().to_string();
+        return Err(RequestError::Unauthorized(host));
+    }
+    Ok(())
+}
@@ -2,17 +2,40 @@ use {
     crate::settings::{GitHubSource, RemoteSource},
     nexus::paths::get_addon_dir,
     serde::{Deserialize, Serialize},
-    std::collections::HashMap,
+    std::{
+        collections::HashMap,
+        path::{Path, PathBuf},
+    },
     tokio::{
         fs::{create_dir_all, read_to_string, File},
         io::AsyncWriteExt,
     },
 };
 
-#[derive(Deserialize, Serialize, Hash, Debug, Default, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Hash, Debug, Default, PartialEq, Eq, Clone, Copy)]
 pub enum SourceKind {
     #[default]
     Timers,
+    // .markers files, as loaded from the markers folder
+    Markers,
+    // TacO / Blish pathing packs
+    Pathing,
+}
+
+impl SourceKind {
+    pub fn all() -> [Self; 3] {
+        [Self::Timers, Self::Markers, Self::Pathing]
+    }
+
+    // where sources of this kind get installed to, timers stay where they always were
+    pub fn install_base(&self, addon_dir: &Path) -> PathBuf {
+        use SourceKind::*;
+        match self {
+            Timers => addon_dir.to_path_buf(),
+            Markers => addon_dir.join("sources").join("markers"),
+            Pathing => addon_dir.join("sources").join("pathing"),
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Default, Debug)]
@@ -25,11 +48,13 @@ impl SourcesFile {
             SourceKind::Timers,
             vec![
                     RemoteSource::GitHub(GitHubSource {
+                        api: GitHubSource::default_api(),
                         owner: "kittywitch".to_string(),
                         repository: "Hero-Timers".to_string(),
                         description: Some("The author of this mod's fork of the below; changes such as Sabetha markers and others planned, specific to this addon.".to_string()),
                     }),
                     RemoteSource::GitHub(GitHubSource {
+                        api: GitHubSource::default_api(),
                         owner: "QuitarHero".to_string(),
                         repository: "Hero-Timers".to_string(),
                         description: Some("The OG timer pack for BlishHUD!".to_string()),
@@ -0,0 +1,244 @@
+use {
+    chrono::{DateTime, TimeDelta, Utc},
+    serde::{Deserialize, Serialize},
+    std::fmt,
+};
+
+// the first retry after a failed check, doubling from there
+const BACKOFF_BASE_MINUTES: i64 = 5;
+const BACKOFF_MAX_HOURS: i64 = 6;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum UpdateCheckPolicy {
+    // only when the button's pressed, nothing goes out over the network otherwise
+    #[default]
+    Manual,
+    OnLoad,
+    Every {
+        hours: u32,
+    },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum UpdateAction {
+    // show it in the data sources tab and leave it at that
+    #[default]
+    Notify,
+    // install it and reload, only for sources that are already installed
+    AutoDownload,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct UpdateSettings {
+    #[serde(default)]
+    pub check: UpdateCheckPolicy,
+    #[serde(default)]
+    pub action: UpdateAction,
+}
+
+impl UpdateCheckPolicy {
+    pub fn variants(hours: u32) -> [Self; 3] {
+        use UpdateCheckPolicy::*;
+        [Manual, OnLoad, Every { hours }]
+    }
+
+    pub fn interval(&self) -> Option<TimeDelta> {
+        use UpdateCheckPolicy::*;
+        match self {
+            Every { hours } => Some(TimeDelta::hours((*hours).max(1) as i64)),
+            Manual | OnLoad => None,
+        }
+    }
+}
+
+impl fmt::Display for UpdateCheckPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use UpdateCheckPolicy::*;
+        match self {
+            Manual => write!(f, "Only when asked"),
+            OnLoad => write!(f, "When the addon loads"),
+            Every { hours } => write!(f, "Every {} hours", (*hours).max(1)),
+        }
+    }
+}
+
+impl UpdateAction {
+    pub fn all() -> [Self; 2] {
+        [Self::Notify, Self::AutoDownload]
+    }
+}
+
+impl fmt::Display for UpdateAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use UpdateAction::*;
+        match self {
+            Notify => write!(f, "Let me know"),
+            AutoDownload => write!(f, "Download and reload"),
+        }
+    }
+}
+
+impl UpdateSettings {
+    // how long to wait after `failures` checks in a row have gone wrong
+    pub fn backoff(failures: u32) -> TimeDelta {
+        let doublings = failures.saturating_sub(1).min(16);
+        let backoff = TimeDelta::minutes(BACKOFF_BASE_MINUTES * (1 << doublings));
+        backoff.min(TimeDelta::hours(BACKOFF_MAX_HOURS))
+    }
+
+    /*
+     * When the next check ought to happen by itself, if ever.
+     *
+     * Everything hangs off of `last_checked`, which is when the last check,
+     * successful or not, happened. `loaded_at` is when the addon started, for
+     * the on load policy and for anyone that's never checked at all.
+     */
+    pub fn next_check(
+        &self,
+        last_checked: Option<DateTime<Utc>>,
+        loaded_at: DateTime<Utc>,
+        failures: u32,
+    ) -> Option<DateTime<Utc>> {
+        use UpdateCheckPolicy::*;
+        let scheduled = match (&self.check, last_checked) {
+            (Manual, _) => return None,
+            (OnLoad, Some(last_checked)) if last_checked >= loaded_at => None,
+            (OnLoad, _) => Some(loaded_at),
+            (Every { .. }, None) => Some(loaded_at),
+            (Every { .. }, Some(last_checked)) => self.check.interval().map(|i| last_checked + i),
+        };
+        let retry = match (failures, last_checked) {
+            (0, _) | (_, None) => None,
+            (failures, Some(last_checked)) => Some(last_checked + Self::backoff(failures)),
+        };
+        match (scheduled, retry) {
+            (Some(scheduled), Some(retry)) => Some(scheduled.min(retry)),
+            (scheduled, retry) => scheduled.or(retry),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::settings::{GitHubSource, NeedsUpdate, RemoteSource, RemoteState, SourceKind},
+        tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpListener,
+        },
+        url::Url,
+    };
+
+    const RELEASE: &str = r#"{
+        "url": "http://localhost/release",
+        "html_url": "http://localhost/release",
+        "assets_url": "http://localhost/release/assets",
+        "upload_url": "http://localhost/release/upload",
+        "tarball_url": "http://localhost/tarball/v1.1.0",
+        "zipball_url": null,
+        "id": 1,
+        "node_id": "mock",
+        "tag_name": "v1.1.0",
+        "target_commitish": "main",
+        "name": "v1.1.0",
+        "body": "things got better",
+        "created_at": "2025-01-01T00:00:00Z",
+        "published_at": "2025-01-01T00:00:00Z",
+        "author": {},
+        "assets": []
+    }"#;
+
+    // a GitHub API that answers everything with the same response
+    async fn mock_api(status: u16, body: &'static str) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request).await;
+                let response = format!(
+                    "HTTP/1.1 {status} Mock\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        Url::parse(&format!("http://{addr}")).unwrap()
+    }
+
+    fn remote(api: Url, installed: Option<&str>) -> RemoteState {
+        let source = RemoteSource::GitHub(GitHubSource {
+            api,
+            owner: "mock".to_string(),
+            repository: "timers".to_string(),
+            description: None,
+        });
+        let mut remote = RemoteState::new_from_source(&source, SourceKind::Timers);
+        remote.installed_tag = installed.map(str::to_string);
+        remote
+    }
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        DateTime::<Utc>::UNIX_EPOCH + TimeDelta::minutes(minutes)
+    }
+
+    #[tokio::test]
+    async fn checks_against_the_api() {
+        let api = mock_api(200, RELEASE).await;
+        assert_eq!(
+            remote(api.clone(), Some("v1.0.0")).needs_update().await,
+            NeedsUpdate::Known(true, "v1.1.0".to_string())
+        );
+        assert_eq!(
+            remote(api.clone(), Some("v1.1.0")).needs_update().await,
+            NeedsUpdate::Known(false, "v1.1.0".to_string())
+        );
+        let notes = remote(api, None).release_notes().await.unwrap();
+        assert_eq!(notes.body.as_deref(), Some("things got better"));
+    }
+
+    #[tokio::test]
+    async fn failed_checks_back_off() {
+        let api = mock_api(500, "{}").await;
+        let update = remote(api, Some("v1.0.0")).needs_update().await;
+        assert!(update.is_error());
+
+        let settings = UpdateSettings {
+            check: UpdateCheckPolicy::Every { hours: 6 },
+            action: UpdateAction::Notify,
+        };
+        let last = Some(at(60));
+        assert_eq!(settings.next_check(last, at(0), 0), Some(at(60 + 6 * 60)));
+        assert_eq!(settings.next_check(last, at(0), 1), Some(at(65)));
+        assert_eq!(settings.next_check(last, at(0), 3), Some(at(80)));
+        // never any later than the schedule would've been anyway
+        assert_eq!(settings.next_check(last, at(0), 30), Some(at(60 + 6 * 60)));
+    }
+
+    #[test]
+    fn policies_schedule() {
+        let manual = UpdateSettings::default();
+        assert_eq!(manual.next_check(None, at(0), 0), None);
+        assert_eq!(manual.next_check(Some(at(1)), at(0), 2), None);
+
+        let on_load = UpdateSettings {
+            check: UpdateCheckPolicy::OnLoad,
+            ..Default::default()
+        };
+        assert_eq!(on_load.next_check(None, at(10), 0), Some(at(10)));
+        assert_eq!(on_load.next_check(Some(at(5)), at(10), 0), Some(at(10)));
+        assert_eq!(on_load.next_check(Some(at(11)), at(10), 0), None);
+        assert_eq!(on_load.next_check(Some(at(11)), at(10), 1), Some(at(16)));
+
+        let every = UpdateSettings {
+            check: UpdateCheckPolicy::Every { hours: 0 },
+            ..Default::default()
+        };
+        // zero hours is taken as one, rather than checking constantly
+        assert_eq!(every.next_check(Some(at(0)), at(0), 0), Some(at(60)));
+        assert_eq!(every.next_check(None, at(3), 0), Some(at(3)));
+    }
+}
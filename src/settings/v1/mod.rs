@@ -1,4 +1,11 @@
 mod remote_state;
+mod settings;
 mod timer;
+mod timer_changes;
 
-pub use {remote_state::RemoteState, timer::TimerSettings};
+pub use {
+    remote_state::RemoteState,
+    settings::{SettingsV1, LEGACY_SETTINGS_NAME},
+    timer::{TimerScope, TimerSettings, TimerSourceSettings},
+    timer_changes::TimerChanges,
+};
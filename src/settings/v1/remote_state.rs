@@ -1,18 +1,40 @@
 use {
     crate::{
-        settings::{source::Source, GitHubSource, NeedsUpdate, RemoteSource},
-        timer::TimerFile,
+        settings::{
+            source::{previous_install_path, roll_back, Channel, Source},
+            GitHubSource, NeedsUpdate, ReleaseNotes, RemoteSource, SourceKind, TimerChanges,
+        },
+        timer::{TimerFile, TimerPack},
     },
     serde::{Deserialize, Serialize},
-    std::{path::PathBuf, sync::Arc},
+    std::{
+        path::{Path, PathBuf},
+        sync::Arc,
+    },
     tokio::fs::remove_dir_all,
 };
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct RemoteState {
     pub source: Arc<RemoteSource>,
+    #[serde(default)]
+    pub kind: SourceKind,
+    #[serde(default)]
+    pub channel: Channel,
     pub installed_tag: Option<String>,
     pub installed_path: Option<PathBuf>,
+    // what was installed before the last update, if it's still around to roll back to
+    #[serde(default)]
+    pub previous_tag: Option<String>,
+    // for the release the channel was on as of the last check
+    #[serde(default)]
+    pub release_notes: Option<ReleaseNotes>,
+    // what the last update did to the timers
+    #[serde(default)]
+    pub changes: Option<TimerChanges>,
+    // what's installed, manifest and all, as of the last load
+    #[serde(skip)]
+    pub pack: Option<TimerPack>,
     #[serde(skip)]
     pub needs_update: NeedsUpdate,
 }
@@ -21,39 +43,74 @@ impl RemoteState {
     pub fn new(owner: &str, repository: &str, description: &str) -> Self {
         Self {
             source: Arc::new(RemoteSource::GitHub(GitHubSource {
+                api: GitHubSource::default_api(),
                 owner: owner.to_string(),
                 repository: repository.to_string(),
                 description: Some(description.to_string()),
             })),
+            kind: SourceKind::Timers,
+            channel: Default::default(),
             installed_tag: Default::default(),
             installed_path: Default::default(),
+            previous_tag: Default::default(),
+            release_notes: Default::default(),
+            changes: Default::default(),
+            pack: Default::default(),
             needs_update: Default::default(),
         }
     }
 
-    pub fn new_from_source(source: &RemoteSource) -> Self {
+    pub fn new_from_source(source: &RemoteSource, kind: SourceKind) -> Self {
         let source = Arc::new(source.clone());
         Self {
             source,
+            kind,
+            channel: Default::default(),
             installed_tag: Default::default(),
             installed_path: Default::default(),
+            previous_tag: Default::default(),
+            release_notes: Default::default(),
+            changes: Default::default(),
+            pack: Default::default(),
             needs_update: Default::default(),
         }
     }
 
-    pub fn source(&self) -> GitHubSource {
-        self.source.source()
+    pub fn install_path(&self, addon_dir: &Path) -> PathBuf {
+        self.source.install_path(&self.kind.install_base(addon_dir))
     }
 
-    pub async fn load(&self) -> Vec<Arc<TimerFile>> {
+    pub async fn load(&self) -> (Option<TimerPack>, Vec<Arc<TimerFile>>) {
         let association = self.source.clone();
-        if let Some(path) = &self.installed_path {
-            TimerFile::load_many(path, association, 100)
-                .await
-                .expect("Could not load timer file for source {self.source}")
-        } else {
-            Default::default()
+        if self.kind != SourceKind::Timers {
+            return Default::default();
         }
+        let Some(path) = &self.installed_path else {
+            return Default::default();
+        };
+        let pack = match TimerPack::open(path).await {
+            Ok(pack) => pack,
+            Err(err) => {
+                log::error!("Couldn't open {} as a timer pack: {}", self.source, err);
+                return Default::default();
+            }
+        };
+        if let Some(required) = pack.unmet_requirement() {
+            log::warn!(
+                "Not loading {}, it needs TaimiHUD {} or newer.",
+                self.source,
+                required
+            );
+            return (Some(pack), Default::default());
+        }
+        let timers = match TimerFile::load_many(Arc::new(pack.clone()), association, 100).await {
+            Ok(timers) => timers,
+            Err(err) => {
+                log::error!("Couldn't load the timers for {}: {}", self.source, err);
+                Default::default()
+            }
+        };
+        (Some(pack), timers)
     }
 
     pub fn update(&mut self, source: Arc<RemoteSource>) {
@@ -63,15 +120,26 @@ impl RemoteState {
     pub async fn uninstall(&mut self) -> anyhow::Result<()> {
         // fuck man, be careful o:
         if let Some(path) = &self.installed_path {
-            if path.exists() {
+            if !self.source.owns_install() {
+                log::info!("Uninstalling: leaving {path:?} alone, it isn't ours.");
+            } else if path.exists() {
                 log::warn!("Uninstalling: removing {path:?}!");
                 remove_dir_all(path).await?;
             } else {
                 log::warn!("Uninstalling: {path:?} no longer exists.");
             }
         }
+        if self.source.owns_install() {
+            if let Some(path) = &self.installed_path {
+                let previous = previous_install_path(path);
+                if previous.exists() {
+                    remove_dir_all(previous).await?;
+                }
+            }
+        }
         self.installed_tag = None;
         self.installed_path = None;
+        self.previous_tag = None;
         self.needs_update = NeedsUpdate::Unknown;
         Ok(())
     }
@@ -91,8 +159,12 @@ impl RemoteState {
 
     pub async fn needs_update(&self) -> NeedsUpdate {
         use NeedsUpdate::*;
-        let source = self.source();
-        let remote_id = source.latest_id().await;
+        // a pin is already the answer, no need to ask anyone
+        if let Some(pin) = self.channel.pin() {
+            let installed = self.installed_tag.as_deref() == Some(pin);
+            return Pinned(!installed, pin.to_string());
+        }
+        let remote_id = self.source.latest_id(&self.channel).await;
         log::debug!("{:?}", remote_id);
         match remote_id {
             Ok(rid) => {
@@ -104,18 +176,70 @@ impl RemoteState {
             }
             Err(err) => {
                 log::error!("Update check failed: {}", err);
-                NeedsUpdate::Error(err.to_string())
+                NeedsUpdate::from_error(err)
             }
         }
     }
+    pub async fn release_notes(&self) -> Option<ReleaseNotes> {
+        match self.source.release_notes(&self.channel).await {
+            Ok(notes) => notes,
+            Err(err) => {
+                log::warn!(
+                    "Couldn't get the release notes for {}: {}",
+                    self.source,
+                    err
+                );
+                None
+            }
+        }
+    }
+
     pub async fn commit_downloaded(
         &mut self,
         tag_name: String,
         install_dir: PathBuf,
     ) -> anyhow::Result<()> {
+        if self.source.owns_install() {
+            self.previous_tag = self.installed_tag.take();
+        }
         self.installed_tag = Some(tag_name);
         self.needs_update = self.needs_update().await;
         self.installed_path = Some(install_dir);
         Ok(())
     }
+
+    pub fn set_channel(&mut self, channel: Channel) {
+        if self.channel != channel {
+            self.channel = channel;
+            // whatever we knew was about the old channel
+            self.needs_update = NeedsUpdate::Unknown;
+        }
+    }
+
+    pub fn can_roll_back(&self) -> bool {
+        self.previous_tag.is_some()
+            && self
+                .installed_path
+                .as_ref()
+                .is_some_and(|p| previous_install_path(p).exists())
+    }
+
+    pub async fn roll_back(&mut self) -> anyhow::Result<()> {
+        let path = self
+            .installed_path
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("{} isn't installed", self.source))?;
+        let previous_tag = self
+            .previous_tag
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("{} has nothing to roll back to", self.source))?;
+        roll_back(path).await?;
+        log::info!("Rolled {} back to {previous_tag}.", self.source);
+        // the one we rolled back from becomes the one to roll forward to
+        self.previous_tag = self.installed_tag.replace(previous_tag);
+        if let Some(newer) = &self.previous_tag {
+            self.needs_update = NeedsUpdate::Known(true, newer.clone());
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,126 @@
+use {
+    crate::settings::{
+        GitHubSource, MarkerAutoPlaceSettings, MarkerSettings, ProgressBarSettings, RemoteSource,
+        RemoteState, SourceKind, TimerSettings, TimerSourceSettings, UpdateSettings,
+    },
+    anyhow::anyhow,
+    chrono::{DateTime, Utc},
+    serde::{de::DeserializeOwned, Deserialize},
+    serde_json::{Map, Value},
+    std::{collections::HashMap, path::PathBuf},
+};
+
+pub const LEGACY_SETTINGS_NAME: &str = "settings.json";
+
+/*
+ * Sources as settings.json had them, from back when GitHub was the only
+ * kind there was. Frozen, so that however RemoteState changes these still
+ * read.
+ */
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum RemoteSourceV1 {
+    GitHub {
+        owner: String,
+        repository: String,
+        #[serde(default)]
+        description: Option<String>,
+    },
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RemoteStateV1 {
+    pub source: RemoteSourceV1,
+    #[serde(default)]
+    pub installed_tag: Option<String>,
+    #[serde(default)]
+    pub installed_path: Option<PathBuf>,
+}
+
+impl From<&RemoteStateV1> for RemoteState {
+    fn from(old: &RemoteStateV1) -> Self {
+        let RemoteSourceV1::GitHub {
+            owner,
+            repository,
+            description,
+        } = &old.source;
+        let source = RemoteSource::GitHub(GitHubSource {
+            api: GitHubSource::default_api(),
+            owner: owner.clone(),
+            repository: repository.clone(),
+            description: description.clone(),
+        });
+        let mut remote = RemoteState::new_from_source(&source, SourceKind::Timers);
+        remote.installed_tag = old.installed_tag.clone();
+        remote.installed_path = old.installed_path.clone();
+        remote
+    }
+}
+
+/*
+ * The settings.json everything used to live in, config and state alike.
+ * Only ever read now, to be split into config.toml and state.toml.
+ */
+#[derive(Default, Debug, Clone)]
+pub struct SettingsV1 {
+    pub last_checked: Option<DateTime<Utc>>,
+    pub updates: UpdateSettings,
+    pub github_token: Option<String>,
+    pub timers: HashMap<String, TimerSettings>,
+    pub timer_sources: HashMap<String, TimerSourceSettings>,
+    pub markers: HashMap<String, MarkerSettings>,
+    pub remotes: Vec<RemoteStateV1>,
+    pub primary_window_open: bool,
+    pub timers_window_open: bool,
+    pub markers_window_open: bool,
+    pub progress_bar: ProgressBarSettings,
+    pub enable_katrender: bool,
+    pub marker_autoplace: MarkerAutoPlaceSettings,
+}
+
+// one field at a time, so one that won't read doesn't take the disabled timers down with it
+fn field<T: DeserializeOwned + Default>(object: &Map<String, Value>, name: &str) -> T {
+    match object.get(name).cloned().map(serde_json::from_value) {
+        Some(Ok(value)) => value,
+        Some(Err(err)) => {
+            log::warn!(
+                "Settings: Leaving {name} out of {LEGACY_SETTINGS_NAME}, it won't read: {err}"
+            );
+            Default::default()
+        }
+        None => Default::default(),
+    }
+}
+
+impl SettingsV1 {
+    pub fn from_json(data: &str) -> anyhow::Result<Self> {
+        let Value::Object(object) = serde_json::from_str(data)? else {
+            return Err(anyhow!("{LEGACY_SETTINGS_NAME} isn't an object"));
+        };
+        let remotes = field::<Vec<Value>>(&object, "remotes")
+            .into_iter()
+            .filter_map(|remote| match serde_json::from_value(remote) {
+                Ok(remote) => Some(remote),
+                Err(err) => {
+                    log::warn!("Settings: Leaving a source out of {LEGACY_SETTINGS_NAME}: {err}");
+                    None
+                }
+            })
+            .collect();
+        Ok(Self {
+            last_checked: field(&object, "last_checked"),
+            updates: field(&object, "updates"),
+            github_token: field(&object, "github_token"),
+            timers: field(&object, "timers"),
+            timer_sources: field(&object, "timer_sources"),
+            markers: field(&object, "markers"),
+            remotes,
+            primary_window_open: field(&object, "primary_window_open"),
+            timers_window_open: field(&object, "timers_window_open"),
+            markers_window_open: field(&object, "markers_window_open"),
+            progress_bar: field(&object, "progress_bar"),
+            enable_katrender: field(&object, "enable_katrender"),
+            marker_autoplace: field(&object, "marker_autoplace"),
+        })
+    }
+}
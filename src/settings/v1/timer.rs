@@ -1,4 +1,11 @@
-use serde::{Deserialize, Serialize};
+use {
+    crate::timer::TimerFile,
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::{HashMap, HashSet},
+        fmt,
+    },
+};
 
 #[derive(Deserialize, Serialize, Default, Debug, Clone, PartialEq)]
 pub struct TimerSettings {
@@ -6,15 +13,87 @@ pub struct TimerSettings {
     pub disabled: bool,
 }
 
-impl TimerSettings {
-    pub fn disable(&mut self) {
-        self.disabled = true;
+/*
+ * Everything switched on or off for the timers of one source. Timer ids are
+ * only unique within a source, so the per timer settings live in here too.
+ */
+#[derive(Deserialize, Serialize, Default, Debug, Clone, PartialEq)]
+pub struct TimerSourceSettings {
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub disabled_categories: HashSet<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub timers: HashMap<String, TimerSettings>,
+}
+
+impl TimerSourceSettings {
+    pub fn is_empty(&self) -> bool {
+        self == &Default::default()
+    }
+
+    // what a timer with no setting of its own gets
+    pub fn inherited(&self, timer: &TimerFile) -> bool {
+        !(self.disabled || self.disabled_categories.contains(&timer.category))
     }
-    pub fn enable(&mut self) {
-        self.disabled = false;
+
+    pub fn count_disabled(&self) -> usize {
+        self.timers.values().filter(|x| x.disabled).count()
     }
-    pub fn toggle(&mut self) -> bool {
-        self.disabled = !self.disabled;
-        self.disabled
+}
+
+// what an enable, disable or toggle applies to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TimerScope {
+    Source(String),
+    Category { source: String, category: String },
+    Timer { source: String, id: String },
+}
+
+impl TimerScope {
+    pub fn source(timer: &TimerFile) -> Self {
+        Self::Source(timer.source_key())
+    }
+
+    pub fn category(timer: &TimerFile) -> Self {
+        Self::Category {
+            source: timer.source_key(),
+            category: timer.category.clone(),
+        }
+    }
+
+    pub fn timer(timer: &TimerFile) -> Self {
+        Self::Timer {
+            source: timer.source_key(),
+            id: timer.id.clone(),
+        }
+    }
+
+    pub fn source_key(&self) -> &str {
+        use TimerScope::*;
+        match self {
+            Source(source) | Category { source, .. } | Timer { source, .. } => source,
+        }
+    }
+
+    pub fn contains(&self, timer: &TimerFile) -> bool {
+        use TimerScope::*;
+        timer.source_key() == self.source_key()
+            && match self {
+                Source(_) => true,
+                Category { category, .. } => timer.category == *category,
+                Timer { id, .. } => timer.id == *id,
+            }
+    }
+}
+
+impl fmt::Display for TimerScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use TimerScope::*;
+        match self {
+            Source(source) => write!(f, "the timers from {source}"),
+            Category { source, category } => write!(f, "category {category:?} from {source}"),
+            Timer { source, id } => write!(f, "timer {id} from {source}"),
+        }
     }
 }
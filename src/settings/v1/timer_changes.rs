@@ -0,0 +1,108 @@
+use {
+    crate::timer::TimerFile,
+    serde::{Deserialize, Serialize},
+    serde_json::Value,
+    std::{collections::HashMap, sync::Arc},
+};
+
+/*
+ * What an update did to a source's timers, by id. A timer counts as changed
+ * if anything in the file besides where it lives on disk is different.
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
+pub struct TimerChanges {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    #[serde(default)]
+    pub added: Vec<String>,
+    #[serde(default)]
+    pub removed: Vec<String>,
+    #[serde(default)]
+    pub changed: Vec<String>,
+}
+
+impl TimerChanges {
+    fn contents(timers: &[Arc<TimerFile>]) -> HashMap<&str, Value> {
+        timers
+            .iter()
+            .map(|timer| {
+                let mut value = serde_json::to_value(&**timer).unwrap_or_default();
+                if let Some(object) = value.as_object_mut() {
+                    object.remove("path");
+                }
+                (timer.id.as_str(), value)
+            })
+            .collect()
+    }
+
+    pub fn between(before: &[Arc<TimerFile>], after: &[Arc<TimerFile>]) -> Self {
+        let before = Self::contents(before);
+        let after = Self::contents(after);
+        let mut changes = Self::default();
+        for (id, value) in &after {
+            match before.get(id) {
+                None => changes.added.push(id.to_string()),
+                Some(old) if old != value => changes.changed.push(id.to_string()),
+                Some(_) => (),
+            }
+        }
+        changes.removed = before
+            .keys()
+            .filter(|id| !after.contains_key(*id))
+            .map(|id| id.to_string())
+            .collect();
+        changes.added.sort();
+        changes.removed.sort();
+        changes.changed.sort();
+        changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json, std::path::PathBuf};
+
+    fn timer(id: &str, name: &str, path: &str) -> Arc<TimerFile> {
+        let mut timer: TimerFile = serde_json::from_value(json!({
+            "id": id,
+            "name": name,
+            "category": "Tests",
+            "description": "",
+            "author": "",
+            "icon": "icon.png",
+            "map": 1,
+            "reset": { "position": [0, 0, 0], "radius": 50 },
+            "phases": [{ "name": "Only", "start": { "position": [0, 0, 0], "radius": 10 } }]
+        }))
+        .unwrap();
+        timer.path = Some(PathBuf::from(path));
+        Arc::new(timer)
+    }
+
+    #[test]
+    fn added_removed_and_changed() {
+        let before = [
+            timer("a", "A", "old/a.bhtimer"),
+            timer("b", "B", "old/b.bhtimer"),
+            timer("c", "C", "old/c.bhtimer"),
+        ];
+        // a only moved, which doesn't count
+        let after = [
+            timer("d", "D", "new/d.bhtimer"),
+            timer("b", "B, but harder", "new/b.bhtimer"),
+            timer("a", "A", "new/a.bhtimer"),
+        ];
+        let changes = TimerChanges::between(&before, &after);
+        assert_eq!(changes.added, ["d"]);
+        assert_eq!(changes.removed, ["c"]);
+        assert_eq!(changes.changed, ["b"]);
+        assert!(!changes.is_empty());
+
+        let moved = [timer("a", "A", "elsewhere/a.bhtimer")];
+        assert!(TimerChanges::between(&before[..1], &moved).is_empty());
+    }
+}
@@ -0,0 +1,71 @@
+use {
+    super::{read_file, write_file},
+    crate::settings::{
+        MarkerAutoPlaceSettings, MarkerSettings, ProgressBarSettings, SettingsV1, TimerSettings,
+        TimerSourceSettings, UpdateSettings,
+    },
+    anyhow::anyhow,
+    serde::{Deserialize, Serialize},
+    std::{collections::HashMap, path::Path},
+};
+
+pub const CONFIG_NAME: &str = "config.toml";
+
+/*
+ * Everything the user chose, and nothing the addon works out for itself.
+ * Fine to edit by hand while the game's closed.
+ */
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct ConfigV2 {
+    #[serde(default)]
+    pub updates: UpdateSettings,
+    // sent along with every GitHub request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github_token: Option<String>,
+    // by timer id alone, from before settings were kept per source
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub timers: HashMap<String, TimerSettings>,
+    // by the source's install dir
+    #[serde(default)]
+    pub timer_sources: HashMap<String, TimerSourceSettings>,
+    #[serde(default)]
+    pub markers: HashMap<String, MarkerSettings>,
+    #[serde(default)]
+    pub progress_bar: ProgressBarSettings,
+    #[serde(default)]
+    pub enable_katrender: bool,
+    #[serde(default)]
+    pub marker_autoplace: MarkerAutoPlaceSettings,
+}
+
+impl From<SettingsV1> for ConfigV2 {
+    fn from(old: SettingsV1) -> Self {
+        Self {
+            updates: old.updates,
+            github_token: old.github_token,
+            timers: old.timers,
+            timer_sources: old.timer_sources,
+            markers: old.markers,
+            progress_bar: old.progress_bar,
+            enable_katrender: old.enable_katrender,
+            marker_autoplace: old.marker_autoplace,
+        }
+    }
+}
+
+impl ConfigV2 {
+    pub fn from_toml(data: &str) -> anyhow::Result<Self> {
+        toml::from_str(data).map_err(|err| anyhow!("Couldn't read the config: {err}"))
+    }
+
+    pub async fn load(addon_dir: &Path) -> anyhow::Result<Option<Self>> {
+        let Some(data) = read_file(&addon_dir.join(CONFIG_NAME)).await? else {
+            return Ok(None);
+        };
+        Ok(Some(Self::from_toml(&data)?))
+    }
+
+    pub async fn save(&self, addon_dir: &Path) -> anyhow::Result<()> {
+        write_file(&addon_dir.join(CONFIG_NAME), &toml::to_string_pretty(self)?).await
+    }
+}
@@ -0,0 +1,59 @@
+mod config;
+mod state;
+
+pub use {
+    config::{ConfigV2, CONFIG_NAME},
+    state::{StateFile, STATE_NAME},
+};
+
+use {
+    std::path::{Path, PathBuf},
+    tokio::{
+        fs::{create_dir_all, read_to_string, rename, try_exists, File},
+        io::AsyncWriteExt,
+    },
+};
+
+// the contents, or None if there's no file yet
+async fn read_file(path: &Path) -> anyhow::Result<Option<String>> {
+    if !try_exists(path).await? {
+        return Ok(None);
+    }
+    log::debug!("Settings: Loading \"{path:?}\".");
+    Ok(Some(read_to_string(path).await?))
+}
+
+/*
+ * Written next to the real thing and then moved over it, so the game
+ * closing halfway through a save can't leave half a file behind.
+ */
+async fn write_file(path: &Path, data: &str) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent).await?;
+    }
+    log::debug!("Settings: Saving to \"{path:?}\".");
+    let staging = path.with_extension("toml.new");
+    let mut file = File::create(&staging).await?;
+    file.write_all(data.as_bytes()).await?;
+    file.sync_all().await?;
+    drop(file);
+    rename(&staging, path).await?;
+    Ok(())
+}
+
+/*
+ * Moves a file that couldn't be read out of the way instead of saving the
+ * defaults over it, so whatever was in there can still be fixed by hand.
+ */
+pub async fn set_aside(path: &Path) -> Option<PathBuf> {
+    let mut aside = path.as_os_str().to_owned();
+    aside.push(".broken");
+    let aside = PathBuf::from(aside);
+    match rename(path, &aside).await {
+        Ok(()) => Some(aside),
+        Err(err) => {
+            log::error!("Couldn't move {path:?} out of the way: {err}");
+            None
+        }
+    }
+}
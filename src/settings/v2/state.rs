@@ -0,0 +1,65 @@
+use {
+    super::{read_file, write_file},
+    crate::settings::{RemoteState, SettingsV1},
+    anyhow::anyhow,
+    chrono::{DateTime, Utc},
+    serde::{Deserialize, Serialize},
+    std::path::Path,
+};
+
+pub const STATE_NAME: &str = "state.toml";
+
+/*
+ * What the addon keeps track of between runs: what's installed, when it
+ * was last checked, and which windows were open. Losing it costs a
+ * redownload, not anybody's choices.
+ */
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct StateFile {
+    #[serde(default)]
+    pub last_checked: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub primary_window_open: bool,
+    #[serde(default)]
+    pub timers_window_open: bool,
+    #[serde(default)]
+    pub markers_window_open: bool,
+    #[serde(default)]
+    pub remotes: Vec<RemoteState>,
+}
+
+impl From<&SettingsV1> for StateFile {
+    fn from(old: &SettingsV1) -> Self {
+        Self {
+            last_checked: old.last_checked,
+            primary_window_open: old.primary_window_open,
+            timers_window_open: old.timers_window_open,
+            markers_window_open: old.markers_window_open,
+            remotes: old.remotes.iter().map(RemoteState::from).collect(),
+        }
+    }
+}
+
+impl StateFile {
+    pub fn stock() -> Self {
+        Self {
+            remotes: RemoteState::suggested_sources().collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn from_toml(data: &str) -> anyhow::Result<Self> {
+        toml::from_str(data).map_err(|err| anyhow!("Couldn't read the state: {err}"))
+    }
+
+    pub async fn load(addon_dir: &Path) -> anyhow::Result<Option<Self>> {
+        let Some(data) = read_file(&addon_dir.join(STATE_NAME)).await? else {
+            return Ok(None);
+        };
+        Ok(Some(Self::from_toml(&data)?))
+    }
+
+    pub async fn save(&self, addon_dir: &Path) -> anyhow::Result<()> {
+        write_file(&addon_dir.join(STATE_NAME), &toml::to_string_pretty(self)?).await
+    }
+}
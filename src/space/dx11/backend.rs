@@ -102,7 +102,7 @@ impl RenderBackend {
     /*
     pub fn draw(&mut self, io: &Io) {
         if let Some(settings) = SETTINGS.get().and_then(|settings| settings.try_read().ok()) {
-            if settings.enable_katrender {
+            if settings.config.enable_katrender {
                 let display_size = io.display_size;
 
                 self.perspective_handler.update_perspective(&display_size);
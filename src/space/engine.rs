@@ -1,6 +1,9 @@
 use {
     super::{
-        dx11::{perspective_input_data::PERSPECTIVEINPUTDATA, InstanceBufferData, RenderBackend},
+        dx11::{
+            perspective_input_data::PERSPECTIVEINPUTDATA, InstanceBufferData, PerspectiveInputData,
+            RenderBackend,
+        },
         object::{ObjectBacking, ObjectLoader},
         pack::Pack,
         render_list::{MapFrustum, RenderList},
@@ -11,7 +14,7 @@ use {
             pack::{loader::DirectoryLoader, trail::ActiveTrail},
             resources::ObjFile,
         },
-        timer::{PhaseState, RotationType, TimerFile, TimerMarker},
+        timer::{PhaseState, RotationType, TimerDirection, TimerFile, TimerMarker},
     },
     anyhow::anyhow,
     bevy_ecs::prelude::*,
@@ -39,6 +42,14 @@ struct Marker {
     marker: TimerMarker,
 }
 
+#[derive(Component)]
+#[allow(unused)]
+struct Direction {
+    phase: Arc<PhaseState>,
+    start: Instant,
+    direction: TimerDirection,
+}
+
 #[derive(Bundle)]
 struct MarkerBundle {
     position: Position,
@@ -48,6 +59,8 @@ struct MarkerBundle {
 pub enum SpaceEvent {
     MarkerFeed(PhaseState),
     MarkerReset(Arc<TimerFile>),
+    // installed pathing sources, on top of whatever is in the pathing folder
+    LoadPacks(Vec<PathBuf>),
 }
 
 fn handle_marker_timings(mut commands: Commands, mut query: Query<(Entity, &Marker, &mut Render)>) {
@@ -71,6 +84,41 @@ fn handle_marker_timings(mut commands: Commands, mut query: Query<(Entity, &Mark
     }
 }
 
+// how far in front of the player the direction arrow floats, in metres
+const DIRECTION_ARROW_OFFSET: f32 = 2.5;
+
+fn handle_direction_timings(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Direction, &mut Render, &mut Position)>,
+) {
+    let now = Instant::now();
+    let player = PerspectiveInputData::read().map(|pdata| pdata.playpos);
+    for (entity, direction, mut render, mut position) in &mut query {
+        if now > direction.direction.end(direction.start) {
+            log::info!(
+                "Direction entity {} reached end after {}, despawning.",
+                entity,
+                direction.direction.duration
+            );
+            commands.entity(entity).despawn();
+            continue;
+        } else if now > direction.direction.start(direction.start) && render.disabled {
+            log::info!(
+                "Direction entity {} reached start at {}!",
+                entity,
+                direction.direction.timestamp
+            );
+            render.disabled = false;
+        }
+        if let Some(player) = player {
+            position.0 = direction
+                .direction
+                .arrow_position(player, DIRECTION_ARROW_OFFSET);
+            render.rotation = RotationType::Heading(direction.direction.yaw(player));
+        }
+    }
+}
+
 pub struct Engine {
     receiver: Receiver<SpaceEvent>,
     pub render_backend: RenderBackend,
@@ -84,14 +132,19 @@ pub struct Engine {
     // ECS stuff
     pub world: World,
 
-    test_pack: Pack,
-    test_trail: usize,
-    active_test_trail: ActiveTrail,
+    packs: Vec<Pack>,
+    // pack index, trail index
+    test_trail: Option<(usize, usize)>,
+    active_test_trail: Option<ActiveTrail>,
     render_list: Option<RenderList>,
 }
 
 impl Engine {
-    pub fn initialise(ui: &Ui, receiver: Receiver<SpaceEvent>) -> anyhow::Result<Engine> {
+    pub fn initialise(
+        ui: &Ui,
+        receiver: Receiver<SpaceEvent>,
+        pack_dirs: Vec<PathBuf>,
+    ) -> anyhow::Result<Engine> {
         let addon_dir = get_addon_dir("Taimi").expect("Invalid addon dir");
 
         let render_backend = RenderBackend::setup(&addon_dir, ui.io().display_size)?;
@@ -112,21 +165,7 @@ impl Engine {
 
         let mut schedule = Schedule::default();
 
-        schedule.add_systems(handle_marker_timings);
-
-        let mut test_pack = Pack::load(DirectoryLoader::new(
-            addon_dir.join("pathing/tw_ALL_IN_ONE"),
-        ))?;
-        const TEST_TRAIL: &str = "tw_guides.tw_mc_soto.tw_mc_soto_trails.tw_mc_soto_trails_thewizardstower.tw_mc_soto_trails_thewizardstower_toggletrail";
-        let test_trail = test_pack
-            .trails
-            .iter()
-            .enumerate()
-            .find(|(_, trail)| trail.category == TEST_TRAIL)
-            .map(|(idx, _)| idx)
-            .ok_or_else(|| anyhow::anyhow!("Can't find test trail"))?;
-        let active_test_trail =
-            ActiveTrail::build(&mut test_pack, test_trail, &render_backend.device)?;
+        schedule.add_systems((handle_marker_timings, handle_direction_timings));
 
         let mut engine = Engine {
             model_files,
@@ -137,11 +176,12 @@ impl Engine {
             world,
             associated_entities: Default::default(),
             phase_states: Default::default(),
-            test_pack,
-            test_trail,
-            active_test_trail,
+            packs: Default::default(),
+            test_trail: None,
+            active_test_trail: None,
             render_list: None,
         };
+        engine.load_packs(pack_dirs);
 
         if let Some(backing) = engine.object_kinds.get("Cat") {
             engine.world.spawn((
@@ -193,6 +233,36 @@ impl Engine {
                 entry.push(id);
             }
         }
+        for direction in &phase_state.directions {
+            if let Some(base_path) = &phase_state.timer.path {
+                let backing = Arc::new(ObjectBacking::create_direction(
+                    &self.render_backend,
+                    direction,
+                    base_path.clone(),
+                )?);
+                let entity = self.world.spawn((
+                    Position(direction.destination),
+                    Direction {
+                        phase: phase_state.clone(),
+                        start: phase_state.start,
+                        direction: direction.clone(),
+                    },
+                    Render {
+                        rotation: RotationType::Heading(0.0),
+                        disabled: true,
+                        backing,
+                    },
+                ));
+                let id = entity.id();
+                log::debug!(
+                    "Creating direction entity {id} towards {} from timer {}, phase {}",
+                    direction.destination,
+                    phase_state.timer.name(),
+                    phase_state.phase.name
+                );
+                entry.push(id);
+            }
+        }
         self.phase_states.push(phase_state);
         Ok(())
     }
@@ -217,6 +287,48 @@ impl Engine {
         self.phase_states.clear();
     }
 
+    /*
+     * Loads every pack in the pathing folder, and the installed pathing
+     * sources in `source_dirs`, replacing whatever was loaded before.
+     */
+    pub fn load_packs(&mut self, source_dirs: Vec<PathBuf>) {
+        let addon_dir = get_addon_dir("Taimi").expect("Invalid addon dir");
+        let mut pack_dirs: Vec<PathBuf> = match std::fs::read_dir(addon_dir.join("pathing")) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.is_dir())
+                .collect(),
+            Err(_) => Default::default(),
+        };
+        pack_dirs.extend(source_dirs);
+        self.packs.clear();
+        self.test_trail = None;
+        self.active_test_trail = None;
+        for dir in pack_dirs {
+            match Pack::load(DirectoryLoader::new(&dir)) {
+                Ok(pack) => {
+                    log::info!("Loaded pathing pack at {dir:?}.");
+                    self.packs.push(pack);
+                }
+                Err(err) => log::error!("Couldn't load pathing pack at {dir:?}: {err}"),
+            }
+        }
+        const TEST_TRAIL: &str = "tw_guides.tw_mc_soto.tw_mc_soto_trails.tw_mc_soto_trails_thewizardstower.tw_mc_soto_trails_thewizardstower_toggletrail";
+        self.test_trail = self.packs.iter().enumerate().find_map(|(pack_idx, pack)| {
+            pack.trails
+                .iter()
+                .position(|trail| trail.category == TEST_TRAIL)
+                .map(|trail_idx| (pack_idx, trail_idx))
+        });
+        if let Some((pack_idx, trail_idx)) = self.test_trail {
+            let device = &self.render_backend.device;
+            match ActiveTrail::build(&mut self.packs[pack_idx], trail_idx, device) {
+                Ok(trail) => self.active_test_trail = Some(trail),
+                Err(err) => log::error!("Couldn't build the test trail: {err}"),
+            }
+        }
+    }
+
     pub fn process_event(&mut self) -> anyhow::Result<()> {
         match self.receiver.try_recv() {
             Ok(event) => {
@@ -224,6 +336,7 @@ impl Engine {
                 match event {
                     MarkerFeed(phase_state) => self.new_phase(phase_state)?,
                     MarkerReset(timer) => self.remove_phase(timer)?,
+                    LoadPacks(dirs) => self.load_packs(dirs),
                 }
             }
             Err(_error) => (),
@@ -256,21 +369,20 @@ impl Engine {
         {
             let mut itery = c.into_iter();
             let slice = itery.next().ok_or(anyhow!("empty slice!"))?;
-            let (r, p) = slice;
+            let (r, _p) = slice;
             if !r.disabled {
-                let rot = match r.rotation {
-                    RotationType::Billboard => {
-                        let mark2d = (p.0.xz() - pdata.pos.xz()).to_angle();
-                        let y = Mat4::from_rotation_y(-90.0f32.to_radians() - mark2d);
-                        y
-                        //Mat4::IDENTITY
-                    }
-                    _ => Mat4::IDENTITY,
-                };
                 let ibd: Vec<_> = vec![slice]
                     .into_iter()
                     .chain(itery)
-                    .map(|(_r, p)| {
+                    .map(|(r, p)| {
+                        let rot = match r.rotation {
+                            RotationType::Billboard => {
+                                let mark2d = (p.0.xz() - pdata.pos.xz()).to_angle();
+                                Mat4::from_rotation_y(-90.0f32.to_radians() - mark2d)
+                            }
+                            RotationType::Heading(yaw) => Mat4::from_rotation_y(yaw),
+                            _ => Mat4::IDENTITY,
+                        };
                         //  r.backing.render.metadata.model_matrix *
                         let affy = Mat4::from_translation(p.0)
                             * rot
@@ -300,11 +412,15 @@ impl Engine {
             }
         }
         let mid = MarkerInputData::read().unwrap();
-        if mid.map_id as i32 == self.test_pack.trails[self.test_trail].data.map_id {
-            backend.shaders.0["trail"].set(&device_context);
-            backend.shaders.1["trail"].set(&device_context);
-            for i in 0..self.active_test_trail.section_bounds.len() {
-                self.active_test_trail.draw_section(&device_context, i);
+        if let (Some((pack_idx, trail_idx)), Some(active_test_trail)) =
+            (self.test_trail, &self.active_test_trail)
+        {
+            if mid.map_id as i32 == self.packs[pack_idx].trails[trail_idx].data.map_id {
+                backend.shaders.0["trail"].set(&device_context);
+                backend.shaders.1["trail"].set(&device_context);
+                for i in 0..active_test_trail.section_bounds.len() {
+                    active_test_trail.draw_section(&device_context, i);
+                }
             }
         }
         Ok(())
@@ -9,10 +9,13 @@ use {
                 Texture,
             },
         },
-        timer::TimerMarker,
+        timer::{TimerDirection, TimerMarker},
+    },
+    glam::{Mat4, Vec3},
+    std::{
+        path::{Path, PathBuf},
+        sync::RwLock,
     },
-    glam::Vec3,
-    std::{path::PathBuf, sync::RwLock},
     windows::Win32::Graphics::Direct3D11::{ID3D11Device, ID3D11DeviceContext},
 };
 
@@ -32,14 +35,39 @@ impl ObjectBacking {
         } else {
             marker.texture.clone()
         };
-        log::info!("Loading texture from {timer_path:?}!");
-        let texture = Texture::load(&render_backend.device, &timer_path)?;
+        Self::create_textured_quad(render_backend, "Marker", &timer_path, marker.model_matrix())
+    }
+
+    pub fn create_direction(
+        render_backend: &RenderBackend,
+        direction: &TimerDirection,
+        path: PathBuf,
+    ) -> anyhow::Result<Self> {
+        let timer_path = match path.parent() {
+            Some(timer_path_parent) => direction.texture.to_path(timer_path_parent),
+            None => direction.texture.to_path(""),
+        };
+        Self::create_textured_quad(
+            render_backend,
+            "Direction",
+            &timer_path,
+            TimerDirection::model_matrix(),
+        )
+    }
+
+    fn create_textured_quad(
+        render_backend: &RenderBackend,
+        name: &str,
+        texture_path: &Path,
+        model_matrix: Mat4,
+    ) -> anyhow::Result<Self> {
+        log::info!("Loading texture from {texture_path:?}!");
+        let texture = Texture::load(&render_backend.device, texture_path)?;
         let shaders = ShaderPair(
             render_backend.shaders.0["textured"].clone(),
             render_backend.shaders.1["textured"].clone(),
         );
         let model = Model::quad()?;
-        let model_matrix = marker.model_matrix();
         let ibd = [InstanceBufferData {
             world: model_matrix,
 
@@ -66,11 +94,11 @@ impl ObjectBacking {
                 topology: PrimitiveTopology::TriangleList,
             },
         };
-        let marker = Self {
-            name: "Marker".to_string(),
+        let object = Self {
+            name: name.to_string(),
             render,
         };
-        Ok(marker)
+        Ok(object)
     }
 
     pub fn set_and_draw(
@@ -11,12 +11,21 @@ pub struct TimerAction {
     pub kind: TimerActionType,
     pub sets: Option<Vec<String>>,
     pub trigger: TimerTrigger,
+    // seconds to skip ahead by, for skipTime; negative values go back in time
+    #[serde(default)]
+    pub time: Option<f32>,
+    // name of the phase to go to, for jumpToPhase
+    #[serde(default)]
+    pub phase: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum TimerActionType {
     SkipTime,
+    JumpToPhase,
+    Reset,
+    RestartPhase,
 }
 
 impl Default for TimerActionType {
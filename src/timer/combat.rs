@@ -0,0 +1,295 @@
+use {
+    arcdps::{
+        evtc::event::Event as arcEvent, Activation, AgentOwned, BuffRemove as ArcBuffRemove,
+        StateChange,
+    },
+    serde::{Deserialize, Serialize},
+    std::collections::VecDeque,
+};
+
+// how many event ids are remembered, for events both arcdps feeds carry
+const SEEN_EVENTS: usize = 256;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CombatEventKind {
+    SkillCast,
+    BuffApply,
+    BuffRemove,
+    Health,
+}
+
+/*
+ * The bits of an arcdps event we care about, pulled out so that matching
+ * doesn't need to know anything about the wire format.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct CombatEventInfo {
+    pub kind: CombatEventKind,
+    pub skill_id: u32,
+    // species of the agent the event is about, if it's an npc
+    pub species_id: Option<u32>,
+    pub health_percent: Option<f32>,
+}
+
+fn species(agent: Option<&AgentOwned>) -> Option<u32> {
+    // arcdps gives npcs an elite of 0xffffffff and their species id as prof
+    let agent = agent?;
+    (agent.elite == u32::MAX).then_some(agent.prof)
+}
+
+impl CombatEventInfo {
+    /*
+     * The area feed has everyone's events in it, but timers only ever care
+     * about what the npcs get up to; ours come in on the local feed.
+     */
+    pub fn npc_event(src: &AgentOwned, dst: Option<&AgentOwned>, evt: &arcEvent) -> bool {
+        let npc = species(Some(src)).is_some() || species(dst).is_some();
+        npc && Self::from_arc(src, dst, evt).is_some()
+    }
+
+    pub fn from_arc(src: &AgentOwned, dst: Option<&AgentOwned>, evt: &arcEvent) -> Option<Self> {
+        match evt.get_statechange() {
+            StateChange::HealthUpdate => Some(Self {
+                kind: CombatEventKind::Health,
+                skill_id: 0,
+                species_id: species(Some(src)),
+                // dst_agent holds the percentage times 100
+                health_percent: Some(evt.dst_agent as f32 / 100.0),
+            }),
+            StateChange::None => {
+                if evt.get_activation() == Activation::Start {
+                    Some(Self {
+                        kind: CombatEventKind::SkillCast,
+                        skill_id: evt.skill_id,
+                        species_id: species(Some(src)),
+                        health_percent: None,
+                    })
+                } else if evt.get_buffremove() != ArcBuffRemove::None {
+                    // removals are reported from the point of view of whoever lost the buff
+                    Some(Self {
+                        kind: CombatEventKind::BuffRemove,
+                        skill_id: evt.skill_id,
+                        species_id: species(Some(src)),
+                        health_percent: None,
+                    })
+                } else if evt.buff != 0 && evt.buff_dmg == 0 && evt.value != 0 {
+                    // an application, value is the duration applied
+                    Some(Self {
+                        kind: CombatEventKind::BuffApply,
+                        skill_id: evt.skill_id,
+                        species_id: species(dst),
+                        health_percent: None,
+                    })
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CombatMatcher {
+    pub event: CombatEventKind,
+    // skill or buff ids, any if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skill_ids: Vec<u32>,
+    // species ids of the agent, any if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub species_ids: Vec<u32>,
+    // percentages, for health events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_below: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_above: Option<f32>,
+}
+
+impl CombatMatcher {
+    pub fn matches(&self, info: &CombatEventInfo) -> bool {
+        let kind_check = self.event == info.kind;
+        let skill_check = self.skill_ids.is_empty() || self.skill_ids.contains(&info.skill_id);
+        let species_check = self.species_ids.is_empty()
+            || info
+                .species_id
+                .is_some_and(|species| self.species_ids.contains(&species));
+        let below_check = match (self.health_below, info.health_percent) {
+            (Some(below), Some(health)) => health < below,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        let above_check = match (self.health_above, info.health_percent) {
+            (Some(above), Some(health)) => health > above,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        kind_check && skill_check && species_check && below_check && above_check
+    }
+
+    pub fn matches_event(
+        &self,
+        src: &AgentOwned,
+        dst: Option<&AgentOwned>,
+        evt: &arcEvent,
+    ) -> bool {
+        CombatEventInfo::from_arc(src, dst, evt).is_some_and(|info| self.matches(&info))
+    }
+}
+
+/*
+ * arcdps hands events involving us to both the local and the area feeds,
+ * under the same id, so whichever gets here second is dropped.
+ */
+#[derive(Debug, Default)]
+pub struct SeenEvents {
+    ids: VecDeque<u64>,
+}
+
+impl SeenEvents {
+    pub fn first_sighting(&mut self, id: u64) -> bool {
+        // no id, no way of telling; better twice than never
+        if id == 0 {
+            return true;
+        }
+        if self.ids.contains(&id) {
+            return false;
+        }
+        if self.ids.len() >= SEEN_EVENTS {
+            self.ids.pop_front();
+        }
+        self.ids.push_back(id);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{CombatEventInfo, CombatEventKind, CombatMatcher, SeenEvents},
+        arcdps::{evtc::event::Event as arcEvent, AgentOwned},
+    };
+
+    const BOSS: u32 = 15438;
+    const SKILL: u32 = 34000;
+
+    fn npc(species: u32) -> AgentOwned {
+        AgentOwned {
+            name: Some("Vale Guardian".to_string()),
+            id: 1,
+            prof: species,
+            elite: u32::MAX,
+            is_self: 0,
+            team: 0,
+        }
+    }
+
+    fn player() -> AgentOwned {
+        AgentOwned {
+            name: Some("Taimi".to_string()),
+            id: 2,
+            prof: 4,
+            elite: 0,
+            is_self: 1,
+            team: 0,
+        }
+    }
+
+    /*
+     * Made up from the raw cbtevent bytes, same as arcdps hands them over,
+     * so these don't care what the fields are called this week.
+     */
+    fn event(skill_id: u32, edit: impl FnOnce(&mut [u8; 64])) -> arcEvent {
+        let mut raw = [0u8; 64];
+        raw[36..40].copy_from_slice(&skill_id.to_le_bytes());
+        edit(&mut raw);
+        unsafe { std::mem::transmute::<[u8; 64], arcEvent>(raw) }
+    }
+
+    fn cast(skill_id: u32) -> arcEvent {
+        // is_activation, start
+        event(skill_id, |raw| raw[51] = 1)
+    }
+
+    fn health(percent_times_100: u64) -> arcEvent {
+        event(0, |raw| {
+            raw[16..24].copy_from_slice(&percent_times_100.to_le_bytes());
+            // is_statechange, health update
+            raw[56] = 8;
+        })
+    }
+
+    fn matcher(value: serde_json::Value) -> CombatMatcher {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn boss_skill_cast() {
+        let matcher = matcher(serde_json::json!({
+            "event": "skillCast",
+            "skillIds": [SKILL],
+            "speciesIds": [BOSS]
+        }));
+        assert!(matcher.matches_event(&npc(BOSS), None, &cast(SKILL)));
+        assert!(!matcher.matches_event(&npc(BOSS + 1), None, &cast(SKILL)));
+        assert!(!matcher.matches_event(&npc(BOSS), None, &cast(SKILL + 1)));
+        assert!(!matcher.matches_event(&player(), None, &cast(SKILL)));
+        // a hit with the skill isn't it being cast
+        assert!(!matcher.matches_event(&npc(BOSS), None, &event(SKILL, |_| ())));
+    }
+
+    #[test]
+    fn health_thresholds() {
+        let below = matcher(serde_json::json!({ "event": "health", "healthBelow": 66 }));
+        let above = matcher(serde_json::json!({ "event": "health", "healthAbove": 66 }));
+        let boss = npc(BOSS);
+        assert!(below.matches_event(&boss, None, &health(6500)));
+        assert!(!below.matches_event(&boss, None, &health(6700)));
+        assert!(above.matches_event(&boss, None, &health(6700)));
+        assert!(!above.matches_event(&boss, None, &health(6500)));
+        // a cast has no health to compare against
+        assert!(!below.matches_event(&boss, None, &cast(SKILL)));
+    }
+
+    #[test]
+    fn buffs_are_about_who_has_them() {
+        let applied = event(SKILL, |raw| {
+            // value, the duration applied, and buff
+            raw[24..28].copy_from_slice(&5000i32.to_le_bytes());
+            raw[49] = 1;
+        });
+        let info = CombatEventInfo::from_arc(&player(), Some(&npc(BOSS)), &applied).unwrap();
+        assert_eq!(info.kind, CombatEventKind::BuffApply);
+        assert_eq!(info.species_id, Some(BOSS));
+
+        let removed = event(SKILL, |raw| {
+            raw[49] = 1;
+            // is_buffremove, all
+            raw[52] = 1;
+        });
+        let info = CombatEventInfo::from_arc(&npc(BOSS), Some(&player()), &removed).unwrap();
+        assert_eq!(info.kind, CombatEventKind::BuffRemove);
+        assert_eq!(info.species_id, Some(BOSS));
+    }
+
+    #[test]
+    fn area_feed_only_passes_npcs() {
+        assert!(CombatEventInfo::npc_event(&npc(BOSS), None, &cast(SKILL)));
+        assert!(!CombatEventInfo::npc_event(&player(), None, &cast(SKILL)));
+        assert!(!CombatEventInfo::npc_event(
+            &npc(BOSS),
+            None,
+            &event(SKILL, |_| ())
+        ));
+    }
+
+    #[test]
+    fn events_are_seen_once() {
+        let mut seen = SeenEvents::default();
+        assert!(seen.first_sighting(7));
+        assert!(!seen.first_sighting(7));
+        assert!(seen.first_sighting(0));
+        assert!(seen.first_sighting(0));
+    }
+}
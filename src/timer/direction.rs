@@ -1,23 +1,34 @@
 use {
     super::BlishVec3,
-    glam::Vec3,
+    glam::{Mat4, Vec3},
     relative_path::RelativePathBuf,
     serde::{Deserialize, Serialize},
     tokio::time::{Duration, Instant},
 };
 
+fn default_anim_speed() -> f32 {
+    1.0
+}
+
+fn default_duration() -> f32 {
+    10.0
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BlishDirection {
-    name: String,
-    destination: BlishVec3,
-    texture: RelativePathBuf,
-    anim_speed: f32,
-    duration: f32,
-    timestamps: Vec<f32>,
+    #[serde(default)]
+    pub name: String,
+    pub destination: BlishVec3,
+    pub texture: RelativePathBuf,
+    #[serde(default = "default_anim_speed")]
+    pub anim_speed: f32,
+    #[serde(default = "default_duration")]
+    pub duration: f32,
+    #[serde(default)]
+    pub timestamps: Vec<f32>,
 }
 
-#[allow(dead_code)]
 impl BlishDirection {
     fn direction(&self, timestamp: f32) -> TimerDirection {
         let destination = self.destination.to_vec3();
@@ -40,17 +51,15 @@ impl BlishDirection {
 }
 
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 pub struct TimerDirection {
-    name: String,
-    destination: Vec3,
-    texture: RelativePathBuf,
-    anim_speed: f32,
-    duration: f32,
-    timestamp: f32,
+    pub name: String,
+    pub destination: Vec3,
+    pub texture: RelativePathBuf,
+    pub anim_speed: f32,
+    pub duration: f32,
+    pub timestamp: f32,
 }
 
-#[allow(dead_code)]
 impl TimerDirection {
     pub fn raw_timestamp(&self) -> Duration {
         Duration::from_secs_f32(self.timestamp)
@@ -69,7 +78,118 @@ impl TimerDirection {
     pub fn start(&self, start: Instant) -> Instant {
         start + self.timestamp()
     }
+    #[allow(dead_code)]
     pub fn remaining(&self, start: Instant) -> Duration {
         self.end(start).saturating_duration_since(Instant::now())
     }
+    pub fn is_active(&self, start: Instant) -> bool {
+        let now = Instant::now();
+        now >= self.start(start) && now < self.end(start)
+    }
+
+    /*
+     * The direction a player at `from` needs to head in to reach the destination.
+     * Zero if they are already standing on it.
+     */
+    pub fn heading(&self, from: Vec3) -> Vec3 {
+        (self.destination - from).normalize_or_zero()
+    }
+    pub fn distance(&self, from: Vec3) -> f32 {
+        self.destination.distance(from)
+    }
+
+    /*
+     * Where the arrow should be drawn: a little in front of the player along the heading,
+     * but never past the destination itself.
+     */
+    pub fn arrow_position(&self, from: Vec3, offset: f32) -> Vec3 {
+        let offset = offset.min(self.distance(from));
+        from + self.heading(from) * offset
+    }
+
+    pub fn yaw(&self, from: Vec3) -> f32 {
+        let heading = self.heading(from);
+        heading.x.atan2(heading.z)
+    }
+
+    // the arrow texture points up its own y axis, so lay it flat on the ground;
+    // the yaw towards the destination is applied per frame
+    pub fn model_matrix() -> Mat4 {
+        Mat4::from_rotation_x(-90.0f32.to_radians())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        serde_json::json,
+        std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI},
+    };
+
+    fn towards(x: f32, y: f32, z: f32) -> TimerDirection {
+        TimerDirection {
+            name: String::new(),
+            destination: Vec3::new(x, y, z),
+            texture: RelativePathBuf::from("arrow.png"),
+            anim_speed: 1.0,
+            duration: 5.0,
+            timestamp: 10.0,
+        }
+    }
+
+    #[test]
+    fn schedule() {
+        let direction: BlishDirection = serde_json::from_value(json!({
+            "destination": [1, 2, 3],
+            "texture": "arrow.png",
+            "duration": 5,
+            "timestamps": [10, 30, 2]
+        }))
+        .unwrap();
+        let directions = direction.get_directions();
+        // blish has z up
+        assert_eq!(directions[0].destination, Vec3::new(1.0, 3.0, 2.0));
+        // shown for the duration leading up to each timestamp, never before the phase starts
+        let starts: Vec<_> = directions.iter().map(TimerDirection::timestamp).collect();
+        assert_eq!(
+            starts,
+            [
+                Duration::from_secs(5),
+                Duration::from_secs(25),
+                Duration::ZERO
+            ]
+        );
+
+        let start = Instant::now() - Duration::from_secs(7);
+        assert!(directions[0].is_active(start));
+        assert!(!directions[1].is_active(start));
+        assert!(!directions[2].is_active(start));
+        assert_eq!(directions[0].end(start), start + Duration::from_secs(10));
+    }
+
+    #[test]
+    fn yaw_per_quadrant() {
+        let cases = [
+            ((0.0, 10.0), 0.0),
+            ((10.0, 10.0), FRAC_PI_4),
+            ((10.0, 0.0), FRAC_PI_2),
+            ((10.0, -10.0), 3.0 * FRAC_PI_4),
+            ((0.0, -10.0), PI),
+            ((-10.0, -10.0), -3.0 * FRAC_PI_4),
+            ((-10.0, 0.0), -FRAC_PI_2),
+            ((-10.0, 10.0), -FRAC_PI_4),
+        ];
+        let from = Vec3::new(100.0, 20.0, -50.0);
+        for ((x, z), yaw) in cases {
+            // the height difference shouldn't matter
+            let direction = towards(from.x + x, from.y + 30.0, from.z + z);
+            let actual = direction.yaw(from);
+            assert!(
+                (actual - yaw).abs() < 1e-5,
+                "({x}, {z}): {actual} isn't {yaw}"
+            );
+        }
+        assert_eq!(towards(100.0, 20.0, -50.0).yaw(from), 0.0);
+    }
 }
@@ -1,7 +1,7 @@
 use {
     crate::{
-        settings::RemoteSource,
-        timer::{TimerPhase, TimerTrigger},
+        settings::{RemoteSource, Source},
+        timer::{TimerPack, TimerPhase, TimerTrigger},
     },
     anyhow::anyhow,
     glob::Paths,
@@ -11,9 +11,16 @@ use {
         path::{Path, PathBuf},
         sync::Arc,
     },
-    tokio::{fs::read_to_string, sync::Semaphore, task::JoinSet},
+    tokio::{
+        fs::{read_to_string, try_exists},
+        sync::Semaphore,
+        task::JoinSet,
+    },
 };
 
+// for the timers in the ad-hoc folder, which don't belong to any source
+pub const ADHOC_SOURCE_KEY: &str = "adhoc";
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TimerFile {
@@ -31,6 +38,11 @@ pub struct TimerFile {
     pub map_id: u32,
     pub reset: TimerTrigger,
     pub phases: Vec<TimerPhase>,
+    // from the pack manifest, if there is one
+    #[serde(skip)]
+    pub icon_dir: Option<PathBuf>,
+    #[serde(skip)]
+    pub default_disabled: bool,
 }
 
 impl TimerFile {
@@ -54,13 +66,33 @@ impl TimerFile {
     pub async fn load(
         path: &PathBuf,
         source: Option<Arc<RemoteSource>>,
+        pack: Option<&TimerPack>,
     ) -> anyhow::Result<Arc<Self>> {
         log::debug!("Attempting to load the timer file at \"{path:?}\".");
         let mut file_data = read_to_string(path).await?;
         json_strip_comments::strip(&mut file_data)?;
-        let mut data: Self = serde_json::from_str(&file_data)?;
+        let deserializer = &mut serde_json::Deserializer::from_str(&file_data);
+        let mut data: Self = serde_path_to_error::deserialize(deserializer)
+            .map_err(|err| anyhow!("{path:?} at {}: {}", err.path(), err.inner()))?;
         data.path = Some(path.to_path_buf());
         data.association = source;
+        data.number_triggers();
+        if let Some(pack) = pack {
+            data.default_disabled = pack.disabled_by_default(&data.id);
+            // icons next to the timer file win over the pack's shared ones
+            let beside = match path.parent() {
+                Some(dir) => try_exists(data.icon.to_path(dir)).await.unwrap_or(false),
+                None => false,
+            };
+            if !beside {
+                data.icon_dir = pack.icons_dir();
+            }
+        }
+        let diagnostics = data.validate();
+        let missing = data.missing_files_async().await;
+        for diagnostic in diagnostics.iter().chain(&missing) {
+            log::warn!("Timer file at \"{path:?}\": {diagnostic}");
+        }
         log::debug!("Successfully loaded the timer file at \"{path:?}\".");
         Ok(Arc::new(data))
     }
@@ -77,7 +109,7 @@ impl TimerFile {
             let permit = semaphore.clone().acquire_owned().await?;
             let path = path?.clone();
             set.spawn(async move {
-                let timer_file = Self::load(&path, None).await?;
+                let timer_file = Self::load(&path, None, None).await?;
                 drop(permit);
                 Ok::<Arc<TimerFile>, anyhow::Error>(timer_file)
             });
@@ -109,20 +141,20 @@ impl TimerFile {
     }
 
     pub async fn load_many(
-        load_dir: &Path,
+        pack: Arc<TimerPack>,
         source: Arc<RemoteSource>,
         simultaneous_limit: usize,
     ) -> anyhow::Result<Vec<Arc<Self>>> {
+        let load_dir = &pack.root;
         log::debug!("Beginning load_many for {load_dir:?} with a simultaneous open limit of {simultaneous_limit}.");
         let mut set = JoinSet::new();
         let semaphore = Arc::new(Semaphore::new(simultaneous_limit));
-        let mut paths = Self::get_paths(load_dir)?;
-        while let Some(path) = paths.next() {
+        for path in pack.paths() {
             let permit = semaphore.clone().acquire_owned().await?;
-            let path = path?.clone();
             let source = source.clone();
+            let pack = pack.clone();
             set.spawn(async move {
-                let timer_file = Self::load(&path, Some(source)).await?;
+                let timer_file = Self::load(&path, Some(source), Some(&pack)).await?;
                 drop(permit);
                 Ok::<Arc<TimerFile>, anyhow::Error>(timer_file)
             });
@@ -153,6 +185,31 @@ impl TimerFile {
         Ok(timer_files)
     }
 
+    /*
+     * Sequence progress is kept against these, so they come from where each
+     * trigger sits in the file and stay put however the timer's copied about.
+     */
+    pub fn number_triggers(&mut self) {
+        let mut next = 0;
+        self.reset.number(&mut next);
+        for phase in &mut self.phases {
+            phase.start.number(&mut next);
+            if let Some(finish) = &mut phase.finish {
+                finish.number(&mut next);
+            }
+            for action in &mut phase.actions {
+                action.trigger.number(&mut next);
+            }
+        }
+    }
+
+    // where the icon path is relative to
+    pub fn icon_base(&self) -> Option<&Path> {
+        self.icon_dir
+            .as_deref()
+            .or_else(|| self.path.as_deref()?.parent())
+    }
+
     pub fn name(&self) -> String {
         self.name.replace("\n", " ")
     }
@@ -162,9 +219,16 @@ impl TimerFile {
     pub fn subtitle(&self) -> Option<String> {
         Some(self.name.split_once('\n')?.1.replace("\n", " - "))
     }
+    // what the timer's settings are kept under, ids only being unique per source
+    pub fn source_key(&self) -> String {
+        match &self.association {
+            Some(s) => s.install_dir(),
+            None => ADHOC_SOURCE_KEY.to_string(),
+        }
+    }
     pub fn source(&self) -> String {
         match &self.association {
-            Some(s) => s.source().to_string(),
+            Some(s) => s.to_string(),
             None => "".to_string(),
         }
     }
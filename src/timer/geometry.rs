@@ -4,10 +4,13 @@ use {
         swizzles::*,
     },
     serde::{Deserialize, Serialize},
-    std::cmp::Ordering,
+    std::{
+        cmp::Ordering,
+        hash::{Hash, Hasher},
+    },
 };
 
-#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq)]
 #[serde(transparent)]
 pub struct BlishVec3 {
     child: Vec3,
@@ -210,13 +213,67 @@ impl PartialOrd<Vec2> for Position {
 
 // one day someone is going to look at this and think i'm deranged
 // And that's Ok! they're right, i am :)
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Polytope {
-    NSphere { center: Position, radius: f32 },
-    NCuboid { pode: Position, antipode: Position },
+    NSphere {
+        center: Position,
+        radius: f32,
+    },
+    NCuboid {
+        pode: Position,
+        antipode: Position,
+    },
+    // upright, so only the xz of the center matters; unbounded if no bottom/top
+    Cylinder {
+        center: Position,
+        radius: f32,
+        bottom: Option<f32>,
+        top: Option<f32>,
+    },
+    // a box turned by yaw radians about the up axis
+    OrientedCuboid {
+        center: Vec3,
+        half_extents: Vec3,
+        yaw: f32,
+    },
+    // a polygon on the xz plane, extruded upwards
+    Prism {
+        points: Vec<Vec2>,
+        bottom: Option<f32>,
+        top: Option<f32>,
+    },
 }
 
 impl Polytope {
+    fn height_is_within(player: Position, bottom: Option<f32>, top: Option<f32>) -> bool {
+        match player {
+            // no height to go on, so we give them the benefit of the doubt
+            Position::Vec2(_) => true,
+            Position::Vec3(vec) => {
+                bottom.is_none_or(|bottom| vec.y >= bottom) && top.is_none_or(|top| vec.y <= top)
+            }
+        }
+    }
+
+    // even-odd ray casting, points on the edge may go either way
+    fn polygon_contains(points: &[Vec2], point: Vec2) -> bool {
+        if points.len() < 3 {
+            return false;
+        }
+        let mut inside = false;
+        let mut j = points.len() - 1;
+        for i in 0..points.len() {
+            let (a, b) = (points[i], points[j]);
+            if (a.y > point.y) != (b.y > point.y)
+                && point.x < (b.x - a.x) * (point.y - a.y) / (b.y - a.y) + a.x
+            {
+                inside = !inside;
+            }
+            j = i;
+        }
+        inside
+    }
+
     pub fn point_is_within(&self, player: Position) -> bool {
         match self {
             Polytope::NSphere { radius, center } => {
@@ -228,6 +285,192 @@ impl Polytope {
                 let maxes = pode.max(*antipode);
                 player >= mins && player <= maxes
             }
+            Polytope::Cylinder {
+                center,
+                radius,
+                bottom,
+                top,
+            } => {
+                let flat: Vec2 = player.into();
+                let flat_center: Vec2 = (*center).into();
+                flat.distance(flat_center) < *radius
+                    && Self::height_is_within(player, *bottom, *top)
+            }
+            Polytope::OrientedCuboid {
+                center,
+                half_extents,
+                yaw,
+            } => {
+                // turn the player into the box's frame of reference instead of turning the box
+                let offset = Vec2::from(player) - center.xz();
+                let (sin, cos) = yaw.sin_cos();
+                let local = Vec2::new(
+                    offset.x * cos + offset.y * sin,
+                    -offset.x * sin + offset.y * cos,
+                );
+                let flat_check = local.x.abs() <= half_extents.x && local.y.abs() <= half_extents.z;
+                flat_check
+                    && Self::height_is_within(
+                        player,
+                        Some(center.y - half_extents.y),
+                        Some(center.y + half_extents.y),
+                    )
+            }
+            Polytope::Prism {
+                points,
+                bottom,
+                top,
+            } => {
+                Self::polygon_contains(points, player.into())
+                    && Self::height_is_within(player, *bottom, *top)
+            }
+        }
+    }
+}
+
+/*
+ * How areas are written down in timer and marker files: Blish coordinates,
+ * degrees, with a "shape" tag.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "shape", rename_all = "camelCase")]
+pub enum BlishArea {
+    #[serde(rename_all = "camelCase")]
+    Sphere { position: BlishVec3, radius: f32 },
+    #[serde(rename_all = "camelCase")]
+    Cuboid {
+        position: BlishVec3,
+        antipode: BlishVec3,
+    },
+    #[serde(rename_all = "camelCase")]
+    Cylinder {
+        position: BlishVec3,
+        radius: f32,
+        #[serde(default)]
+        bottom: Option<f32>,
+        #[serde(default)]
+        top: Option<f32>,
+    },
+    #[serde(rename_all = "camelCase")]
+    OrientedCuboid {
+        position: BlishVec3,
+        // full width, depth and height, not half
+        size: BlishVec3,
+        #[serde(default)]
+        yaw: f32,
+    },
+    #[serde(rename_all = "camelCase")]
+    Polygon {
+        // x and y, as in Blish, the height is z
+        points: Vec<Vec2>,
+        #[serde(default)]
+        bottom: Option<f32>,
+        #[serde(default)]
+        top: Option<f32>,
+    },
+}
+
+// marker sets live in hash sets, so this has to be too; same deal as Position
+impl Eq for BlishArea {}
+
+impl Hash for BlishArea {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let floats: Vec<f32> = match self {
+            Self::Sphere { position, radius } => position
+                .child
+                .to_array()
+                .into_iter()
+                .chain([*radius])
+                .collect(),
+            Self::Cuboid { position, antipode } => position
+                .child
+                .to_array()
+                .into_iter()
+                .chain(antipode.child.to_array())
+                .collect(),
+            Self::Cylinder {
+                position,
+                radius,
+                bottom,
+                top,
+            } => position
+                .child
+                .to_array()
+                .into_iter()
+                .chain([*radius])
+                .chain(*bottom)
+                .chain(*top)
+                .collect(),
+            Self::OrientedCuboid {
+                position,
+                size,
+                yaw,
+            } => position
+                .child
+                .to_array()
+                .into_iter()
+                .chain(size.child.to_array())
+                .chain([*yaw])
+                .collect(),
+            Self::Polygon {
+                points,
+                bottom,
+                top,
+            } => points
+                .iter()
+                .flat_map(|p| p.to_array())
+                .chain(*bottom)
+                .chain(*top)
+                .collect(),
+        };
+        std::mem::discriminant(self).hash(state);
+        for float in floats {
+            float.to_bits().hash(state);
+        }
+    }
+}
+
+impl BlishArea {
+    pub fn polytope(&self) -> Polytope {
+        match self {
+            Self::Sphere { position, radius } => Polytope::NSphere {
+                center: position.to_vec3().into(),
+                radius: *radius,
+            },
+            Self::Cuboid { position, antipode } => Polytope::NCuboid {
+                pode: position.to_vec3().into(),
+                antipode: antipode.to_vec3().into(),
+            },
+            Self::Cylinder {
+                position,
+                radius,
+                bottom,
+                top,
+            } => Polytope::Cylinder {
+                center: position.to_vec3().into(),
+                radius: *radius,
+                bottom: *bottom,
+                top: *top,
+            },
+            Self::OrientedCuboid {
+                position,
+                size,
+                yaw,
+            } => Polytope::OrientedCuboid {
+                center: position.to_vec3(),
+                half_extents: size.to_vec3().abs() / 2.0,
+                yaw: yaw.to_radians(),
+            },
+            // blish's x and y are our x and z, which is what a Vec2 position already is
+            Self::Polygon {
+                points,
+                bottom,
+                top,
+            } => Polytope::Prism {
+                points: points.clone(),
+                bottom: *bottom,
+                top: *top,
+            },
         }
     }
 }
@@ -246,3 +489,133 @@ impl From<BlishPosition> for Position {
         pos.to_sane()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    // ours, so y is up
+    fn at(x: f32, y: f32, z: f32) -> Position {
+        Position::Vec3(Vec3::new(x, y, z))
+    }
+
+    fn area(value: serde_json::Value) -> Polytope {
+        serde_json::from_value::<BlishArea>(value)
+            .unwrap()
+            .polytope()
+    }
+
+    #[test]
+    fn cylinder() {
+        let cylinder = Polytope::Cylinder {
+            center: at(0.0, 0.0, 0.0),
+            radius: 10.0,
+            bottom: Some(0.0),
+            top: Some(20.0),
+        };
+        assert!(cylinder.point_is_within(at(5.0, 10.0, -5.0)));
+        assert!(!cylinder.point_is_within(at(8.0, 10.0, 8.0)));
+        assert!(!cylinder.point_is_within(at(0.0, 25.0, 0.0)));
+        assert!(!cylinder.point_is_within(at(0.0, -1.0, 0.0)));
+        // no height, no reason to say no
+        assert!(cylinder.point_is_within(Position::Vec2(Vec2::new(5.0, 5.0))));
+
+        let endless = Polytope::Cylinder {
+            center: at(0.0, 0.0, 0.0),
+            radius: 10.0,
+            bottom: None,
+            top: None,
+        };
+        assert!(endless.point_is_within(at(0.0, 10_000.0, 0.0)));
+    }
+
+    #[test]
+    fn oriented_cuboid() {
+        let straight = Polytope::OrientedCuboid {
+            center: Vec3::new(0.0, 10.0, 0.0),
+            half_extents: Vec3::new(10.0, 3.0, 1.0),
+            yaw: 0.0,
+        };
+        assert!(straight.point_is_within(at(9.0, 10.0, 0.5)));
+        assert!(!straight.point_is_within(at(0.0, 10.0, 5.0)));
+        assert!(!straight.point_is_within(at(0.0, 14.0, 0.0)));
+
+        // long side now points 30 degrees round from x towards z
+        let (sin, cos) = 30f32.to_radians().sin_cos();
+        let turned = Polytope::OrientedCuboid {
+            center: Vec3::new(0.0, 10.0, 0.0),
+            half_extents: Vec3::new(10.0, 3.0, 1.0),
+            yaw: 30f32.to_radians(),
+        };
+        assert!(turned.point_is_within(at(8.0 * cos, 10.0, 8.0 * sin)));
+        assert!(!turned.point_is_within(at(8.0 * cos, 10.0, -8.0 * sin)));
+        assert!(!turned.point_is_within(at(9.0, 10.0, 0.0)));
+    }
+
+    #[test]
+    fn concave_prism() {
+        // a U, open at the top
+        let prism = Polytope::Prism {
+            points: vec![
+                Vec2::new(0.0, 0.0),
+                Vec2::new(30.0, 0.0),
+                Vec2::new(30.0, 30.0),
+                Vec2::new(20.0, 30.0),
+                Vec2::new(20.0, 10.0),
+                Vec2::new(10.0, 10.0),
+                Vec2::new(10.0, 30.0),
+                Vec2::new(0.0, 30.0),
+            ],
+            bottom: Some(0.0),
+            top: Some(5.0),
+        };
+        for (x, z) in [(5.0, 20.0), (25.0, 20.0), (15.0, 5.0)] {
+            assert!(prism.point_is_within(at(x, 1.0, z)), "{x}, {z}");
+        }
+        // in the gap between the arms, and off to the side
+        for (x, z) in [(15.0, 20.0), (35.0, 5.0), (-1.0, 5.0)] {
+            assert!(!prism.point_is_within(at(x, 1.0, z)), "{x}, {z}");
+        }
+        assert!(!prism.point_is_within(at(5.0, 6.0, 20.0)));
+    }
+
+    #[test]
+    fn areas_from_json() {
+        // blish has z up, so this is 100 high
+        let sphere = area(json!({ "shape": "sphere", "position": [1, 2, 100], "radius": 5 }));
+        assert!(sphere.point_is_within(at(1.0, 100.0, 2.0)));
+        assert!(!sphere.point_is_within(at(1.0, 2.0, 100.0)));
+
+        let cylinder = area(json!({
+            "shape": "cylinder",
+            "position": [50, 60, 0],
+            "radius": 5,
+            "bottom": 10,
+            "top": 20
+        }));
+        assert!(cylinder.point_is_within(at(52.0, 15.0, 61.0)));
+        assert!(!cylinder.point_is_within(at(52.0, 25.0, 61.0)));
+
+        // 20 along blish x, 4 along blish y, 6 high, turned 30 degrees from x towards y
+        let turned = area(json!({
+            "shape": "orientedCuboid",
+            "position": [100, 50, 10],
+            "size": [20, 4, 6],
+            "yaw": 30
+        }));
+        let (sin, cos) = 30f32.to_radians().sin_cos();
+        assert!(turned.point_is_within(at(100.0 + 8.0 * cos, 12.0, 50.0 + 8.0 * sin)));
+        assert!(!turned.point_is_within(at(100.0 + 8.0 * cos, 12.0, 50.0 - 8.0 * sin)));
+        assert!(!turned.point_is_within(at(100.0 + 8.0 * cos, 14.0, 50.0 + 8.0 * sin)));
+
+        let polygon = area(json!({
+            "shape": "polygon",
+            "points": [[0, 0], [10, 0], [0, 10]],
+            "bottom": -5,
+            "top": 5
+        }));
+        assert!(polygon.point_is_within(at(2.0, 0.0, 2.0)));
+        assert!(!polygon.point_is_within(at(8.0, 0.0, 8.0)));
+        assert!(!polygon.point_is_within(at(2.0, 6.0, 2.0)));
+    }
+}
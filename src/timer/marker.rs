@@ -68,6 +68,8 @@ impl BlishMarker {
 pub enum RotationType {
     Rotation(Vec3),
     Billboard,
+    // a yaw around the world's up axis, updated as the player moves
+    Heading(f32),
 }
 
 #[derive(Clone)]
@@ -119,6 +121,7 @@ impl TimerMarker {
                     * Mat4::from_rotation_y(rot.y)
                     * Mat4::from_rotation_z(rot.z)
             }
+            RotationType::Heading(_) => Mat4::IDENTITY,
         };
         // then move them
         //let mtx_position = Mat4::from_translation(self.position);
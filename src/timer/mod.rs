@@ -1,23 +1,38 @@
 pub mod action;
 pub mod alert;
 pub mod blishcolour;
+pub mod combat;
 pub mod direction;
 pub mod file;
 pub mod geometry;
 pub mod marker;
+pub mod pack;
 pub mod phase;
+#[cfg(test)]
+pub mod simulation;
+pub mod sound;
 pub mod state_machine;
 pub mod trigger;
+pub mod validate;
+pub mod watch;
 
 #[allow(unused_imports)]
 pub use {
     action::{TimerAction, TimerActionType},
     alert::{BlishAlert, TimerAlert, TimerAlertType},
     blishcolour::BlishColour,
+    combat::{CombatEventInfo, CombatEventKind, CombatMatcher},
+    direction::{BlishDirection, TimerDirection},
     file::TimerFile,
-    geometry::{BlishPosition, BlishVec3, Polytope, Position},
+    geometry::{BlishArea, BlishPosition, BlishVec3, Polytope, Position},
     marker::{BlishMarker, RotationType, TimerMarker},
+    pack::{PackManifest, TimerPack, MANIFEST_NAME},
     phase::TimerPhase,
+    sound::{BlishSound, TimerSound},
     state_machine::{PhaseState, TextAlert, TimerKeybinds, TimerMachine},
-    trigger::{CombatState, TimerTrigger, TimerTriggerType},
+    trigger::{
+        CombatState, MapArrival, Mount, Profession, TimerTrigger, TimerTriggerType, TriggerState,
+    },
+    validate::{lint_dir, lint_file, Diagnostic, FileReport, Severity},
+    watch::TimerWatcher,
 };
@@ -0,0 +1,344 @@
+use {
+    crate::timer::{TimerFile, TimerWatcher},
+    anyhow::anyhow,
+    relative_path::RelativePathBuf,
+    serde::{Deserialize, Serialize},
+    std::{
+        cmp::Ordering,
+        collections::HashSet,
+        path::{Component, Path, PathBuf},
+    },
+    tokio::fs::{read_to_string, try_exists},
+};
+
+pub const MANIFEST_NAME: &str = "taimi-pack.toml";
+
+fn default_include() -> Vec<String> {
+    vec![TimerFile::glob()]
+}
+
+/*
+ * An optional taimi-pack.toml at the root of a source. Without one, a
+ * source is every .bhtimer file in it, all enabled, same as it always was.
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct PackManifest {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    // packs that need newer features can refuse to load on older addons
+    #[serde(default)]
+    pub min_taimi_version: Option<String>,
+    // globs, relative to the pack, of the timer files to load
+    #[serde(default = "default_include")]
+    pub include: Vec<String>,
+    // where icons are looked for when they aren't next to the timer file
+    #[serde(default)]
+    pub icons: Option<RelativePathBuf>,
+    // timer ids that start out disabled, for experimental or niche timers
+    #[serde(default)]
+    pub disabled: Vec<String>,
+}
+
+impl Default for PackManifest {
+    fn default() -> Self {
+        Self {
+            name: None,
+            version: None,
+            min_taimi_version: None,
+            include: default_include(),
+            icons: None,
+            disabled: Default::default(),
+        }
+    }
+}
+
+// the numeric bits of a version, so "1.2" and "1.2.0-rc1" both come out as 1.2.0
+fn version_parts(version: &str) -> Vec<u64> {
+    let version = version.trim().trim_start_matches('v');
+    let version = version.split(['-', '+']).next().unwrap_or_default();
+    let mut parts: Vec<u64> = version
+        .split('.')
+        .map(|part| part.parse().unwrap_or_default())
+        .collect();
+    while parts.last() == Some(&0) {
+        parts.pop();
+    }
+    parts
+}
+
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    version_parts(a).cmp(&version_parts(b))
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimerPack {
+    pub root: PathBuf,
+    pub manifest: PackManifest,
+    // whether the manifest came from a file, or is just the defaults
+    pub has_manifest: bool,
+}
+
+impl TimerPack {
+    pub async fn open(root: &Path) -> anyhow::Result<Self> {
+        let manifest_path = root.join(MANIFEST_NAME);
+        let data = match try_exists(&manifest_path).await? {
+            true => Some(read_to_string(&manifest_path).await?),
+            false => None,
+        };
+        Self::from_manifest(root, data.as_deref())
+    }
+
+    // for the linter, which has no runtime to wait on
+    pub fn open_blocking(root: &Path) -> anyhow::Result<Self> {
+        let manifest_path = root.join(MANIFEST_NAME);
+        let data = match std::fs::exists(&manifest_path)? {
+            true => Some(std::fs::read_to_string(&manifest_path)?),
+            false => None,
+        };
+        Self::from_manifest(root, data.as_deref())
+    }
+
+    fn from_manifest(root: &Path, data: Option<&str>) -> anyhow::Result<Self> {
+        let manifest = match data {
+            Some(data) => toml::from_str(data).map_err(|err| {
+                anyhow!(
+                    "{:?} isn't a valid manifest: {err}",
+                    root.join(MANIFEST_NAME)
+                )
+            })?,
+            None => Default::default(),
+        };
+        Ok(Self {
+            root: root.to_path_buf(),
+            manifest,
+            has_manifest: data.is_some(),
+        })
+    }
+
+    // the TaimiHUD version this pack wants, if this isn't it
+    pub fn unmet_requirement(&self) -> Option<&str> {
+        let required = self.manifest.min_taimi_version.as_deref()?;
+        match compare_versions(env!("CARGO_PKG_VERSION"), required) {
+            Ordering::Less => Some(required),
+            _ => None,
+        }
+    }
+
+    fn stays_inside(glob: &str) -> bool {
+        Path::new(glob)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    }
+
+    // a bad include only costs the timers it would've found, not the pack
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut seen = HashSet::new();
+        let mut paths = Vec::new();
+        for include in &self.manifest.include {
+            if !Self::stays_inside(include) {
+                log::warn!(
+                    "Ignoring the include {include:?} in {:?}, it leaves the pack.",
+                    self.root
+                );
+                continue;
+            }
+            let pattern = self.root.join(include);
+            let Some(pattern) = pattern.to_str() else {
+                log::warn!(
+                    "Ignoring the include {include:?} in {:?}, its path isn't unicode.",
+                    self.root
+                );
+                continue;
+            };
+            let entries = match glob::glob(pattern) {
+                Ok(entries) => entries,
+                Err(err) => {
+                    log::warn!(
+                        "Ignoring the include {include:?} in {:?}, it isn't a valid glob: {err}",
+                        self.root
+                    );
+                    continue;
+                }
+            };
+            for entry in entries {
+                let path = match entry {
+                    Ok(path) => path,
+                    Err(err) => {
+                        log::warn!(
+                            "Skipping part of the include {include:?} in {:?}: {err}",
+                            self.root
+                        );
+                        continue;
+                    }
+                };
+                if TimerWatcher::is_timer_file(&path) && seen.insert(path.clone()) {
+                    paths.push(path);
+                }
+            }
+        }
+        paths
+    }
+
+    // whether a file, say one that just changed on disk, is part of the pack
+    pub fn includes(&self, path: &Path) -> bool {
+        let relative = match path.strip_prefix(&self.root) {
+            Ok(relative) => relative.to_path_buf(),
+            // the watcher's paths are resolved, the root may not be
+            Err(_) => {
+                let resolved = self
+                    .root
+                    .canonicalize()
+                    .ok()
+                    .and_then(|root| path.strip_prefix(root).ok().map(Path::to_path_buf));
+                match resolved {
+                    Some(relative) => relative,
+                    None => return false,
+                }
+            }
+        };
+        self.manifest
+            .include
+            .iter()
+            .filter(|include| Self::stays_inside(include))
+            .filter_map(|include| glob::Pattern::new(include).ok())
+            .any(|pattern| pattern.matches_path(&relative))
+    }
+
+    pub fn icons_dir(&self) -> Option<PathBuf> {
+        let icons = self.manifest.icons.as_ref()?;
+        // logical, so the icons can't be anywhere outside of the pack
+        Some(icons.to_logical_path(&self.root))
+    }
+
+    pub fn disabled_by_default(&self, id: &str) -> bool {
+        self.manifest.disabled.iter().any(|d| d == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::fs::{create_dir_all, write},
+    };
+
+    // a pack with a timer at each of these, relative to the pack
+    fn pack(manifest: Option<&str>, timers: &[&str]) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("pack");
+        create_dir_all(&root).unwrap();
+        for timer in timers {
+            let path = root.join(timer);
+            create_dir_all(path.parent().unwrap()).unwrap();
+            write(path, "{}").unwrap();
+        }
+        if let Some(manifest) = manifest {
+            write(root.join(MANIFEST_NAME), manifest).unwrap();
+        }
+        (dir, root)
+    }
+
+    fn relative(pack: &TimerPack) -> Vec<String> {
+        let mut paths: Vec<String> = pack
+            .paths()
+            .iter()
+            .map(|p| {
+                let p = p.strip_prefix(&pack.root).unwrap();
+                p.to_string_lossy().replace('\\', "/")
+            })
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[tokio::test]
+    async fn reads_the_manifest() {
+        let (_dir, root) = pack(
+            Some(
+                r#"
+                name = "Raids"
+                version = "1.4.0"
+                include = ["raids/*.bhtimer"]
+                icons = "icons"
+                disabled = ["experimental"]
+                "#,
+            ),
+            &[],
+        );
+        let pack = TimerPack::open(&root).await.unwrap();
+        assert!(pack.has_manifest);
+        assert_eq!(pack.manifest.name.as_deref(), Some("Raids"));
+        assert_eq!(pack.manifest.include, ["raids/*.bhtimer"]);
+        assert_eq!(pack.icons_dir(), Some(root.join("icons")));
+        assert!(pack.disabled_by_default("experimental"));
+        assert!(!pack.disabled_by_default("vale-guardian"));
+        assert_eq!(TimerPack::open_blocking(&root).unwrap(), pack);
+    }
+
+    #[tokio::test]
+    async fn no_manifest_is_everything() {
+        let (_dir, root) = pack(None, &["a.bhtimer", "raids/b.bhtimer", "notes.txt"]);
+        let pack = TimerPack::open(&root).await.unwrap();
+        assert!(!pack.has_manifest);
+        assert_eq!(pack.manifest, PackManifest::default());
+        assert_eq!(relative(&pack), ["a.bhtimer", "raids/b.bhtimer"]);
+    }
+
+    #[test]
+    fn broken_manifests_are_errors() {
+        let (_dir, root) = pack(Some("include = 4"), &[]);
+        let err = TimerPack::open_blocking(&root).unwrap_err();
+        assert!(err.to_string().contains("isn't a valid manifest"), "{err}");
+    }
+
+    #[test]
+    fn includes_stay_inside_the_pack() {
+        let (dir, root) = pack(
+            Some(r#"include = ["../*.bhtimer", "/*.bhtimer", "*.bhtimer"]"#),
+            &["inside.bhtimer"],
+        );
+        write(dir.path().join("outside.bhtimer"), "{}").unwrap();
+        let pack = TimerPack::open_blocking(&root).unwrap();
+        assert_eq!(relative(&pack), ["inside.bhtimer"]);
+        assert!(!pack.includes(&dir.path().join("outside.bhtimer")));
+        assert!(pack.includes(&root.join("inside.bhtimer")));
+    }
+
+    #[test]
+    fn overlapping_includes_are_deduped() {
+        let (_dir, root) = pack(
+            Some(r#"include = ["**/*.bhtimer", "raids/*.bhtimer", "raids/w1.bhtimer"]"#),
+            &["raids/w1.bhtimer", "raids/w2.bhtimer", "fractals.bhtimer"],
+        );
+        let pack = TimerPack::open_blocking(&root).unwrap();
+        assert_eq!(pack.paths().len(), 3);
+        assert_eq!(
+            relative(&pack),
+            ["fractals.bhtimer", "raids/w1.bhtimer", "raids/w2.bhtimer"]
+        );
+    }
+
+    #[test]
+    fn invalid_globs_are_skipped() {
+        let (_dir, root) = pack(Some(r#"include = ["[abc", "*.bhtimer"]"#), &["a.bhtimer"]);
+        let pack = TimerPack::open_blocking(&root).unwrap();
+        assert_eq!(relative(&pack), ["a.bhtimer"]);
+    }
+
+    #[test]
+    fn minimum_versions() {
+        let mut pack = TimerPack::default();
+        assert_eq!(pack.unmet_requirement(), None);
+        pack.manifest.min_taimi_version = Some("999.0".to_string());
+        assert_eq!(pack.unmet_requirement(), Some("999.0"));
+        pack.manifest.min_taimi_version = Some("0.1".to_string());
+        assert_eq!(pack.unmet_requirement(), None);
+        pack.manifest.min_taimi_version = Some(format!("v{}", env!("CARGO_PKG_VERSION")));
+        assert_eq!(pack.unmet_requirement(), None);
+
+        assert_eq!(compare_versions("1.2", "1.2.0-rc1"), Ordering::Equal);
+        assert_eq!(compare_versions("1.10", "1.9.9"), Ordering::Greater);
+    }
+}
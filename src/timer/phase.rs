@@ -1,11 +1,10 @@
 use {
-    super::{direction::BlishDirection, BlishMarker, TimerMarker},
+    super::{BlishDirection, BlishMarker, BlishSound, TimerDirection, TimerMarker, TimerSound},
     crate::timer::{BlishAlert, TimerAction, TimerAlert, TimerTrigger},
     serde::{
         de::{self, Error as _, MapAccess, Visitor},
         Deserialize, Serialize,
     },
-    serde_json::Value,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -19,20 +18,12 @@ pub struct TimerPhase {
     pub alerts: Vec<BlishAlert>,
     #[serde(default)]
     pub actions: Vec<TimerAction>,
-    /*
-     * Not yet implemented:
-     * - directions
-     * - markers
-     * - sounds
-     */
-    #[serde(skip, default)]
-    #[allow(dead_code)]
-    directions: Vec<BlishDirection>,
+    #[serde(default)]
+    pub directions: Vec<BlishDirection>,
+    #[serde(default)]
+    pub sounds: Vec<BlishSound>,
     #[serde(flatten, default)]
     pub markers: BlishMarkers,
-    #[serde(skip, default)]
-    #[allow(dead_code)]
-    sounds: Value,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -54,6 +45,18 @@ impl TimerPhase {
             .flat_map(BlishMarker::get_markers)
             .collect()
     }
+    pub fn get_directions(&self) -> Vec<TimerDirection> {
+        self.directions
+            .iter()
+            .flat_map(BlishDirection::get_directions)
+            .collect()
+    }
+    pub fn get_sounds(&self) -> Vec<TimerSound> {
+        self.sounds
+            .iter()
+            .flat_map(BlishSound::get_sounds)
+            .collect()
+    }
 }
 
 #[derive(Serialize, Debug, Clone, Default)]
@@ -0,0 +1,341 @@
+use {
+    crate::{
+        audio::{RecordingSink, SoundCue},
+        render::RenderEvent,
+        timer::{
+            state_machine::{MachineEvent, MachineRecorder},
+            CombatState, Mount, Position, Profession, TimerAlert, TimerFile, TimerMachine,
+        },
+    },
+    anyhow::bail,
+    glam::Vec3,
+    serde::{Deserialize, Serialize},
+    std::{
+        fmt::{self, Display},
+        path::Path,
+        sync::{Arc, Mutex},
+    },
+    tokio::{
+        sync::mpsc::channel,
+        time::{sleep_until, Duration, Instant},
+    },
+};
+
+/*
+ * One moment of a recorded (or made up) run through an encounter.
+ *
+ * Positions are in the same space as the mumble avatar position the
+ * controller feeds the machines, not the swizzled Blish one.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationSample {
+    // seconds since the start of the simulation
+    pub time: f32,
+    pub position: Vec3,
+    #[serde(default)]
+    pub map_id: Option<u32>,
+    #[serde(default)]
+    pub combat: Option<CombatState>,
+    #[serde(default)]
+    pub mount: Option<Mount>,
+    #[serde(default)]
+    pub profession: Option<Profession>,
+    #[serde(default)]
+    pub keys_pressed: Vec<u32>,
+    #[serde(default)]
+    pub keys_released: Vec<u32>,
+}
+
+impl SimulationSample {
+    pub fn load(path: &Path) -> anyhow::Result<Vec<Self>> {
+        let mut data = std::fs::read_to_string(path)?;
+        json_strip_comments::strip(&mut data)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SimulationEvent {
+    Transition {
+        at: f32,
+        from: String,
+        to: String,
+    },
+    AlertFeed {
+        at: f32,
+        phase: String,
+        // when the phase counts as having started, which a skip moves about
+        start: f32,
+        alerts: Vec<TimerAlert>,
+    },
+    AlertReset {
+        at: f32,
+    },
+    Sound {
+        at: f32,
+        cue: SoundCue,
+    },
+    TextAlert {
+        at: f32,
+        message: String,
+    },
+}
+
+impl SimulationEvent {
+    pub fn at(&self) -> f32 {
+        use SimulationEvent::*;
+        match self {
+            Transition { at, .. }
+            | AlertFeed { at, .. }
+            | AlertReset { at }
+            | Sound { at, .. }
+            | TextAlert { at, .. } => *at,
+        }
+    }
+}
+
+// one line per event, which is what the .expected files next to pack fixtures hold
+impl Display for SimulationEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use SimulationEvent::*;
+        write!(f, "{:.1} ", self.at())?;
+        match self {
+            Transition { from, to, .. } => write!(f, "transition {from} -> {to}"),
+            AlertFeed {
+                phase,
+                start,
+                alerts,
+                ..
+            } => {
+                write!(f, "feed {phase}")?;
+                for alert in alerts {
+                    write!(f, ", {} at {:.1}", alert.text, start + alert.timestamp)?;
+                }
+                Ok(())
+            }
+            AlertReset { .. } => write!(f, "reset"),
+            Sound { cue, .. } => match cue.path.file_name() {
+                Some(file) => write!(f, "sound {}", file.to_string_lossy()),
+                None => write!(f, "sound {:?}", cue.path),
+            },
+            TextAlert { message, .. } => write!(f, "alert {message}"),
+        }
+    }
+}
+
+/*
+ * Drives a single TimerMachine through a list of samples on a paused tokio
+ * clock, so nothing here needs the game, the renderer or real time passing.
+ */
+pub struct Simulation {
+    timer: Arc<TimerFile>,
+    // how long to keep the clock running after the last sample, for trailing sounds
+    pub tail: Duration,
+}
+
+impl Simulation {
+    pub fn new(timer: Arc<TimerFile>) -> Self {
+        Self {
+            timer,
+            tail: Duration::ZERO,
+        }
+    }
+
+    pub fn with_tail(mut self, tail: Duration) -> Self {
+        self.tail = tail;
+        self
+    }
+
+    pub fn run(&self, samples: &[SimulationSample]) -> anyhow::Result<Vec<SimulationEvent>> {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .start_paused(true)
+            .build()?;
+        rt.block_on(self.simulate(samples))
+    }
+
+    async fn simulate(&self, samples: &[SimulationSample]) -> anyhow::Result<Vec<SimulationEvent>> {
+        let recorder = MachineRecorder::default();
+        let sink = Arc::new(RecordingSink::default());
+        // the text alerts, e.g. resets, come through the render channel
+        let (sender, mut receiver) = channel::<RenderEvent>(64);
+        let text_alerts: Arc<Mutex<Vec<(Instant, String)>>> = Default::default();
+        let collector = tokio::spawn({
+            let text_alerts = text_alerts.clone();
+            async move {
+                while let Some(event) = receiver.recv().await {
+                    if let RenderEvent::AlertStart(alert) = event {
+                        text_alerts
+                            .lock()
+                            .unwrap()
+                            .push((Instant::now(), alert.message));
+                    }
+                }
+            }
+        });
+        let mut machine = TimerMachine::new(self.timer.clone(), Default::default(), sender)
+            .with_recorder(recorder.clone())
+            .with_audio_sink(sink.clone());
+
+        let start = Instant::now();
+        let mut map_id = None;
+        let mut last_time = 0.0f32;
+        for sample in samples {
+            if sample.time < last_time {
+                bail!(
+                    "Simulation samples must be in order, {} comes after {}",
+                    sample.time,
+                    last_time
+                );
+            }
+            last_time = sample.time;
+            Self::advance_to(start + Duration::from_secs_f32(sample.time)).await;
+
+            let sample_map_id = sample.map_id.unwrap_or(self.timer.map_id);
+            if map_id != Some(sample_map_id) {
+                machine.update_on_map(sample_map_id);
+                machine.arrived_from(map_id);
+                map_id = Some(sample_map_id);
+            }
+            if let Some(combat) = sample.combat {
+                machine.set_combat_state(combat);
+            }
+            if sample.mount.is_some() {
+                machine.set_mount(sample.mount);
+            }
+            if sample.profession.is_some() {
+                machine.set_profession(sample.profession);
+            }
+            for &key in &sample.keys_pressed {
+                machine.key_event(key, false);
+            }
+            for &key in &sample.keys_released {
+                machine.key_event(key, true);
+            }
+            machine.tick(Position::Vec3(sample.position)).await;
+        }
+        Self::advance_to(Instant::now() + self.tail).await;
+        machine.cleanup().await;
+        drop(machine);
+        tokio::task::yield_now().await;
+        collector.abort();
+
+        let since = |at: Instant| at.saturating_duration_since(start).as_secs_f32();
+        // skipping ahead puts a phase's start before the simulation's
+        let signed = |at: Instant| match at.checked_duration_since(start) {
+            Some(after) => after.as_secs_f32(),
+            None => -start.duration_since(at).as_secs_f32(),
+        };
+        let mut events: Vec<SimulationEvent> = recorder
+            .lock()
+            .unwrap()
+            .drain(..)
+            .map(|(at, event)| match event {
+                MachineEvent::Transition { from, to } => SimulationEvent::Transition {
+                    at: since(at),
+                    from,
+                    to,
+                },
+                MachineEvent::Feed(ps) => SimulationEvent::AlertFeed {
+                    at: since(at),
+                    phase: ps.phase.name.clone(),
+                    start: signed(ps.start),
+                    alerts: ps.alerts,
+                },
+                MachineEvent::Reset(_) => SimulationEvent::AlertReset { at: since(at) },
+            })
+            .collect();
+        events.extend(
+            sink.played()
+                .into_iter()
+                .map(|(at, cue)| SimulationEvent::Sound { at: since(at), cue }),
+        );
+        events.extend(text_alerts.lock().unwrap().drain(..).map(|(at, message)| {
+            SimulationEvent::TextAlert {
+                at: since(at),
+                message,
+            }
+        }));
+        // stable, so things that happened at the same moment keep their order
+        events.sort_by(|a, b| a.at().total_cmp(&b.at()));
+        Ok(events)
+    }
+
+    /*
+     * The paused clock skips ahead to each timer in turn while we sleep, so
+     * sounds and alerts due before the target see the time they were due at.
+     */
+    async fn advance_to(target: Instant) {
+        sleep_until(target).await;
+        // let anything that woke up alongside us get a go before we carry on
+        tokio::task::yield_now().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{Simulation, SimulationSample},
+        crate::timer::TimerFile,
+        glam::Vec3,
+        std::{
+            fs::read_to_string,
+            path::{Path, PathBuf},
+            sync::Arc,
+        },
+        tokio::time::Duration,
+    };
+
+    fn pack_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/simulation")
+    }
+
+    /*
+     * Every timer in the fixture pack gets run through the samples beside it,
+     * and has to do exactly what its .expected file says, a line per event.
+     */
+    #[test]
+    fn pack_regressions() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let paths: Vec<PathBuf> = TimerFile::get_paths(&pack_dir())
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert!(!paths.is_empty(), "no timers in {:?}", pack_dir());
+        for path in paths {
+            let timer = rt.block_on(TimerFile::load(&path, None, None)).unwrap();
+            let samples = SimulationSample::load(&path.with_extension("samples.json")).unwrap();
+            let expected = read_to_string(path.with_extension("expected")).unwrap();
+            let events = Simulation::new(timer)
+                .with_tail(Duration::from_secs(2))
+                .run(&samples)
+                .unwrap();
+            let events: Vec<String> = events.iter().map(ToString::to_string).collect();
+            let expected: Vec<&str> = expected.lines().filter(|l| !l.trim().is_empty()).collect();
+            assert_eq!(events, expected, "{path:?}");
+        }
+    }
+
+    #[test]
+    fn samples_out_of_order() {
+        let path = pack_dir().join("fixture-boss.bhtimer");
+        let mut data = read_to_string(&path).unwrap();
+        json_strip_comments::strip(&mut data).unwrap();
+        let timer: TimerFile = serde_json::from_str(&data).unwrap();
+        let sample = |time| SimulationSample {
+            time,
+            position: Vec3::ZERO,
+            map_id: None,
+            combat: None,
+            mount: None,
+            profession: None,
+            keys_pressed: Vec::new(),
+            keys_released: Vec::new(),
+        };
+        let result = Simulation::new(Arc::new(timer)).run(&[sample(2.0), sample(1.0)]);
+        assert!(result.is_err());
+    }
+}
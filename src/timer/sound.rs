@@ -0,0 +1,66 @@
+use {
+    relative_path::RelativePathBuf,
+    serde::{Deserialize, Serialize},
+    std::path::{Path, PathBuf},
+    tokio::time::{Duration, Instant},
+};
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BlishSound {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub file: RelativePathBuf,
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+    #[serde(default)]
+    pub timestamps: Vec<f32>,
+}
+
+impl BlishSound {
+    fn sound(&self, timestamp: f32) -> TimerSound {
+        TimerSound {
+            name: self.name.clone(),
+            file: self.file.clone(),
+            volume: self.volume.clamp(0.0, 1.0),
+            timestamp,
+        }
+    }
+
+    pub fn get_sounds(&self) -> Vec<TimerSound> {
+        self.timestamps
+            .iter()
+            .map(|&timestamp| self.sound(timestamp))
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TimerSound {
+    pub name: Option<String>,
+    pub file: RelativePathBuf,
+    pub volume: f32,
+    pub timestamp: f32,
+}
+
+impl TimerSound {
+    pub fn timestamp(&self) -> Duration {
+        Duration::from_secs_f32(self.timestamp)
+    }
+
+    pub fn start(&self, start: Instant) -> Instant {
+        start + self.timestamp()
+    }
+
+    // sound files are relative to the timer file, same as marker textures
+    pub fn path(&self, timer_path: Option<&Path>) -> PathBuf {
+        match timer_path.and_then(Path::parent) {
+            Some(parent) => self.file.to_path(parent),
+            None => self.file.to_path(""),
+        }
+    }
+}
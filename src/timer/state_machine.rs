@@ -1,16 +1,25 @@
 use {
     super::TimerMarker,
     crate::{
+        audio::{audio_sink, AudioSink, SoundCue},
         render::RenderEvent,
-        timer::{CombatState, Position, TimerAlert, TimerFile, TimerPhase},
+        timer::{
+            CombatEventInfo, CombatState, MapArrival, Mount, Position, Profession, TimerAction,
+            TimerActionType, TimerAlert, TimerDirection, TimerFile, TimerPhase, TimerSound,
+            TriggerState,
+        },
         RENDER_SENDER,
     },
     bitflags::bitflags,
-    std::{fmt::Display, ops::Deref, sync::Arc},
+    std::{
+        fmt::Display,
+        ops::Deref,
+        sync::{Arc, Mutex as StdMutex},
+    },
     tokio::{
         sync::{mpsc::Sender, Mutex},
         task::JoinHandle,
-        time::{sleep, Duration, Instant},
+        time::{sleep, sleep_until, Duration, Instant},
     },
 };
 
@@ -50,7 +59,7 @@ impl EventMapper {
             Self::Feed(ps) => {
                 if let Some(settings) = SETTINGS.get().and_then(|settings| settings.try_read().ok())
                 {
-                    if settings.enable_katrender {
+                    if settings.config.enable_katrender {
                         let space_sender = SPACE_SENDER.get().unwrap();
                         let _ = space_sender.send(SpaceEvent::MarkerFeed(ps.clone())).await;
                         let _ = space_sender;
@@ -60,7 +69,7 @@ impl EventMapper {
             Self::Reset(tf) => {
                 if let Some(settings) = SETTINGS.get().and_then(|settings| settings.try_read().ok())
                 {
-                    if settings.enable_katrender {
+                    if settings.config.enable_katrender {
                         let space_sender = SPACE_SENDER.get().unwrap();
                         let _ = space_sender.send(SpaceEvent::MarkerReset(tf.clone())).await;
                         let _ = space_sender;
@@ -93,6 +102,19 @@ impl EventMapper {
     }
 }
 
+/*
+ * What a machine did, for when nobody's listening on the globals and we'd
+ * like to look at it afterwards instead, e.g. the simulation harness.
+ */
+#[derive(Debug, Clone)]
+pub enum MachineEvent {
+    Transition { from: String, to: String },
+    Feed(PhaseState),
+    Reset(Arc<TimerFile>),
+}
+
+pub type MachineRecorder = Arc<StdMutex<Vec<(Instant, MachineEvent)>>>;
+
 /*
 * A timer can be:
 * - existent without knowledge of current map
@@ -146,6 +168,11 @@ impl TimerFilePhase {
         }
     }
 
+    fn named(timer: Arc<TimerFile>, name: &str) -> Option<Self> {
+        let phase = timer.phases.iter().position(|p| p.name == name)?;
+        Some(Self { timer, phase })
+    }
+
     #[allow(dead_code)]
     fn reset(mut self) -> Self {
         self.phase = 0;
@@ -180,18 +207,24 @@ pub struct TimerMachine {
     pub timer: Arc<TimerFile>,
     alert_sem: Arc<Mutex<()>>,
     sender: Sender<RenderEvent>,
-    combat_state: CombatState,
     tasks: Vec<Arc<JoinHandle<()>>>,
-    key_pressed: TimerKeybinds,
+    triggers: TriggerState,
+    audio: Arc<dyn AudioSink>,
+    phase_start: Option<Instant>,
+    recorder: Option<MachineRecorder>,
+    // last trigger result per action of the current phase, actions fire on the rising edge
+    action_states: Vec<bool>,
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct PhaseState {
     pub timer: Arc<TimerFile>,
     pub start: Instant,
     pub phase: TimerFilePhase,
     pub alerts: Vec<TimerAlert>,
     pub markers: Vec<TimerMarker>,
+    pub directions: Vec<TimerDirection>,
+    pub sounds: Vec<TimerSound>,
 }
 
 #[derive(Clone)]
@@ -211,9 +244,66 @@ impl TimerMachine {
             timer,
             alert_sem,
             sender,
-            combat_state: CombatState::Outside,
             tasks: Default::default(),
-            key_pressed: Default::default(),
+            triggers: Default::default(),
+            audio: audio_sink(),
+            phase_start: None,
+            recorder: None,
+            action_states: Default::default(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_recorder(mut self, recorder: MachineRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    fn record(&self, event: MachineEvent) -> bool {
+        match &self.recorder {
+            Some(recorder) => {
+                recorder.lock().unwrap().push((Instant::now(), event));
+                true
+            }
+            None => false,
+        }
+    }
+
+    async fn emit(&self, event: EventMapper) {
+        let recorded = match &event {
+            EventMapper::Feed(ps) => MachineEvent::Feed(ps.clone()),
+            EventMapper::Reset(tf) => MachineEvent::Reset(tf.clone()),
+        };
+        if !self.record(recorded) {
+            event.send().await;
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_audio_sink(mut self, audio: Arc<dyn AudioSink>) -> Self {
+        self.audio = audio;
+        self
+    }
+
+    async fn play_sound(
+        audio: Arc<dyn AudioSink>,
+        timer: Arc<TimerFile>,
+        sound: TimerSound,
+        start: Instant,
+    ) {
+        sleep_until(sound.start(start)).await;
+        let path = sound.path(timer.path.as_deref());
+        log::info!(
+            "Playing {:?} at {}s for \"{}\"",
+            path,
+            sound.timestamp,
+            timer.name
+        );
+        if let Err(err) = audio.play(SoundCue {
+            path,
+            volume: sound.volume,
+        }) {
+            log::error!("Couldn't play sound for \"{}\": {err}", timer.name);
         }
     }
 
@@ -278,7 +368,7 @@ impl TimerMachine {
         use TimerMachineState::*;
         match &self.state {
             OnPhase(_) | FinishedPhase(_) | Finished => {
-                if trigger.check(pos, self.combat_state, &mut self.key_pressed) {
+                if trigger.check(pos, &mut self.triggers) {
                     self.do_reset().await;
                 }
             }
@@ -289,7 +379,7 @@ impl TimerMachine {
     pub async fn do_reset(&mut self) {
         let reason = format!("Reset triggered for \"{}\"", self.timer.name);
         log::info!("Reset triggered!");
-        self.combat_state = CombatState::Outside;
+        self.triggers.combat = CombatState::Outside;
         self.state_change(TimerMachineState::OnMap).await;
         self.abort_tasks(reason.clone()).await;
         let zero_s = Duration::from_secs(0);
@@ -309,28 +399,48 @@ impl TimerMachine {
             .await;
     }
 
-    async fn abort_tasks(&self, reason: String) {
+    async fn abort_tasks(&mut self, reason: String) {
         log::info!(
             "Aborting {} tasks for reason: \"{}\".",
             self.tasks.len(),
             reason
         );
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
         let reset_event = EventMapper::reset(self.timer.clone());
-        reset_event.send().await;
+        self.emit(reset_event).await;
     }
 
-    async fn start_tasks(&self, phase: &TimerFilePhase) {
+    async fn start_tasks(&mut self, phase: &TimerFilePhase, start: Instant) {
         let alerts = phase.get_alerts();
         let markers = phase.get_markers();
+        let directions = phase.get_directions();
+        let sounds = phase.get_sounds();
+        let now = Instant::now();
+        self.phase_start = Some(start);
+        self.triggers.since = Some(start);
+        // after a skip, anything that should've already played stays quiet
+        for sound in sounds.iter().filter(|s| s.start(start) >= now) {
+            let task = tokio::spawn(Self::play_sound(
+                self.audio.clone(),
+                self.timer.clone(),
+                sound.clone(),
+                start,
+            ));
+            self.tasks.push(Arc::new(task));
+        }
         let phase_state = PhaseState {
             timer: self.timer.clone(),
-            start: Instant::now(),
+            start,
             phase: phase.clone(),
             alerts,
             markers,
+            directions,
+            sounds,
         };
-        let feed_event = EventMapper::feed(phase_state.clone());
-        feed_event.send().await;
+        let feed_event = EventMapper::feed(phase_state);
+        self.emit(feed_event).await;
     }
 
     /**
@@ -343,18 +453,127 @@ impl TimerMachine {
             _ => state,
         };
         let reason = format!("Switching from state {} to {}", self.state, final_state);
+        self.record(MachineEvent::Transition {
+            from: self.state.to_string(),
+            to: final_state.to_string(),
+        });
         self.abort_tasks(reason).await;
+        self.phase_start = None;
+        self.triggers.since = Some(Instant::now());
+        self.triggers.reset_sequences();
         if let OnPhase(phase) = &final_state {
-            self.start_tasks(phase).await;
+            // restarting a phase shouldn't re-arm the action that restarted it
+            let same_phase =
+                matches!(&self.state, OnPhase(current) if current.phase == phase.phase);
+            if !same_phase {
+                self.action_states.clear();
+            }
+            self.start_tasks(phase, Instant::now()).await;
         }
         self.state = final_state;
     }
 
+    /**
+     * checks the actions of the current phase, returns true if one of them
+     * moved us out of this phase
+     */
+    async fn action_check(&mut self, phase: &TimerFilePhase, pos: Position) -> bool {
+        self.action_states.resize(phase.actions.len(), false);
+        let mut fired = Vec::new();
+        for (idx, action) in phase.actions.iter().enumerate() {
+            let result = action.trigger.check(pos, &mut self.triggers);
+            let previous = std::mem::replace(&mut self.action_states[idx], result);
+            if result && !previous {
+                fired.push(action.clone());
+            }
+        }
+        for action in fired {
+            if self.do_action(phase, &action).await {
+                return true;
+            }
+        }
+        false
+    }
+
+    async fn do_action(&mut self, phase: &TimerFilePhase, action: &TimerAction) -> bool {
+        log::info!(
+            "Action \"{}\" ({:?}) triggered for \"{}\"",
+            action.name,
+            action.kind,
+            self.timer.name
+        );
+        use {TimerActionType::*, TimerMachineState::*};
+        match action.kind {
+            SkipTime => {
+                let (Some(time), Some(start)) = (action.time, self.phase_start) else {
+                    log::warn!(
+                        "Action \"{}\" for \"{}\" has no time to skip!",
+                        action.name,
+                        self.timer.name
+                    );
+                    return false;
+                };
+                // negative times rewind, the same as skipping back in a video would
+                let start = match Duration::try_from_secs_f32(time.abs()) {
+                    Ok(skip) if time < 0.0 => start + skip,
+                    Ok(skip) => start.checked_sub(skip).unwrap_or(start),
+                    Err(_) => {
+                        log::warn!(
+                            "Action \"{}\" for \"{}\" wants to skip {time}s, which isn't a time!",
+                            action.name,
+                            self.timer.name
+                        );
+                        return false;
+                    }
+                };
+                let reason = format!("Skipping {time}s in \"{}\"", phase.name);
+                self.abort_tasks(reason).await;
+                self.start_tasks(phase, start).await;
+                false
+            }
+            JumpToPhase => {
+                let next = action
+                    .phase
+                    .as_deref()
+                    .and_then(|name| TimerFilePhase::named(self.timer.clone(), name));
+                match next {
+                    Some(next) => {
+                        self.state_change(OnPhase(next)).await;
+                        true
+                    }
+                    None => {
+                        log::warn!(
+                            "Action \"{}\" for \"{}\" wants to jump to phase {:?}, which doesn't exist!",
+                            action.name,
+                            self.timer.name,
+                            action.phase
+                        );
+                        false
+                    }
+                }
+            }
+            Reset => {
+                self.do_reset().await;
+                true
+            }
+            RestartPhase => {
+                self.state_change(OnPhase(phase.clone())).await;
+                true
+            }
+        }
+    }
+
     /**
      * tick, in comparison to state_change, runs perpetually and is used for
      * checking to see if conditions for a next phase are met
      */
     pub async fn tick(&mut self, pos: Position) {
+        self.tick_state(pos).await;
+        // combat events only get the one tick to be noticed in
+        self.triggers.combat_events.clear();
+    }
+
+    async fn tick_state(&mut self, pos: Position) {
         // It is always important to check if we have met the conditions for resetting the timer
         self.reset_check(pos).await;
 
@@ -370,7 +589,7 @@ impl TimerMachine {
             OnMap => {
                 // All timers have a start trigger and a zeroth (first) phase
                 let trigger = &self.timer.phases.first().unwrap().start;
-                if trigger.check(pos, self.combat_state, &mut self.key_pressed) {
+                if trigger.check(pos, &mut self.triggers) {
                     if let Some(phase) = TimerFilePhase::new(self.timer.clone()) {
                         self.state_change(OnPhase(phase)).await;
                     }
@@ -378,9 +597,14 @@ impl TimerMachine {
             }
             // within a phase (nth)
             OnPhase(phase) => {
+                let phase = phase.clone();
+                // actions can skip about, jump elsewhere or reset us entirely
+                if self.action_check(&phase, pos).await {
+                    return;
+                }
                 // handle the finish check
                 if let Some(trigger) = &phase.finish {
-                    if trigger.check(pos, self.combat_state, &mut self.key_pressed) {
+                    if trigger.check(pos, &mut self.triggers) {
                         self.state_change(FinishedPhase(phase.clone())).await;
                     }
                 }
@@ -389,7 +613,7 @@ impl TimerMachine {
                 // check the next phase's start trigger
                 if let Some(next_phase) = &phase.clone().next() {
                     let trigger = &next_phase.start;
-                    if trigger.check(pos, self.combat_state, &mut self.key_pressed) {
+                    if trigger.check(pos, &mut self.triggers) {
                         self.state_change(OnPhase(next_phase.clone())).await;
                     }
                 }
@@ -402,16 +626,38 @@ impl TimerMachine {
         let flag = 1u8 << idx;
         match is_release {
             false => self
-                .key_pressed
+                .triggers
+                .keys
                 .insert(TimerKeybinds::from_bits_retain(flag)),
             true => self
-                .key_pressed
+                .triggers
+                .keys
                 .remove(TimerKeybinds::from_bits_retain(flag)),
         }
     }
 
     pub fn set_combat_state(&mut self, combat_state: CombatState) {
-        self.combat_state = combat_state;
+        self.triggers.combat = combat_state;
+    }
+
+    pub fn combat_event(&mut self, info: CombatEventInfo) {
+        // if nobody's ticking us, don't hoard them forever
+        if self.triggers.combat_events.len() < 256 {
+            self.triggers.combat_events.push(info);
+        }
+    }
+
+    pub fn set_mount(&mut self, mount: Option<Mount>) {
+        self.triggers.mount = mount;
+    }
+
+    pub fn set_profession(&mut self, profession: Option<Profession>) {
+        self.triggers.profession = profession;
+    }
+
+    // for mapChange triggers, the controller tells a new machine where the player came from
+    pub fn arrived_from(&mut self, from: Option<u32>) {
+        self.triggers.arrival = Some(MapArrival { from });
     }
 
     pub fn update_on_map(&mut self, map_id: u32) {
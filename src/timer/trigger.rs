@@ -1,7 +1,9 @@
 use {
     super::TimerKeybinds,
-    crate::timer::{BlishPosition, Polytope, Position},
+    crate::timer::{BlishArea, BlishPosition, CombatEventInfo, CombatMatcher, Polytope, Position},
     serde::{Deserialize, Serialize},
+    std::collections::HashMap,
+    tokio::time::{Duration, Instant},
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -14,6 +16,9 @@ pub struct TimerTrigger {
     pub position: Option<BlishPosition>,
     pub antipode: Option<BlishPosition>,
     pub radius: Option<f32>,
+    // Taimi extension, takes priority over position, antipode and radius
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub area: Option<BlishArea>,
     #[serde(default)]
     pub require_combat: bool,
     #[serde(default)]
@@ -22,15 +27,209 @@ pub struct TimerTrigger {
     pub require_entry: bool,
     #[serde(default)]
     pub require_departure: bool,
+    /*
+     * Taimi extensions, plain bhtimer files never set these.
+     */
+    // children, for anyOf, allOf, not and sequence
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<TimerTrigger>,
+    // seconds a sequence has to complete in, from its first step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub within: Option<f32>,
+    // seconds since the phase started, for elapsed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elapsed: Option<f32>,
+    // the map we must have come from, for mapChange; any map if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_map: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mounts: Vec<Mount>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub professions: Vec<Profession>,
+    // the arcdps event to wait for, for combatEvent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub combat_event: Option<CombatMatcher>,
+    // where this is in the file, see TimerFile::number_triggers
+    #[serde(skip)]
+    pub id: usize,
 }
 
-#[derive(Debug, Clone, PartialEq, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Copy)]
+#[serde(rename_all = "camelCase")]
 pub enum CombatState {
     Outside,
     Entered,
     Exited,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Mount {
+    None,
+    Jackal,
+    Griffon,
+    Springer,
+    Skimmer,
+    Raptor,
+    RollerBeetle,
+    Warclaw,
+    Skyscale,
+    Skiff,
+    SiegeTurtle,
+}
+
+impl Mount {
+    // mumble's mount index
+    pub fn from_index(index: u32) -> Option<Self> {
+        use Mount::*;
+        Some(match index {
+            0 => None,
+            1 => Jackal,
+            2 => Griffon,
+            3 => Springer,
+            4 => Skimmer,
+            5 => Raptor,
+            6 => RollerBeetle,
+            7 => Warclaw,
+            8 => Skyscale,
+            9 => Skiff,
+            10 => SiegeTurtle,
+            _ => return Option::None,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Profession {
+    Guardian,
+    Warrior,
+    Engineer,
+    Ranger,
+    Thief,
+    Elementalist,
+    Mesmer,
+    Necromancer,
+    Revenant,
+}
+
+impl Profession {
+    // mumble identity's profession id
+    pub fn from_index(index: u32) -> Option<Self> {
+        use Profession::*;
+        Some(match index {
+            1 => Guardian,
+            2 => Warrior,
+            3 => Engineer,
+            4 => Ranger,
+            5 => Thief,
+            6 => Elementalist,
+            7 => Mesmer,
+            8 => Necromancer,
+            9 => Revenant,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MapArrival {
+    pub from: Option<u32>,
+}
+
+/*
+ * Everything a trigger might want to know about the world, owned by the
+ * machine checking it.
+ */
+#[derive(Debug, Clone)]
+pub struct TriggerState {
+    pub combat: CombatState,
+    pub keys: TimerKeybinds,
+    // what elapsed triggers count from
+    pub since: Option<Instant>,
+    // set when we arrive on the map, eaten by the first mapChange that matches
+    pub arrival: Option<MapArrival>,
+    pub mount: Option<Mount>,
+    pub profession: Option<Profession>,
+    // arcdps events since the last tick
+    pub combat_events: Vec<CombatEventInfo>,
+    // sequence progress, keyed by the id of the sequence trigger
+    sequences: HashMap<usize, (usize, Instant)>,
+}
+
+impl Default for TriggerState {
+    fn default() -> Self {
+        Self {
+            combat: CombatState::Outside,
+            keys: Default::default(),
+            since: None,
+            arrival: None,
+            mount: None,
+            profession: None,
+            combat_events: Default::default(),
+            sequences: Default::default(),
+        }
+    }
+}
+
+impl TriggerState {
+    pub fn elapsed(&self) -> Option<Duration> {
+        Some(Instant::now().saturating_duration_since(self.since?))
+    }
+
+    pub fn reset_sequences(&mut self) {
+        self.sequences.clear();
+    }
+}
+
+/*
+ * What a trigger that came out true would use up. Nothing is taken until the
+ * trigger being checked fires as a whole, so a composite that comes out false
+ * leaves the keys and the arrival for anything else waiting on them.
+ */
+#[derive(Debug, Default)]
+struct Consumed {
+    keys: TimerKeybinds,
+    arrival: bool,
+}
+
+impl Consumed {
+    fn merge(mut self, other: Consumed) -> Self {
+        self.keys.insert(other.keys);
+        self.arrival |= other.arrival;
+        self
+    }
+
+    fn apply(self, state: &mut TriggerState) {
+        state.keys.remove(self.keys);
+        if self.arrival {
+            state.arrival = None;
+        }
+    }
+}
+
+/*
+ * A sequence moving on a step, or back to the start. These stick whether or
+ * not the trigger they're in fires, along with whatever the step used up, or
+ * a held key would carry on to the next step by itself.
+ */
+#[derive(Debug)]
+struct SequenceProgress {
+    id: usize,
+    step: Option<(usize, Instant)>,
+    consumed: Consumed,
+}
+
+impl SequenceProgress {
+    fn apply(self, state: &mut TriggerState) {
+        match self.step {
+            Some(step) => state.sequences.insert(self.id, step),
+            None => state.sequences.remove(&self.id),
+        };
+        self.consumed.apply(state);
+    }
+}
+
 impl TimerTrigger {
     #[allow(dead_code)]
     pub fn position(&self) -> Option<Position> {
@@ -42,7 +241,16 @@ impl TimerTrigger {
         self.antipode.map(Into::into)
     }
 
+    // the keybind index this trigger listens for, if it's set and one of ours
+    pub fn key_index(&self) -> Option<u32> {
+        let idx = self.key_bind.as_ref()?.trim().parse::<u32>().ok()?;
+        (idx < TimerKeybinds::all().bits().count_ones()).then_some(idx)
+    }
+
     pub fn polytope(&self) -> Option<Polytope> {
+        if let Some(area) = &self.area {
+            return Some(area.polytope());
+        }
         match *self {
             Self {
                 radius: Some(radius),
@@ -63,35 +271,178 @@ impl TimerTrigger {
             _ => None,
         }
     }
-    pub fn check(&self, pos: Position, cb: CombatState, key_pressed: &mut TimerKeybinds) -> bool {
-        let shape = match self.polytope() {
-            Some(s) => s,
-            None => return false,
+    // gives this and everything below it an id, in file order, counting on from next
+    pub fn number(&mut self, next: &mut usize) {
+        self.id = *next;
+        *next += 1;
+        for child in &mut self.triggers {
+            child.number(next);
+        }
+    }
+
+    fn key_check(&self, keys: &TimerKeybinds) -> Option<Consumed> {
+        // the linter shouts about binds that aren't ours, no need to take the game down with us
+        let flag = TimerKeybinds::from_bits_retain(1u8 << self.key_index()?);
+        keys.contains(flag.clone()).then_some(Consumed {
+            keys: flag,
+            ..Default::default()
+        })
+    }
+
+    fn sequence_check(
+        &self,
+        pos: Position,
+        state: &TriggerState,
+        progress: &mut Vec<SequenceProgress>,
+    ) -> bool {
+        let now = Instant::now();
+        let (mut step, started) = state.sequences.get(&self.id).copied().unwrap_or((0, now));
+        // a window that isn't a duration, negative say, is no window at all
+        let window = self
+            .within
+            .and_then(|within| Duration::try_from_secs_f32(within).ok());
+        if let Some(window) = window {
+            if step > 0 && now.saturating_duration_since(started) > window {
+                step = 0;
+            }
+        }
+        let Some(trigger) = self.triggers.get(step) else {
+            return false;
         };
+        let Some(consumed) = trigger.evaluate(pos, state, progress) else {
+            progress.push(SequenceProgress {
+                id: self.id,
+                step: (step > 0).then_some((step, started)),
+                consumed: Default::default(),
+            });
+            return false;
+        };
+        let started = if step == 0 { now } else { started };
+        let done = step + 1 == self.triggers.len();
+        progress.push(SequenceProgress {
+            id: self.id,
+            step: (!done).then_some((step + 1, started)),
+            consumed,
+        });
+        done
+    }
+
+    fn map_change_check(&self, state: &TriggerState) -> Option<Consumed> {
+        match state.arrival {
+            Some(MapArrival { from }) if self.from_map.is_none() || self.from_map == from => {
+                Some(Consumed {
+                    arrival: true,
+                    ..Default::default()
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn check(&self, pos: Position, state: &mut TriggerState) -> bool {
+        let mut progress = Vec::new();
+        let consumed = self.evaluate(pos, state, &mut progress);
+        for step in progress {
+            step.apply(state);
+        }
+        match consumed {
+            Some(consumed) => {
+                consumed.apply(state);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /**
+     * whether this trigger holds, and if it does, what it'd use up. Every
+     * child of a composite gets looked at against the same state, so the
+     * order they're written in doesn't matter.
+     */
+    fn evaluate(
+        &self,
+        pos: Position,
+        state: &TriggerState,
+        progress: &mut Vec<SequenceProgress>,
+    ) -> Option<Consumed> {
         use TimerTriggerType::*;
-        let key_check = match self.kind {
-            Location => true,
-            Key => {
-                if let Some(key_bind) = &self.key_bind {
-                    let idx = key_bind.parse::<usize>().unwrap();
-                    let flag = 1u8 << idx;
-                    let flaggy = TimerKeybinds::from_bits_retain(flag);
-                    let result = key_pressed.contains(flaggy.clone());
-                    key_pressed.remove(flaggy);
-                    result
-                } else {
-                    unreachable!("keybind not specified for a key type phase trigger");
+        let nothing = || Some(Consumed::default());
+        let kind_check = match self.kind {
+            Location => nothing(),
+            Key => self.key_check(&state.keys),
+            AnyOf | AllOf | Not => {
+                let results: Vec<_> = self
+                    .triggers
+                    .iter()
+                    .map(|t| t.evaluate(pos, state, progress))
+                    .collect();
+                let held = results.iter().filter(|r| r.is_some()).count();
+                let fired = match self.kind {
+                    AnyOf => held > 0,
+                    AllOf => held == results.len(),
+                    _ => held == 0,
+                };
+                match (fired, &self.kind) {
+                    (false, _) => None,
+                    // whatever a not's children would use up, they aren't
+                    (true, Not) => nothing(),
+                    (true, _) => Some(
+                        results
+                            .into_iter()
+                            .flatten()
+                            .fold(Consumed::default(), Consumed::merge),
+                    ),
                 }
             }
+            Sequence => self
+                .sequence_check(pos, state, progress)
+                .then(Consumed::default),
+            Elapsed => match (self.elapsed, state.elapsed()) {
+                (Some(target), Some(elapsed)) => {
+                    (elapsed.as_secs_f32() >= target).then(Consumed::default)
+                }
+                _ => None,
+            },
+            MapChange => self.map_change_check(state),
+            Mount => state
+                .mount
+                .is_some_and(|m| self.mounts.contains(&m))
+                .then(Consumed::default),
+            Profession => state
+                .profession
+                .is_some_and(|p| self.professions.contains(&p))
+                .then(Consumed::default),
+            CombatEvent => self
+                .combat_event
+                .as_ref()
+                .is_some_and(|m| state.combat_events.iter().any(|e| m.matches(e)))
+                .then(Consumed::default),
         };
-        let position_check = shape.point_is_within(pos);
+        // plain bhtimer triggers always need an area, ours only if they ask about entry or departure
+        let position_check = self.polytope().map(|shape| shape.point_is_within(pos));
+        if position_check.is_none() && self.kind.requires_area() {
+            return None;
+        }
+        let cb = state.combat;
         let combat_entered_check = !self.require_combat || cb == CombatState::Entered;
         let combat_exited_check = !self.require_out_of_combat || cb == CombatState::Exited;
         let combat_check = combat_entered_check && combat_exited_check;
-        let entry_check = !self.require_entry || position_check;
-        let departure_check = !self.require_departure || !position_check;
-        let complete = entry_check && departure_check && combat_check && key_check;
-        complete
+        let area_check =
+            Self::area_check(position_check, self.require_entry, self.require_departure);
+        match area_check && combat_check {
+            true => kind_check,
+            false => None,
+        }
+    }
+
+    /**
+     * whether being inside (or not, or not knowing) an area satisfies the
+     * entry and departure requirements; marker sets share this too
+     */
+    pub fn area_check(inside: Option<bool>, require_entry: bool, require_departure: bool) -> bool {
+        let entry_check = !require_entry || inside == Some(true);
+        let departure_check = !require_departure || inside == Some(false);
+        entry_check && departure_check
     }
 }
 
@@ -100,6 +451,25 @@ impl TimerTrigger {
 pub enum TimerTriggerType {
     Location,
     Key,
+    AnyOf,
+    AllOf,
+    Not,
+    Sequence,
+    Elapsed,
+    MapChange,
+    Mount,
+    Profession,
+    CombatEvent,
+}
+
+impl TimerTriggerType {
+    pub fn requires_area(&self) -> bool {
+        matches!(self, Self::Location | Self::Key)
+    }
+
+    pub fn is_composite(&self) -> bool {
+        matches!(self, Self::AnyOf | Self::AllOf | Self::Not | Self::Sequence)
+    }
 }
 
 impl Default for TimerTriggerType {
@@ -107,3 +477,93 @@ impl Default for TimerTriggerType {
         Self::Location
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{TimerKeybinds, TimerTrigger, TriggerState},
+        crate::timer::Position,
+        glam::Vec3,
+        serde_json::json,
+    };
+
+    const HERE: Position = Position::Vec3(Vec3::ZERO);
+
+    fn key(idx: u32) -> serde_json::Value {
+        json!({ "type": "key", "keyBind": idx.to_string(), "position": [0, 0, 0], "radius": 10 })
+    }
+
+    fn trigger(value: serde_json::Value) -> TimerTrigger {
+        let mut trigger: TimerTrigger = serde_json::from_value(value).unwrap();
+        trigger.number(&mut 0);
+        trigger
+    }
+
+    fn pressed(idxs: &[u32]) -> TriggerState {
+        let mut state = TriggerState::default();
+        for idx in idxs {
+            state.keys.insert(TimerKeybinds::from_bits_retain(1 << idx));
+        }
+        state
+    }
+
+    #[test]
+    fn false_composite_leaves_keys_alone() {
+        let all_of = trigger(json!({
+            "type": "allOf",
+            "triggers": [
+                key(0),
+                { "position": [500, 500, 500], "radius": 1, "requireEntry": true }
+            ]
+        }));
+        let mut state = pressed(&[0]);
+        assert!(!all_of.check(HERE, &mut state));
+        assert!(state.keys.contains(TimerKeybinds::A));
+    }
+
+    #[test]
+    fn children_see_the_same_state() {
+        let all_of = trigger(json!({ "type": "allOf", "triggers": [key(0), key(0)] }));
+        let mut state = pressed(&[0]);
+        assert!(all_of.check(HERE, &mut state));
+        assert!(state.keys.is_empty());
+    }
+
+    #[test]
+    fn not_uses_nothing_up() {
+        let any_of = trigger(json!({
+            "type": "anyOf",
+            "triggers": [{ "type": "not", "triggers": [key(1)] }, key(0)]
+        }));
+        let mut state = pressed(&[0]);
+        assert!(any_of.check(HERE, &mut state));
+        assert!(state.keys.is_empty());
+    }
+
+    #[test]
+    fn sequence_progress_survives_copies() {
+        let sequence = trigger(json!({ "type": "sequence", "triggers": [key(0), key(0)] }));
+        let mut state = pressed(&[0]);
+        assert!(!sequence.check(HERE, &mut state));
+        // the step's key went with it, so the second step wants a fresh press
+        assert!(!sequence.clone().check(HERE, &mut state));
+        state.keys.insert(TimerKeybinds::A);
+        assert!(sequence.clone().check(HERE, &mut state));
+    }
+
+    #[test]
+    fn sequences_without_a_real_window() {
+        // negative, and too long to be a Duration
+        for within in [-1.0, 1e30] {
+            let sequence = trigger(json!({
+                "type": "sequence",
+                "within": within,
+                "triggers": [key(0), key(1)]
+            }));
+            let mut state = pressed(&[0]);
+            assert!(!sequence.check(HERE, &mut state), "within {within}");
+            state.keys.insert(TimerKeybinds::B);
+            assert!(sequence.check(HERE, &mut state), "within {within}");
+        }
+    }
+}
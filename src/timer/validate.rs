@@ -0,0 +1,715 @@
+use {
+    crate::timer::{
+        BlishAlert, BlishArea, TimerActionType, TimerFile, TimerKeybinds, TimerPack, TimerPhase,
+        TimerTrigger, TimerTriggerType, MANIFEST_NAME,
+    },
+    serde::Serialize,
+    std::{
+        collections::HashSet,
+        fmt::Display,
+        path::{Path, PathBuf},
+    },
+    strum_macros::Display,
+    tokio::fs::try_exists,
+};
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tga", "bmp", "dds"];
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Display)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    #[strum(to_string = "warning")]
+    Warning,
+    #[strum(to_string = "error")]
+    Error,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    // e.g. $.phases[1].start.keyBind
+    pub path: String,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.path, self.message)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Default)]
+struct Linter {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter {
+    fn error<S: Into<String>>(&mut self, path: S, message: String) {
+        self.push(Severity::Error, path, message);
+    }
+
+    fn warning<S: Into<String>>(&mut self, path: S, message: String) {
+        self.push(Severity::Warning, path, message);
+    }
+
+    fn push<S: Into<String>>(&mut self, severity: Severity, path: S, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            path: path.into(),
+            message,
+        });
+    }
+
+    fn area(&mut self, path: &str, area: &BlishArea) {
+        match area {
+            BlishArea::Sphere { radius, .. } | BlishArea::Cylinder { radius, .. }
+                if *radius <= 0.0 =>
+            {
+                self.error(
+                    format!("{path}.radius"),
+                    format!("radius {radius} is not positive"),
+                )
+            }
+            BlishArea::Polygon { points, .. } if points.len() < 3 => self.error(
+                format!("{path}.points"),
+                format!("polygon has {} points, it needs at least 3", points.len()),
+            ),
+            BlishArea::Cylinder {
+                bottom: Some(bottom),
+                top: Some(top),
+                ..
+            }
+            | BlishArea::Polygon {
+                bottom: Some(bottom),
+                top: Some(top),
+                ..
+            } if bottom > top => self.error(
+                path,
+                format!("bottom {bottom} is above top {top}, nothing can be inside"),
+            ),
+            _ => (),
+        }
+    }
+
+    fn trigger(&mut self, path: &str, trigger: &TimerTrigger) {
+        if let Some(area) = &trigger.area {
+            self.area(&format!("{path}.area"), area);
+        }
+        if trigger.polytope().is_none() && trigger.kind.requires_area() {
+            self.error(
+                path,
+                "trigger has no area, it needs a position with either a radius or an antipode"
+                    .to_string(),
+            );
+        }
+        if trigger.polytope().is_none()
+            && !trigger.kind.requires_area()
+            && (trigger.require_entry || trigger.require_departure)
+        {
+            self.error(
+                path,
+                "trigger requires entry or departure but has no area to enter or leave".to_string(),
+            );
+        }
+        if trigger.kind.is_composite() && trigger.triggers.is_empty() {
+            self.error(
+                format!("{path}.triggers"),
+                format!("{:?} trigger has no triggers to combine", trigger.kind),
+            );
+        }
+        for (idx, child) in trigger.triggers.iter().enumerate() {
+            self.trigger(&format!("{path}.triggers[{idx}]"), child);
+        }
+        for (field, seconds) in [("within", trigger.within), ("elapsed", trigger.elapsed)] {
+            match seconds {
+                Some(seconds) if seconds < 0.0 || seconds.is_nan() => self.error(
+                    format!("{path}.{field}"),
+                    format!("{field} {seconds} isn't a number of seconds"),
+                ),
+                _ => (),
+            }
+        }
+        match trigger.kind {
+            TimerTriggerType::Elapsed if trigger.elapsed.is_none() => self.error(
+                format!("{path}.elapsed"),
+                "elapsed trigger has no elapsed time".to_string(),
+            ),
+            TimerTriggerType::Mount if trigger.mounts.is_empty() => self.error(
+                format!("{path}.mounts"),
+                "mount trigger has no mounts".to_string(),
+            ),
+            TimerTriggerType::Profession if trigger.professions.is_empty() => self.error(
+                format!("{path}.professions"),
+                "profession trigger has no professions".to_string(),
+            ),
+            TimerTriggerType::CombatEvent if trigger.combat_event.is_none() => self.error(
+                format!("{path}.combatEvent"),
+                "combatEvent trigger has nothing to match against".to_string(),
+            ),
+            _ => (),
+        }
+        if trigger.kind == TimerTriggerType::Key {
+            let max = TimerKeybinds::all().bits().count_ones() - 1;
+            match &trigger.key_bind {
+                None => self.error(
+                    format!("{path}.keyBind"),
+                    "key trigger has no keyBind".to_string(),
+                ),
+                Some(bind) => match bind.trim().parse::<u32>() {
+                    Err(_) => self.error(
+                        format!("{path}.keyBind"),
+                        format!("keyBind {bind:?} isn't a number"),
+                    ),
+                    Ok(idx) if idx > max => self.error(
+                        format!("{path}.keyBind"),
+                        format!("keyBind {idx} is out of range, it must be between 0 and {max}"),
+                    ),
+                    Ok(_) => (),
+                },
+            }
+        }
+        if trigger.require_combat && trigger.require_out_of_combat {
+            self.warning(
+                path,
+                "trigger requires both being in and out of combat, so it'll never fire".to_string(),
+            );
+        }
+        if trigger.require_entry && trigger.require_departure {
+            self.warning(
+                path,
+                "trigger requires both entry and departure, so it'll never fire".to_string(),
+            );
+        }
+    }
+
+    fn alert(&mut self, path: &str, alert: &BlishAlert) {
+        if alert.alert.is_none() && alert.warning.is_none() {
+            self.warning(path, "alert has neither alert nor warning text".to_string());
+        }
+        if alert.alert.is_some() && alert.alert_duration.is_none() {
+            self.warning(
+                path,
+                "alert has text but no alertDuration, it won't be shown".to_string(),
+            );
+        }
+        if alert.warning.is_some() && alert.warning_duration.is_none() {
+            self.warning(
+                path,
+                "warning has text but no warningDuration, it won't be shown".to_string(),
+            );
+        }
+        let durations = [
+            ("alertDuration", alert.alert_duration),
+            ("warningDuration", alert.warning_duration),
+        ];
+        for (idx, &timestamp) in alert.timestamps.iter().enumerate() {
+            for (field, duration) in durations {
+                if let Some(duration) = duration {
+                    if timestamp < duration {
+                        self.warning(
+                            format!("{path}.timestamps[{idx}]"),
+                            format!("timestamp {timestamp}s is shorter than the {field} of {duration}s, it'll be cut short"),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn phase(&mut self, path: &str, phase: &TimerPhase, timer: &TimerFile) {
+        self.trigger(&format!("{path}.start"), &phase.start);
+        if let Some(finish) = &phase.finish {
+            self.trigger(&format!("{path}.finish"), finish);
+        }
+        for (idx, alert) in phase.alerts.iter().enumerate() {
+            self.alert(&format!("{path}.alerts[{idx}]"), alert);
+        }
+        for (idx, action) in phase.actions.iter().enumerate() {
+            let action_path = format!("{path}.actions[{idx}]");
+            self.trigger(&format!("{action_path}.trigger"), &action.trigger);
+            use TimerActionType::*;
+            match action.kind {
+                SkipTime => match action.time {
+                    None => self.error(
+                        format!("{action_path}.time"),
+                        "skipTime action has no time to skip".to_string(),
+                    ),
+                    // negative is fine, that goes back in time
+                    Some(time) if !time.is_finite() => self.error(
+                        format!("{action_path}.time"),
+                        format!("time {time} isn't a number of seconds"),
+                    ),
+                    Some(_) => (),
+                },
+                JumpToPhase => match &action.phase {
+                    None => self.error(
+                        format!("{action_path}.phase"),
+                        "jumpToPhase action has no phase".to_string(),
+                    ),
+                    Some(name) if !timer.phases.iter().any(|p| &p.name == name) => self.error(
+                        format!("{action_path}.phase"),
+                        format!("there's no phase named {name:?} to jump to"),
+                    ),
+                    Some(_) => (),
+                },
+                _ => (),
+            }
+        }
+    }
+}
+
+// a file a timer points at, and where in the timer it's pointed at from
+struct Reference {
+    path: String,
+    file: PathBuf,
+    what: &'static str,
+}
+
+impl Reference {
+    fn missing(&self) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Warning,
+            path: self.path.clone(),
+            message: format!("{} {:?} doesn't exist", self.what, self.file),
+        }
+    }
+}
+
+impl TimerFile {
+    /**
+     * Checks the parts of a timer that deserialize just fine but will
+     * misbehave at runtime. Nothing here touches the disk, the files it
+     * points at are checked by missing_files.
+     */
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut linter = Linter::default();
+        linter.trigger("$.reset", &self.reset);
+        if self.phases.is_empty() {
+            linter.error("$.phases", "timer has no phases".to_string());
+        }
+        for (idx, phase) in self.phases.iter().enumerate() {
+            linter.phase(&format!("$.phases[{idx}]"), phase, self);
+        }
+        linter.diagnostics
+    }
+
+    // only once the timer knows where it lives
+    fn references(&self) -> Vec<Reference> {
+        let Some(base) = self.path.as_deref().and_then(Path::parent) else {
+            return Vec::new();
+        };
+        // icons can come from the pack's shared directory instead
+        let icon_base = self.icon_base().unwrap_or(base);
+        let mut references = vec![Reference {
+            path: "$.icon".to_string(),
+            file: self.icon.to_path(icon_base),
+            what: "icon",
+        }];
+        for (pidx, phase) in self.phases.iter().enumerate() {
+            let path = format!("$.phases[{pidx}]");
+            for (idx, alert) in phase.alerts.iter().enumerate() {
+                if let Some(icon) = &alert.icon {
+                    references.push(Reference {
+                        path: format!("{path}.alerts[{idx}].icon"),
+                        file: icon.to_path(icon_base),
+                        what: "icon",
+                    });
+                }
+            }
+            for (idx, marker) in phase.markers.0.iter().enumerate() {
+                references.push(Reference {
+                    path: format!("{path}.markers[{idx}].texture"),
+                    file: base.join(&marker.texture),
+                    what: "texture",
+                });
+            }
+            for (idx, direction) in phase.directions.iter().enumerate() {
+                references.push(Reference {
+                    path: format!("{path}.directions[{idx}].texture"),
+                    file: direction.texture.to_path(base),
+                    what: "texture",
+                });
+            }
+            for (idx, sound) in phase.sounds.iter().enumerate() {
+                references.push(Reference {
+                    path: format!("{path}.sounds[{idx}].file"),
+                    file: sound.file.to_path(base),
+                    what: "sound",
+                });
+            }
+        }
+        references
+    }
+
+    // every file this timer points at, resolved against where it lives
+    pub fn referenced_files(&self) -> Vec<PathBuf> {
+        self.references().into_iter().map(|r| r.file).collect()
+    }
+
+    // for the linter, which isn't in any rush
+    pub fn missing_files(&self) -> Vec<Diagnostic> {
+        self.references()
+            .iter()
+            .filter(|r| !r.file.exists())
+            .map(Reference::missing)
+            .collect()
+    }
+
+    // for loading, where blocking the runtime on the disk isn't on
+    pub async fn missing_files_async(&self) -> Vec<Diagnostic> {
+        let mut missing = Vec::new();
+        for reference in self.references() {
+            if !matches!(try_exists(&reference.file).await, Ok(true)) {
+                missing.push(reference.missing());
+            }
+        }
+        missing
+    }
+}
+
+/**
+ * Parses and validates a single timer file. Parse failures come back as a
+ * single error with the JSON path serde got stuck at.
+ */
+pub fn lint_file(path: &Path) -> (Option<TimerFile>, FileReport) {
+    lint_pack_file(path, None)
+}
+
+fn lint_pack_file(path: &Path, pack: Option<&TimerPack>) -> (Option<TimerFile>, FileReport) {
+    let mut linter = Linter::default();
+    let timer = match std::fs::read_to_string(path) {
+        Ok(mut data) => match json_strip_comments::strip(&mut data) {
+            Ok(()) => {
+                let deserializer = &mut serde_json::Deserializer::from_str(&data);
+                match serde_path_to_error::deserialize::<_, TimerFile>(deserializer) {
+                    Ok(mut timer) => {
+                        timer.path = Some(path.to_path_buf());
+                        if let Some(pack) = pack {
+                            // as when loading, icons beside the timer win over the pack's
+                            let beside = path
+                                .parent()
+                                .is_some_and(|dir| timer.icon.to_path(dir).exists());
+                            if !beside {
+                                timer.icon_dir = pack.icons_dir();
+                            }
+                        }
+                        linter.diagnostics.extend(timer.validate());
+                        linter.diagnostics.extend(timer.missing_files());
+                        Some(timer)
+                    }
+                    Err(err) => {
+                        let json_path = match err.path().to_string().as_str() {
+                            "." => "$".to_string(),
+                            p => format!("$.{p}"),
+                        };
+                        linter.error(json_path, err.into_inner().to_string());
+                        None
+                    }
+                }
+            }
+            Err(err) => {
+                linter.error("$", format!("couldn't strip comments: {err}"));
+                None
+            }
+        },
+        Err(err) => {
+            linter.error("$", format!("couldn't read file: {err}"));
+            None
+        }
+    };
+    let report = FileReport {
+        path: path.to_path_buf(),
+        diagnostics: linter.diagnostics,
+    };
+    (timer, report)
+}
+
+/**
+ * Lints every timer below a directory, plus a report for each image in
+ * there that no timer uses.
+ */
+pub fn lint_dir(dir: &Path) -> anyhow::Result<Vec<FileReport>> {
+    let mut reports = Vec::new();
+    let pack = match TimerPack::open_blocking(dir) {
+        Ok(pack) => pack,
+        Err(err) => {
+            reports.push(FileReport {
+                path: dir.join(MANIFEST_NAME),
+                diagnostics: vec![Diagnostic {
+                    severity: Severity::Error,
+                    path: "$".to_string(),
+                    message: err.to_string(),
+                }],
+            });
+            TimerPack::default()
+        }
+    };
+    let mut referenced = HashSet::new();
+    for path in TimerFile::get_paths(dir)? {
+        let path = path?;
+        let (timer, report) = lint_pack_file(&path, Some(&pack));
+        if let Some(timer) = timer {
+            referenced.extend(
+                timer
+                    .referenced_files()
+                    .into_iter()
+                    .map(|f| f.canonicalize().unwrap_or(f)),
+            );
+        }
+        reports.push(report);
+    }
+    let image_glob = dir.join("**/*");
+    let image_glob = image_glob
+        .to_str()
+        .ok_or_else(|| anyhow::anyhow!("Lint path glob unparseable for {dir:?}"))?;
+    for path in glob::glob(image_glob)? {
+        let path = path?;
+        let is_image = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+        if !is_image {
+            continue;
+        }
+        let canonical = path.canonicalize().unwrap_or(path.clone());
+        if !referenced.contains(&canonical) {
+            reports.push(FileReport {
+                path,
+                diagnostics: vec![Diagnostic {
+                    severity: Severity::Warning,
+                    path: "$".to_string(),
+                    message: "image isn't referenced by any timer".to_string(),
+                }],
+            });
+        }
+    }
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{lint_dir, lint_file, Severity},
+        crate::timer::{TimerFile, MANIFEST_NAME},
+        serde_json::{json, Value},
+        std::fs::{create_dir_all, write},
+    };
+
+    // a timer with nothing for the linter to say about it
+    fn timer() -> Value {
+        json!({
+            "id": "lint-test",
+            "name": "Lint test",
+            "category": "Tests",
+            "description": "",
+            "author": "",
+            "icon": "icon.png",
+            "map": 1,
+            "reset": { "position": [0, 0, 0], "radius": 50, "requireDeparture": true },
+            "phases": [{
+                "name": "Only",
+                "start": { "type": "key", "keyBind": "1", "position": [0, 0, 0], "radius": 10 },
+                "alerts": [{ "alert": "Soon", "alertDuration": 5, "timestamps": [10] }],
+                "sounds": [{ "file": "beep.wav", "timestamps": [10] }]
+            }]
+        })
+    }
+
+    fn lint(timer: Value) -> Vec<(Severity, String)> {
+        let timer: TimerFile = serde_json::from_value(timer).unwrap();
+        timer
+            .validate()
+            .into_iter()
+            .map(|d| (d.severity, d.path))
+            .collect()
+    }
+
+    #[test]
+    fn clean_timer() {
+        assert_eq!(lint(timer()), vec![]);
+    }
+
+    #[test]
+    fn missing_polytope() {
+        let mut timer = timer();
+        timer["reset"] = json!({ "position": [0, 0, 0] });
+        assert_eq!(lint(timer), vec![(Severity::Error, "$.reset".to_string())]);
+    }
+
+    #[test]
+    fn key_without_bind() {
+        let mut timer = timer();
+        timer["phases"][0]["start"]
+            .as_object_mut()
+            .unwrap()
+            .remove("keyBind");
+        assert_eq!(
+            lint(timer),
+            vec![(Severity::Error, "$.phases[0].start.keyBind".to_string())]
+        );
+    }
+
+    #[test]
+    fn key_bind_out_of_range() {
+        for bind in ["5", "nope"] {
+            let mut timer = timer();
+            timer["phases"][0]["start"]["keyBind"] = json!(bind);
+            assert_eq!(
+                lint(timer),
+                vec![(Severity::Error, "$.phases[0].start.keyBind".to_string())],
+                "keyBind {bind}"
+            );
+        }
+        let mut timer = timer();
+        timer["phases"][0]["start"]["keyBind"] = json!("4");
+        assert_eq!(lint(timer), vec![]);
+    }
+
+    #[test]
+    fn alert_shorter_than_duration() {
+        let mut timer = timer();
+        timer["phases"][0]["alerts"][0]["timestamps"] = json!([10, 3]);
+        assert_eq!(
+            lint(timer),
+            vec![(
+                Severity::Warning,
+                "$.phases[0].alerts[0].timestamps[1]".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn no_phases() {
+        let mut timer = timer();
+        timer["phases"] = json!([]);
+        assert_eq!(lint(timer), vec![(Severity::Error, "$.phases".to_string())]);
+    }
+
+    #[test]
+    fn parse_errors_have_a_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.bhtimer");
+        let mut timer = timer();
+        timer["reset"]["radius"] = json!("far");
+        write(&path, timer.to_string()).unwrap();
+        let (parsed, report) = lint_file(&path);
+        assert!(parsed.is_none());
+        let paths: Vec<_> = report.diagnostics.iter().map(|d| &d.path[..]).collect();
+        assert_eq!(paths, vec!["$.reset.radius"]);
+    }
+
+    #[test]
+    fn missing_and_unreferenced_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path().join("timer.bhtimer"), timer().to_string()).unwrap();
+        write(dir.path().join("icon.png"), b"").unwrap();
+        write(dir.path().join("stray.png"), b"").unwrap();
+        let reports = lint_dir(dir.path()).unwrap();
+        let mut found: Vec<_> = reports
+            .iter()
+            .flat_map(|r| {
+                let file = r.path.file_name().unwrap().to_string_lossy().to_string();
+                r.diagnostics
+                    .iter()
+                    .map(move |d| (file.clone(), d.severity, d.path.clone()))
+            })
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                ("stray.png".to_string(), Severity::Warning, "$".to_string()),
+                (
+                    "timer.bhtimer".to_string(),
+                    Severity::Warning,
+                    "$.phases[0].sounds[0].file".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_files_async_agrees() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path().join("icon.png"), b"").unwrap();
+        let mut timer: TimerFile = serde_json::from_value(timer()).unwrap();
+        timer.path = Some(dir.path().join("timer.bhtimer"));
+        let sync: Vec<_> = timer.missing_files().into_iter().map(|d| d.path).collect();
+        let not: Vec<_> = timer
+            .missing_files_async()
+            .await
+            .into_iter()
+            .map(|d| d.path)
+            .collect();
+        assert_eq!(sync, vec!["$.phases[0].sounds[0].file".to_string()]);
+        assert_eq!(sync, not);
+    }
+
+    #[test]
+    fn shared_pack_icons() {
+        let dir = tempfile::tempdir().unwrap();
+        create_dir_all(dir.path().join("timers")).unwrap();
+        create_dir_all(dir.path().join("icons")).unwrap();
+        write(dir.path().join(MANIFEST_NAME), "icons = \"icons\"\n").unwrap();
+        let mut timer = timer();
+        timer["phases"][0]["alerts"][0]["icon"] = json!("alert.png");
+        timer["phases"][0]["sounds"] = json!([]);
+        write(dir.path().join("timers/timer.bhtimer"), timer.to_string()).unwrap();
+        write(dir.path().join("icons/icon.png"), b"").unwrap();
+        write(dir.path().join("icons/alert.png"), b"").unwrap();
+        let reports = lint_dir(dir.path()).unwrap();
+        let diagnostics: Vec<_> = reports.iter().flat_map(|r| &r.diagnostics).collect();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+    }
+
+    #[test]
+    fn negative_seconds() {
+        let mut timer = timer();
+        timer["phases"][0]["start"] = json!({
+            "type": "sequence",
+            "within": -2,
+            "triggers": [{ "type": "elapsed", "elapsed": -5 }]
+        });
+        assert_eq!(
+            lint(timer),
+            vec![
+                (
+                    Severity::Error,
+                    "$.phases[0].start.triggers[0].elapsed".to_string()
+                ),
+                (Severity::Error, "$.phases[0].start.within".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn skip_times() {
+        let lint_skip = |time: Value| {
+            let mut timer = timer();
+            timer["phases"][0]["actions"] = json!([{
+                "name": "skip",
+                "type": "skipTime",
+                "time": time,
+                "trigger": { "position": [0, 0, 0], "radius": 10 }
+            }]);
+            lint(timer)
+        };
+        assert_eq!(lint_skip(json!(10)), vec![]);
+        assert_eq!(lint_skip(json!(-5)), vec![]);
+        // too big for an f32, so it comes out infinite
+        assert_eq!(
+            lint_skip(json!(1e39)),
+            vec![(Severity::Error, "$.phases[0].actions[0].time".to_string())]
+        );
+        assert_eq!(
+            lint_skip(Value::Null),
+            vec![(Severity::Error, "$.phases[0].actions[0].time".to_string())]
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use {
+    notify::{
+        event::{EventKind, ModifyKind},
+        recommended_watcher, Event, RecommendedWatcher, RecursiveMode, Watcher,
+    },
+    std::{
+        collections::HashSet,
+        path::{Path, PathBuf},
+        sync::Mutex,
+    },
+};
+
+/*
+ * Watches the directories timer files live in and reports which .bhtimer
+ * files were touched. It doesn't debounce anything, editors love to write a
+ * file several times per save, so whoever receives the paths should wait for
+ * things to settle before reloading.
+ */
+#[derive(Debug)]
+pub struct TimerWatcher {
+    watcher: Mutex<RecommendedWatcher>,
+    watched: Mutex<HashSet<PathBuf>>,
+}
+
+impl TimerWatcher {
+    pub fn new<F>(on_change: F) -> anyhow::Result<Self>
+    where
+        F: Fn(Vec<PathBuf>) + Send + 'static,
+    {
+        let watcher = recommended_watcher(move |res: notify::Result<Event>| match res {
+            Ok(event) => {
+                let paths = Self::changed_timer_files(&event);
+                if !paths.is_empty() {
+                    on_change(paths);
+                }
+            }
+            Err(err) => log::error!("Timer watcher error: {err}"),
+        })?;
+        Ok(Self {
+            watcher: Mutex::new(watcher),
+            watched: Default::default(),
+        })
+    }
+
+    fn changed_timer_files(event: &Event) -> Vec<PathBuf> {
+        use EventKind::*;
+        match event.kind {
+            Create(_) | Remove(_) | Modify(ModifyKind::Data(_)) | Modify(ModifyKind::Name(_)) => (),
+            // some platforms only ever give us Any, so take it rather than miss saves
+            Modify(ModifyKind::Any) | Any => (),
+            _ => return Default::default(),
+        }
+        event
+            .paths
+            .iter()
+            .filter(|p| Self::is_timer_file(p))
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_timer_file(path: &Path) -> bool {
+        path.extension().is_some_and(|ext| ext == "bhtimer")
+    }
+
+    /*
+     * The watcher hands back resolved paths, which needn't match how the file
+     * was reached when it was loaded, through a symlink or a "..", say. The
+     * file itself may be gone by now, so it's the directory that's resolved.
+     */
+    pub fn canonical(path: &Path) -> PathBuf {
+        match (path.parent(), path.file_name()) {
+            (Some(dir), Some(name)) => match dir.canonicalize() {
+                Ok(dir) => dir.join(name),
+                Err(_) => path.to_path_buf(),
+            },
+            _ => path.to_path_buf(),
+        }
+    }
+
+    /*
+     * Makes the set of watched directories exactly `dirs`.
+     *
+     * Everything is rewatched rather than diffed; installing a source removes
+     * and recreates its directory, which quietly kills the old watch.
+     */
+    pub fn watch_dirs<I: IntoIterator<Item = PathBuf>>(&self, dirs: I) {
+        let mut watcher = self.watcher.lock().unwrap();
+        let mut watched = self.watched.lock().unwrap();
+        for dir in watched.drain() {
+            let _ = watcher.unwatch(&dir);
+        }
+        for dir in dirs {
+            if !dir.exists() {
+                log::debug!("Not watching {dir:?} for timer changes, it doesn't exist.");
+                continue;
+            }
+            match watcher.watch(&dir, RecursiveMode::Recursive) {
+                Ok(()) => {
+                    log::debug!("Watching {dir:?} for timer changes.");
+                    watched.insert(dir);
+                }
+                Err(err) => log::error!("Couldn't watch {dir:?} for timer changes: {err}"),
+            }
+        }
+    }
+}